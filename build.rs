@@ -0,0 +1,32 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+/// Passes the commit gcz is built from and the target triple on to `gcz --version`.
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GCZ_BUILD_COMMIT={}", commit);
+    println!(
+        "cargo:rustc-env=GCZ_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+
+    // outside a checkout, as when built from crates.io, there is nothing to watch
+    if let Ok(head) = fs::read_to_string(".git/HEAD") {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        let branch = head
+            .trim()
+            .strip_prefix("ref: ")
+            .map(|r| format!(".git/{}", r));
+        // a packed ref has no file of its own
+        if let Some(branch) = branch.filter(|path| fs::metadata(path).is_ok()) {
+            println!("cargo:rerun-if-changed={}", branch);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use crate::GczError;
+use std::fs;
+use toml_edit::{Document, Item};
+
+/// Answers to the wizard's prompts, read from `--answers <file>` so tools can drive gcz
+/// without a terminal:
+///
+/// ```toml
+/// type = "feat"
+/// scope = "api"
+/// subject = "add the export endpoint"
+/// body = "Exports run in the background."
+/// breaking = "exports replace the old download links"
+/// footers = ["Refs: #123"]
+/// ```
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers {
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub subject: String,
+    pub body: Option<String>,
+    /// `breaking = true` marks the header with `!`; a string also explains it in a
+    /// `BREAKING CHANGE:` footer.
+    pub breaking: Option<Breaking>,
+    pub footers: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Breaking {
+    Marked,
+    Explained(String),
+}
+
+impl Answers {
+    /// The message after the type prefix: the subject, then the body and footers, each
+    /// after a blank line.
+    pub fn text(&self) -> String {
+        let mut text = self.subject.trim().to_string();
+        if let Some(body) = self.body.as_deref().filter(|body| !body.trim().is_empty()) {
+            text.push_str("\n\n");
+            text.push_str(body.trim());
+        }
+        let mut footers = Vec::new();
+        if let Some(Breaking::Explained(reason)) = &self.breaking {
+            footers.push(format!("BREAKING CHANGE: {}", reason.trim()));
+        }
+        footers.extend(self.footers.iter().map(|footer| footer.trim().to_string()));
+        if !footers.is_empty() {
+            text.push_str("\n\n");
+            text.push_str(&footers.join("\n"));
+        }
+        text
+    }
+}
+
+/// Reads the answers file at `path`.
+pub fn read(path: &str) -> Result<Answers, GczError> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| std::io::Error::new(err.kind(), format!("{}: {}", path, err)))?;
+    parse(&text).map_err(|reason| GczError::InvalidAnswers {
+        path: path.to_string(),
+        reason,
+    })
+}
+
+/// Unknown keys are errors, so a typo can't silently leave a prompt unanswered.
+fn parse(text: &str) -> Result<Answers, String> {
+    let doc = Document::parse(text).map_err(|err| err.message().to_string())?;
+    let mut answers = Answers::default();
+    for (key, item) in doc.iter() {
+        match key {
+            "type" => answers.commit_type = string(key, item)?,
+            "scope" => answers.scope = Some(string(key, item)?).filter(|s| !s.is_empty()),
+            "subject" => answers.subject = string(key, item)?,
+            "body" => answers.body = Some(string(key, item)?),
+            "breaking" => {
+                answers.breaking = match (item.as_bool(), item.as_str()) {
+                    (Some(true), _) => Some(Breaking::Marked),
+                    (Some(false), _) => None,
+                    (_, Some(reason)) => Some(Breaking::Explained(reason.to_string())),
+                    _ => return Err("`breaking` must be true, false or a string".to_string()),
+                }
+            }
+            "footers" => {
+                answers.footers = item
+                    .as_array()
+                    .and_then(|array| {
+                        array
+                            .iter()
+                            .map(|value| value.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .ok_or("`footers` must be a list of strings")?
+            }
+            _ => return Err(format!("unknown key `{}`", key)),
+        }
+    }
+    if answers.commit_type.is_empty() {
+        return Err("`type` is missing".to_string());
+    }
+    if answers.subject.trim().is_empty() {
+        return Err("`subject` is missing".to_string());
+    }
+    Ok(answers)
+}
+
+fn string(key: &str, item: &Item) -> Result<String, String> {
+    item.as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("`{}` must be a string", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_answers() {
+        let answers = parse(
+            "type = \"feat\"\nscope = \"api\"\nsubject = \"add export\"\n\
+             body = \"Runs in the background.\"\nbreaking = \"links change\"\n\
+             footers = [\"Refs: #12\"]\n",
+        )
+        .unwrap();
+        assert_eq!(answers.commit_type, "feat");
+        assert_eq!(answers.scope.as_deref(), Some("api"));
+        assert_eq!(
+            answers.text(),
+            "add export\n\nRuns in the background.\n\nBREAKING CHANGE: links change\nRefs: #12"
+        );
+
+        assert_eq!(
+            parse("type = \"fix\"\nsubject = \"x\"\nbreaking = true").map(|a| a.breaking),
+            Ok(Some(Breaking::Marked))
+        );
+        assert_eq!(
+            parse("type = \"fix\"\nsubjet = \"x\""),
+            Err("unknown key `subjet`".to_string())
+        );
+        assert_eq!(
+            parse("subject = \"x\""),
+            Err("`type` is missing".to_string())
+        );
+    }
+}
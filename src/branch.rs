@@ -0,0 +1,97 @@
+use crate::config::Config;
+use crate::{ask_line, git, select_commit_type, select_option, text, GczError, UntrackedFiles};
+use std::io;
+
+/// Longest slug taken from the description; it is cut at a word boundary.
+const MAX_SLUG_LEN: usize = 40;
+
+/// Entry point of `gcz branch`: asks for a type, a description and an optional ticket and
+/// creates a branch such as `feat/login-page` or `fix/JIRA-123-crash`.
+pub fn branch(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    if git::toplevel()?.is_none() {
+        return Err(GczError::NotARepository);
+    }
+
+    let mut untracked = UntrackedFiles::new(Vec::new());
+    let commit_type = select_commit_type(stdout, config, &mut untracked, None)?;
+    let description = ask_line(stdout, "What is the branch for?", &config.keymap)?;
+    let ticket = ask_line(
+        stdout,
+        "Ticket, e.g. JIRA-123 (leave empty for none):",
+        &config.keymap,
+    )?;
+
+    let name = branch_name(&commit_type, Some(ticket.trim()), &description);
+    if !git::is_valid_branch_name(&name)? {
+        println!("`{}` isn't a valid branch name", name);
+        return Ok(());
+    }
+    let options = [
+        "Create and switch to it".to_string(),
+        "Just create it".to_string(),
+        "Cancel".to_string(),
+    ];
+    let title = format!("Create the branch `{}`?", name);
+    match select_option(stdout, &title, &options, &config.keymap)? {
+        0 => git::create_branch(&name, true)?,
+        1 => git::create_branch(&name, false)?,
+        _ => return Ok(()),
+    }
+    println!("Created {}", name);
+    Ok(())
+}
+
+/// `type/ticket-slug`, or `type/slug` without a ticket.
+fn branch_name(commit_type: &str, ticket: Option<&str>, description: &str) -> String {
+    let ticket: String = ticket
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    let slug = slugify(description);
+    let name = match (ticket.is_empty(), slug.is_empty()) {
+        (true, _) => slug,
+        (false, true) => ticket,
+        (false, false) => format!("{}-{}", ticket, slug),
+    };
+    format!("{}/{}", commit_type, name)
+}
+
+/// `Añadir la página de login!` becomes `anadir-la-pagina-de-login`: folded like the type
+/// filter, with every run of other characters turned into one `-`.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for word in text::fold(text)
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + 1 + word.len() > MAX_SLUG_LEN {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_name_branches() {
+        assert_eq!(branch_name("feat", None, "Login page!"), "feat/login-page");
+        assert_eq!(
+            branch_name("fix", Some("JIRA-123"), "Crash on start"),
+            "fix/JIRA-123-crash-on-start"
+        );
+        assert_eq!(branch_name("fix", Some("JIRA-123"), "  "), "fix/JIRA-123");
+        assert_eq!(
+            slugify("Añadir la página de login, con OAuth y también magic links"),
+            "anadir-la-pagina-de-login-con-oauth-y"
+        );
+    }
+}
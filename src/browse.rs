@@ -0,0 +1,382 @@
+use crate::config::Config;
+use crate::conventional;
+use crate::keymap::{Action, Keymap, Screen};
+use crate::term::{self, enable_raw_mode};
+use crate::{draw_status_line, finalize, git, glyph, show_help, terminal_size, text, GczError};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+
+/// At most this many of the latest commits are listed.
+const MAX_COMMITS: usize = 500;
+
+/// Lines above the list: the filter, the type and scope counts and a blank line.
+const HEADER_ROWS: usize = 4;
+
+/// Types and scopes shown in the counts above the list.
+const MAX_TALLY: usize = 8;
+
+/// Body lines shown under an expanded commit.
+const MAX_BODY_LINES: usize = 10;
+
+/// A commit with its header taken apart; `commit_type` is `None` when it isn't conventional.
+#[derive(Debug)]
+struct Commit {
+    short_hash: String,
+    date: String,
+    author: String,
+    commit_type: Option<String>,
+    scope: Option<String>,
+    breaking: bool,
+    /// The description of a conventional header, or the whole header otherwise.
+    subject: String,
+    body: String,
+}
+
+impl Commit {
+    fn parse(entry: git::LogEntry) -> Commit {
+        let (header, body) = conventional::split_message(&entry.message);
+        let parsed = conventional::parse_header(header).ok();
+        Commit {
+            short_hash: entry.hash.chars().take(7).collect(),
+            date: entry.date,
+            author: entry.author,
+            commit_type: parsed.as_ref().map(|h| h.commit_type.to_string()),
+            scope: parsed.as_ref().and_then(|h| h.scope.map(str::to_string)),
+            breaking: parsed.as_ref().is_some_and(|h| h.breaking),
+            subject: parsed.map_or(header, |h| h.description).to_string(),
+            body: body.trim().to_string(),
+        }
+    }
+}
+
+/// Entry point of `gcz browse`: lists the latest commits by type and scope, filtered by
+/// typing `feat`, `feat(api)` or `(api)`.
+pub fn browse(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    if git::toplevel()?.is_none() {
+        return Err(GczError::NotARepository);
+    }
+    let commits: Vec<Commit> = git::recent_log(MAX_COMMITS)?
+        .into_iter()
+        .map(Commit::parse)
+        .collect();
+
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    let result = browse_commits(stdout, &commits, &config.keymap);
+    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    finalize(result, stdout)?
+}
+
+fn browse_commits(
+    stdout: &mut io::Stdout,
+    commits: &[Commit],
+    keymap: &Keymap,
+) -> Result<(), GczError> {
+    let mut query = String::new();
+    let mut selected = 0;
+    let mut top = 0;
+    let mut expanded = false;
+
+    loop {
+        let filter = Filter::parse(&query);
+        let shown: Vec<&Commit> = commits.iter().filter(|c| filter.matches(c)).collect();
+        let (width, height) = terminal_size()?;
+        let details = if expanded {
+            shown.get(selected).map_or(0, |c| details(c).len())
+        } else {
+            0
+        };
+        let status = usize::from(term::status_line());
+        let rows = height.saturating_sub(HEADER_ROWS + details + status).max(1);
+        // keep the selected commit on screen
+        top = top.min(selected).max((selected + 1).saturating_sub(rows));
+
+        if term::should_redraw()? {
+            let view = View {
+                query: &query,
+                commits: &shown,
+                total: commits.len(),
+                selected,
+                top,
+                rows,
+                width,
+                expanded,
+            };
+            view.render(stdout, keymap)?;
+        }
+
+        let key_event = match event::read()? {
+            Event::Key(key_event) => key_event,
+            _ => continue,
+        };
+        match keymap.action(Screen::Browse, &key_event) {
+            Some(Action::Abort) => return Ok(()),
+            Some(Action::Help) => show_help(stdout, keymap, &[Screen::Browse])?,
+            Some(Action::Up) => selected = selected.saturating_sub(1),
+            Some(Action::Down) if selected + 1 < shown.len() => selected += 1,
+            Some(Action::Home) => selected = 0,
+            Some(Action::End) => selected = shown.len().saturating_sub(1),
+            Some(Action::Confirm) => expanded = !expanded,
+            Some(Action::DeleteBack) => {
+                query.pop();
+                selected = 0;
+            }
+            Some(Action::Clear) => {
+                query.clear();
+                selected = 0;
+            }
+            _ => match key_event.code {
+                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// What the commits are narrowed to: a type and a scope they start with, folded by
+/// `text::fold`.
+#[derive(Debug, PartialEq, Eq)]
+struct Filter {
+    commit_type: String,
+    scope: Option<String>,
+}
+
+impl Filter {
+    /// `feat` or `feat(api)`; `(api)` leaves the type open.
+    fn parse(query: &str) -> Filter {
+        let query = text::fold(query.trim());
+        match query.split_once('(') {
+            Some((commit_type, scope)) => Filter {
+                commit_type: commit_type.to_string(),
+                scope: Some(scope.trim_end_matches(')').to_string()),
+            },
+            None => Filter {
+                commit_type: query,
+                scope: None,
+            },
+        }
+    }
+
+    fn matches(&self, commit: &Commit) -> bool {
+        let starts_with = |value: Option<&String>, prefix: &str| {
+            value.is_some_and(|value| text::fold(value).starts_with(prefix))
+        };
+        (self.commit_type.is_empty() || starts_with(commit.commit_type.as_ref(), &self.commit_type))
+            && self
+                .scope
+                .as_ref()
+                .is_none_or(|scope| starts_with(commit.scope.as_ref(), scope))
+    }
+}
+
+/// How often each value occurs, the most frequent first.
+fn tally<'a>(values: impl Iterator<Item = &'a str>) -> Vec<(&'a str, usize)> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((value, 1)),
+        }
+    }
+    // stable, so ties keep the order they first appeared in, the newest first
+    counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    counts
+}
+
+/// The lines shown under the selected commit when it is expanded.
+fn details(commit: &Commit) -> Vec<String> {
+    let mut lines = vec![format!("{} on {}", commit.author, commit.date)];
+    lines.extend(
+        commit
+            .body
+            .lines()
+            .take(MAX_BODY_LINES)
+            .map(|line| format!("  {}", line)),
+    );
+    lines
+}
+
+struct View<'a> {
+    query: &'a str,
+    commits: &'a [&'a Commit],
+    total: usize,
+    selected: usize,
+    top: usize,
+    rows: usize,
+    width: usize,
+    expanded: bool,
+}
+
+impl View<'_> {
+    fn render(&self, stdout: &mut io::Stdout, keymap: &Keymap) -> Result<(), GczError> {
+        execute!(
+            stdout,
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print(text::clip(
+                &format!(
+                    "{} of {} commits (type `feat`, `feat(api)` or `(api)` to filter, {}: help): {}",
+                    self.commits.len(),
+                    self.total,
+                    keymap.describe(Screen::Browse, Action::Help),
+                    self.query
+                ),
+                self.width
+            )),
+            cursor::MoveToNextLine(1)
+        )?;
+        let types = tally(
+            self.commits
+                .iter()
+                .map(|c| c.commit_type.as_deref().unwrap_or("(other)")),
+        );
+        let scopes = tally(self.commits.iter().filter_map(|c| c.scope.as_deref()));
+        for (label, counts) in [("Types", types), ("Scopes", scopes)] {
+            let counts: Vec<String> = counts
+                .iter()
+                .take(MAX_TALLY)
+                .map(|(value, count)| format!("{} {}", value, count))
+                .collect();
+            execute!(
+                stdout,
+                SetForegroundColor(Color::DarkGrey),
+                Print(text::clip(
+                    &format!(
+                        "{}: {}",
+                        label,
+                        if counts.is_empty() {
+                            "none".to_string()
+                        } else {
+                            counts.join(", ")
+                        }
+                    ),
+                    self.width
+                )),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+        execute!(stdout, cursor::MoveToNextLine(1))?;
+
+        let end = (self.top + self.rows).min(self.commits.len());
+        for (i, commit) in self.commits[self.top..end].iter().enumerate() {
+            let i = self.top + i;
+            self.render_commit(stdout, commit, i == self.selected)?;
+            if i == self.selected && self.expanded {
+                for line in details(commit) {
+                    execute!(
+                        stdout,
+                        SetForegroundColor(Color::DarkGrey),
+                        Print(text::clip(&format!("    {}", line), self.width)),
+                        SetForegroundColor(Color::Reset),
+                        cursor::MoveToNextLine(1)
+                    )?;
+                }
+            }
+        }
+        draw_status_line(stdout, keymap, Screen::Browse)?;
+        stdout.flush()?;
+        Ok(())
+    }
+
+    fn render_commit(
+        &self,
+        stdout: &mut io::Stdout,
+        commit: &Commit,
+        selected: bool,
+    ) -> Result<(), GczError> {
+        let pointer = if selected { glyph::get().pointer } else { " " };
+        let lead = format!("{} {} {} ", pointer, commit.short_hash, commit.date);
+        let kind = match &commit.commit_type {
+            Some(commit_type) => format!(
+                "{}{}{}: ",
+                commit_type,
+                commit
+                    .scope
+                    .as_ref()
+                    .map(|scope| format!("({})", scope))
+                    .unwrap_or_default(),
+                if commit.breaking { "!" } else { "" }
+            ),
+            None => String::new(),
+        };
+        let subject_width = self
+            .width
+            .saturating_sub(text::width(&lead) + text::width(&kind));
+        let (kind_color, subject_color) = match (selected, commit.commit_type.is_some()) {
+            (true, _) => (Color::Green, Color::Green),
+            (false, true) => (Color::Cyan, Color::Reset),
+            (false, false) => (Color::Reset, Color::DarkGrey),
+        };
+        execute!(
+            stdout,
+            SetForegroundColor(if selected { Color::Green } else { Color::Reset }),
+            Print(text::clip(&lead, self.width)),
+            SetForegroundColor(kind_color),
+            Print(text::clip(
+                &kind,
+                self.width.saturating_sub(text::width(&lead))
+            )),
+            SetForegroundColor(subject_color),
+            Print(text::clip(&commit.subject, subject_width)),
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(message: &str) -> Commit {
+        Commit::parse(git::LogEntry {
+            hash: "0123456789".to_string(),
+            author: "Jane".to_string(),
+            date: "2026-01-01".to_string(),
+            message: message.to_string(),
+        })
+    }
+
+    #[test]
+    fn should_filter_commits_by_type_and_scope() {
+        let commits = [
+            commit("feat(api): add endpoint"),
+            commit("fix(ui): align button"),
+            commit("Fix typo"),
+        ];
+        let shown = |query: &str| -> Vec<&str> {
+            let filter = Filter::parse(query);
+            commits
+                .iter()
+                .filter(|c| filter.matches(c))
+                .map(|c| c.subject.as_str())
+                .collect()
+        };
+
+        assert_eq!(shown(""), ["add endpoint", "align button", "Fix typo"]);
+        assert_eq!(shown("F"), ["add endpoint", "align button"]);
+        assert_eq!(shown("fe(a"), ["add endpoint"]);
+        assert_eq!(shown("(UI)"), ["align button"]);
+        assert!(shown("feat(ui)").is_empty());
+    }
+
+    #[test]
+    fn should_tally_the_most_frequent_first() {
+        let values = ["fix", "feat", "feat", "docs"];
+        assert_eq!(
+            tally(values.into_iter()),
+            [("feat", 2), ("fix", 1), ("docs", 1)]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::config::{self, Config};
+use crate::conventional;
+use crate::git::{self, LogEntry};
+use crate::GczError;
+use serde_json::{json, Value};
+use std::fs;
+use tera::{Context, Tera};
+
+/// Section titles of the built-in template; other types use their own name.
+const SECTION_TITLES: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("style", "Style"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "CI"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+const DEFAULT_TEMPLATE: &str = "\
+## {{ version | default(value=\"Unreleased\") }}{% if date %} ({{ date }}){% endif %}
+{% if breaking %}
+### ⚠ Breaking Changes
+{% for commit in breaking %}
+- {% if commit.scope %}**{{ commit.scope }}:** {% endif %}{{ commit.description }} ({{ commit.short_hash }})
+{%- endfor %}
+{% endif %}
+{%- for group in groups %}
+### {{ group.title }}
+{% for commit in group.commits %}
+- {% if commit.scope %}**{{ commit.scope }}:** {% endif %}{{ commit.description }} ({{ commit.short_hash }})
+{%- endfor %}
+{% endfor %}";
+
+/// Entry point of `gcz changelog`: prints the changes in `range`, or since the previous tag.
+pub fn changelog(
+    config: &Config,
+    range: Option<&str>,
+    template: Option<&str>,
+) -> Result<(), GczError> {
+    let (from, to) = match range.and_then(|range| range.split_once("..")) {
+        Some((from, to)) => (Some(from), to),
+        None => (None, range.unwrap_or("HEAD")),
+    };
+    let to = if to.is_empty() { "HEAD" } else { to };
+    let previous = match from {
+        Some(from) => Some(from.to_string()),
+        None => git::latest_tag(&format!("{}^", to))?,
+    };
+    let log_range = match &previous {
+        Some(previous) => format!("{}..{}", previous, to),
+        None => to.to_string(),
+    };
+
+    // the end of the range gives the heading its version when it is tagged
+    let version = match git::latest_tag(to)? {
+        Some(tag) if git::resolve_commit(&tag)? == git::resolve_commit(to)? => Some(tag),
+        _ => None,
+    };
+    let entries = git::log(&log_range)?;
+    let date = entries.first().map(|entry| entry.date.clone());
+
+    let source = match template
+        .map(String::from)
+        .or(config.changelog_template.clone())
+    {
+        Some(path) => fs::read_to_string(config::repo_relative(&path)?)?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let context = build_context(&entries, &config.types, version, previous, date);
+    print!("{}", render(&source, context)?);
+    Ok(())
+}
+
+fn render(source: &str, context: Value) -> Result<String, GczError> {
+    let context = Context::from_value(context).map_err(template_error)?;
+    let mut tera = Tera::default();
+    tera.add_raw_template("changelog", source)
+        .map_err(template_error)?;
+    tera.render("changelog", &context).map_err(template_error)
+}
+
+/// Tera keeps the useful part of the message in the error's source chain.
+fn template_error(err: tera::Error) -> GczError {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    GczError::Template(message)
+}
+
+/// The data a changelog template sees; documented in the README.
+fn build_context(
+    entries: &[LogEntry],
+    types: &[String],
+    version: Option<String>,
+    previous: Option<String>,
+    date: Option<String>,
+) -> Value {
+    let commits: Vec<Value> = entries.iter().map(commit_context).collect();
+
+    let mut group_types: Vec<&str> = types.iter().map(String::as_str).collect();
+    for commit in &commits {
+        if let Some(commit_type) = commit["type"].as_str() {
+            if !group_types.contains(&commit_type) {
+                group_types.push(commit_type);
+            }
+        }
+    }
+    let groups: Vec<Value> = group_types
+        .into_iter()
+        .filter_map(|commit_type| {
+            let members: Vec<&Value> = commits
+                .iter()
+                .filter(|commit| commit["type"] == commit_type)
+                .collect();
+            let title = SECTION_TITLES
+                .iter()
+                .find(|(name, _)| *name == commit_type)
+                .map_or(commit_type, |(_, title)| title);
+            (!members.is_empty())
+                .then(|| json!({ "type": commit_type, "title": title, "commits": members }))
+        })
+        .collect();
+    let breaking: Vec<&Value> = commits
+        .iter()
+        .filter(|commit| commit["breaking"] == true)
+        .collect();
+
+    json!({
+        "version": version,
+        "previous": previous,
+        "date": date,
+        "groups": groups,
+        "breaking": breaking,
+        "commits": commits,
+    })
+}
+
+/// Non-conventional commits only have `type`, `scope` and `description` set to null.
+fn commit_context(entry: &LogEntry) -> Value {
+    let (header, body) = conventional::split_message(&entry.message);
+    let parsed = conventional::parse_header(header).ok();
+    let breaking_footer = body
+        .lines()
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+
+    json!({
+        "hash": entry.hash,
+        "short_hash": entry.hash.get(..7).unwrap_or(&entry.hash),
+        "author": entry.author,
+        "date": entry.date,
+        "header": header,
+        "body": body,
+        "type": parsed.as_ref().map(|h| h.commit_type),
+        "scope": parsed.as_ref().and_then(|h| h.scope),
+        "description": parsed.as_ref().map(|h| h.description),
+        "breaking": parsed.as_ref().is_some_and(|h| h.breaking) || breaking_footer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str, message: &str) -> LogEntry {
+        LogEntry {
+            hash: hash.to_string(),
+            author: "Jane Doe".to_string(),
+            date: "2026-10-16".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_render_the_default_template() {
+        let entries = vec![
+            entry("a1b2c3d4e5", "fix(parser): handle empty input"),
+            entry("b2c3d4e5f6", "feat!: drop the legacy flag"),
+            entry("c3d4e5f6a7", "Merge cleanup"),
+            entry("d4e5f6a7b8", "feat(cli): add --yes"),
+        ];
+        let types = vec!["feat".to_string(), "fix".to_string()];
+        let context = build_context(
+            &entries,
+            &types,
+            Some("v1.2.0".to_string()),
+            Some("v1.1.0".to_string()),
+            Some("2026-10-16".to_string()),
+        );
+
+        assert_eq!(
+            render(DEFAULT_TEMPLATE, context).unwrap(),
+            "## v1.2.0 (2026-10-16)\n\
+             \n\
+             ### ⚠ Breaking Changes\n\
+             \n\
+             - drop the legacy flag (b2c3d4e)\n\
+             \n\
+             ### Features\n\
+             \n\
+             - drop the legacy flag (b2c3d4e)\n\
+             - **cli:** add --yes (d4e5f6a)\n\
+             \n\
+             ### Bug Fixes\n\
+             \n\
+             - **parser:** handle empty input (a1b2c3d)\n"
+        );
+    }
+}
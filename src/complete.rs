@@ -0,0 +1,87 @@
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Shorter words aren't worth completing.
+const MIN_LENGTH: usize = 4;
+
+/// The words a subject is likely to mention, from a git-style `diff`: the identifiers on its
+/// changed lines and the paths and names of its files, the most frequent first.
+pub fn vocabulary(diff: &str) -> Vec<String> {
+    let identifier =
+        Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").expect("identifier pattern should compile");
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for line in diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git a/") {
+            let path = paths.rsplit_once(" b/").map_or(paths, |(_, path)| path);
+            let name = path.rsplit('/').next().unwrap_or(path);
+            for word in [path, name] {
+                *counts.entry(word).or_default() += 1;
+            }
+            continue;
+        }
+        if line.starts_with("+++") || line.starts_with("---") {
+            continue;
+        }
+        if let Some(code) = line.strip_prefix(['+', '-']) {
+            for word in identifier.find_iter(code) {
+                *counts.entry(word.as_str()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut words: Vec<(&str, usize)> = counts
+        .into_iter()
+        .filter(|(word, _)| word.len() >= MIN_LENGTH)
+        .collect();
+    words.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    words
+        .into_iter()
+        .map(|(word, _)| word.to_string())
+        .collect()
+}
+
+/// Where the word ending at byte `end` of `text` starts; words may hold the dots, slashes
+/// and dashes of a path.
+pub fn word_start(text: &str, end: usize) -> usize {
+    text[..end]
+        .rfind(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '/' | '-')))
+        .map_or(0, |at| at + 1)
+}
+
+/// The words of `vocabulary` that `prefix` starts, ignoring case, in their order there.
+pub fn matches<'a>(vocabulary: &'a [String], prefix: &str) -> Vec<&'a str> {
+    let prefix = prefix.to_lowercase();
+    vocabulary
+        .iter()
+        .filter(|word| word.len() > prefix.len() && word.to_lowercase().starts_with(&prefix))
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_complete_identifiers_and_paths_from_the_diff() {
+        let diff = "diff --git a/src/parser.rs b/src/parser.rs\n\
+                    --- a/src/parser.rs\n\
+                    +++ b/src/parser.rs\n\
+                    @@ -1,2 +1,2 @@ fn unrelated_context() {\n\
+                    -fn parse_header(raw: &str) {}\n\
+                    +fn parse_header(raw: &str, strict: bool) {}\n\
+                    +fn parse_footer() { parse_header(\"\", true) }\n";
+        let vocabulary = vocabulary(diff);
+
+        assert_eq!(vocabulary[0], "parse_header");
+        assert!(!vocabulary.iter().any(|word| word == "unrelated_context"));
+        assert_eq!(
+            matches(&vocabulary, "PARSE"),
+            ["parse_header", "parse_footer", "parser.rs"]
+        );
+        assert_eq!(matches(&vocabulary, "src/"), ["src/parser.rs"]);
+
+        let subject = "fix: handle src/pa";
+        assert_eq!(&subject[word_start(subject, subject.len())..], "src/pa");
+    }
+}
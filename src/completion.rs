@@ -0,0 +1,161 @@
+use crate::config::Config;
+use crate::{conventional, git, GczError};
+
+/// Subjects searched for scopes already in use.
+const SCOPE_HISTORY: usize = 500;
+
+/// Entry point of `gcz completions <shell>`: prints a script that completes the subcommands
+/// and flags of `cli`, and asks `gcz __complete` for the types and scopes as you type.
+pub fn script(cli: &clap::Command, shell: &str) -> String {
+    let mut cli = cli.clone();
+    cli.build();
+    let subcommands: Vec<&str> = cli
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .map(|sub| sub.get_name())
+        .collect();
+    let flags: Vec<&clap::Arg> = cli
+        .get_arguments()
+        .filter(|arg| !arg.is_hide_set() && arg.get_long().is_some())
+        .collect();
+
+    match shell {
+        "bash" => BASH
+            .replace("{subcommands}", &subcommands.join(" "))
+            .replace("{flags}", &long_flags(&flags).join(" ")),
+        "zsh" => ZSH
+            .replace("{subcommands}", &subcommands.join(" "))
+            .replace("{flags}", &long_flags(&flags).join(" ")),
+        _ => {
+            let mut script = FISH.replace("{subcommands}", &subcommands.join(" "));
+            // --type and --scope are completed by the lines in FISH
+            for arg in flags
+                .into_iter()
+                .filter(|arg| !["type", "scope"].contains(&arg.get_id().as_str()))
+            {
+                script.push_str(&format!("complete -c gcz -l {}", arg.get_long().unwrap()));
+                if let Some(short) = arg.get_short() {
+                    script.push_str(&format!(" -s {}", short));
+                }
+                if arg.get_action().takes_values() {
+                    script.push_str(" -r");
+                }
+                if let Some(help) = arg.get_help() {
+                    let help = help.to_string().replace('\'', "\\'");
+                    script.push_str(&format!(" -d '{}'", help));
+                }
+                script.push('\n');
+            }
+            script
+        }
+    }
+}
+
+fn long_flags(flags: &[&clap::Arg]) -> Vec<String> {
+    flags
+        .iter()
+        .filter_map(|arg| arg.get_long())
+        .map(|long| format!("--{}", long))
+        .collect()
+}
+
+/// Entry point of `gcz __complete <kind>`: prints the `types` or `scopes` in effect here,
+/// one per line, for the scripts to filter.
+pub fn complete(config: &Config, kind: &str) -> Result<(), GczError> {
+    let values = match kind {
+        "types" => config.types.clone(),
+        _ => known_scopes(config)?,
+    };
+    for value in values {
+        println!("{}", value);
+    }
+    Ok(())
+}
+
+/// The configured scopes, then the ones used in the latest commits.
+pub fn known_scopes(config: &Config) -> Result<Vec<String>, GczError> {
+    Ok(scopes(
+        &config.scopes,
+        &git::recent_subjects(SCOPE_HISTORY)?,
+    ))
+}
+
+/// The configured scopes, then the ones used in `subjects`, without repeats.
+fn scopes(configured: &[String], subjects: &[String]) -> Vec<String> {
+    let mut scopes = configured.to_vec();
+    for subject in subjects {
+        if let Some(scope) = conventional::parse_header(subject)
+            .ok()
+            .and_then(|header| header.scope)
+        {
+            if !scopes.iter().any(|s| s == scope) {
+                scopes.push(scope.to_string());
+            }
+        }
+    }
+    scopes
+}
+
+const BASH: &str = r#"_gcz() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    case "$prev" in
+        -t|--type)
+            COMPREPLY=($(compgen -W "$(command gcz __complete types 2>/dev/null)" -- "$cur"))
+            return ;;
+        -s|--scope)
+            COMPREPLY=($(compgen -W "$(command gcz __complete scopes 2>/dev/null)" -- "$cur"))
+            return ;;
+    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "{flags}" -- "$cur"))
+    elif [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{subcommands}" -- "$cur"))
+    fi
+}
+complete -o default -F _gcz gcz
+"#;
+
+const ZSH: &str = r#"#compdef gcz
+_gcz() {
+    case "${words[CURRENT-1]}" in
+        -t|--type)
+            compadd -- ${(f)"$(command gcz __complete types 2>/dev/null)"}
+            return ;;
+        -s|--scope)
+            compadd -- ${(f)"$(command gcz __complete scopes 2>/dev/null)"}
+            return ;;
+    esac
+    if [[ "$PREFIX" == -* ]]; then
+        compadd -- {flags}
+    elif (( CURRENT == 2 )); then
+        compadd -- {subcommands}
+    else
+        _files
+    fi
+}
+compdef _gcz gcz
+"#;
+
+const FISH: &str = r#"complete -c gcz -n __fish_use_subcommand -f -a '{subcommands}'
+complete -c gcz -s t -l type -x -a '(command gcz __complete types 2>/dev/null)'
+complete -c gcz -s s -l scope -x -a '(command gcz __complete scopes 2>/dev/null)'
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_offer_configured_then_used_scopes() {
+        let subjects = [
+            "feat(api): add endpoint".to_string(),
+            "fix(ui): align".to_string(),
+            "fix(api): typo".to_string(),
+            "Merge branch 'main'".to_string(),
+        ];
+        assert_eq!(
+            scopes(&["core".to_string()], &subjects),
+            ["core", "api", "ui"]
+        );
+    }
+}
@@ -0,0 +1,2064 @@
+use crate::keymap::{self, Keymap};
+use crate::{extends, git, glob, plugin};
+use crate::{GczError, COMMIT_TYPES};
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs};
+use toml_edit::{Document, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// Value type expected for a config key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Bool,
+    /// A boolean, or `"auto"` to let gcz decide.
+    BoolOrAuto,
+    String,
+    /// A string restricted to the given values.
+    Enum(&'static [&'static str]),
+    StringList,
+    /// An array of names made of letters, digits, `-` and `_`, e.g. `plugins`.
+    NameList,
+    /// An array of regular expressions.
+    RegexList,
+    /// A byte count, either an integer or a string like `"5MB"`.
+    Size,
+    /// Action names to space-separated keys, e.g. `[keys]`.
+    Keys,
+    /// Seconds as an integer or a string such as `"90s"` or `"5m"`.
+    Duration,
+    /// A table of string values, e.g. `[templates]`.
+    StringMap,
+    /// `[profiles.<name>]` or `[remotes."<pattern>"]` tables holding any of the other keys.
+    Profiles,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Bool => write!(f, "a boolean"),
+            Kind::BoolOrAuto => write!(f, "a boolean or \"auto\""),
+            Kind::String => write!(f, "a string"),
+            Kind::Enum(values) => write!(f, "one of \"{}\"", values.join("\", \"")),
+            Kind::StringList => write!(f, "an array of strings"),
+            Kind::NameList => write!(f, "an array of names made of letters, digits, - and _"),
+            Kind::RegexList => write!(f, "an array of valid regular expressions"),
+            Kind::Size => write!(f, "a size such as 5242880 or \"5MB\""),
+            Kind::Duration => write!(f, "a duration such as 300 or \"5m\""),
+            Kind::Keys => write!(
+                f,
+                "a table of actions ({}) to keys such as \"ctrl+e\"",
+                keymap::action_names().collect::<Vec<_>>().join(", ")
+            ),
+            Kind::StringMap => write!(f, "a table of strings"),
+            Kind::Profiles => write!(f, "a table of tables"),
+        }
+    }
+}
+
+pub struct KeySpec {
+    pub name: &'static str,
+    pub kind: Kind,
+}
+
+pub const SCHEMA: &[KeySpec] = &[
+    KeySpec {
+        name: "emoji",
+        kind: Kind::BoolOrAuto,
+    },
+    KeySpec {
+        name: "compact",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "plain",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "confirm",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "confirm_abort",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "inline",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "types",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "disabled_types",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "favorite_types",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "signoff",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "co_authors",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "footers",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "trailer_presets",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "expand_tabs",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "commit_stats",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "record_usage",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "sort_types_by_use",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "protected_branches",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "branch_patterns",
+        kind: Kind::RegexList,
+    },
+    KeySpec {
+        name: "email_domains",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "emoji_position",
+        kind: Kind::Enum(&["before_type", "after_colon", "instead_of_type"]),
+    },
+    KeySpec {
+        name: "emoji_picker",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "emoji_column",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "save_emoji_toggle",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "ask_scope",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "scope_pane",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "scopes",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "templates",
+        kind: Kind::StringMap,
+    },
+    KeySpec {
+        name: "type_emojis",
+        kind: Kind::StringMap,
+    },
+    KeySpec {
+        name: "type_rules",
+        kind: Kind::StringMap,
+    },
+    KeySpec {
+        name: "scope_emojis",
+        kind: Kind::StringMap,
+    },
+    KeySpec {
+        name: "path_emojis",
+        kind: Kind::StringMap,
+    },
+    KeySpec {
+        name: "mapped_emoji",
+        kind: Kind::Enum(&["add", "replace"]),
+    },
+    KeySpec {
+        name: "push",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "pull_request",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "ask_note",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "custom_types",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "type_aliases",
+        kind: Kind::StringMap,
+    },
+    KeySpec {
+        name: "forbidden_words",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "secret_detection",
+        kind: Kind::Enum(&["error", "warn", "off"]),
+    },
+    KeySpec {
+        name: "spell_check",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "dictionary",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "staged_checks",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "staged_patterns",
+        kind: Kind::RegexList,
+    },
+    KeySpec {
+        name: "stash_unstaged",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "large_file_threshold",
+        kind: Kind::Size,
+    },
+    KeySpec {
+        name: "lfs_patterns",
+        kind: Kind::StringList,
+    },
+    KeySpec {
+        name: "git_timeout",
+        kind: Kind::Duration,
+    },
+    KeySpec {
+        name: "editor",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "keys",
+        kind: Kind::Keys,
+    },
+    KeySpec {
+        name: "ascii",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "keyboard_protocol",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "status_line",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "header_preview",
+        kind: Kind::Bool,
+    },
+    KeySpec {
+        name: "emoji_width",
+        kind: Kind::Enum(&["unicode", "legacy"]),
+    },
+    KeySpec {
+        name: "plugins",
+        kind: Kind::NameList,
+    },
+    KeySpec {
+        name: "script",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "translate_command",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "changelog_template",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "extends",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "extends_sha256",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "profile",
+        kind: Kind::String,
+    },
+    KeySpec {
+        name: "profiles",
+        kind: Kind::Profiles,
+    },
+    KeySpec {
+        name: "remotes",
+        kind: Kind::Profiles,
+    },
+];
+
+pub const DEFAULT_LFS_PATTERNS: &[&str] = &[
+    "*.psd", "*.ai", "*.sketch", "*.zip", "*.gz", "*.7z", "*.rar", "*.mp4", "*.mov", "*.avi",
+    "*.mp3", "*.wav", "*.iso", "*.dmg", "*.exe", "*.dll",
+];
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Put the type's emoji in the header; `None` ("auto") follows the recent commits
+    /// until the command line resolves it.
+    pub emoji: Option<bool>,
+    /// Show the types on a single line instead of a vertical list.
+    pub compact: bool,
+    /// Ask with numbered menus and line input instead of the full-screen prompts.
+    pub plain: bool,
+    /// Show the confirmation screens; errors from the checks still stop the commit.
+    pub confirm: bool,
+    /// Ask before Ctrl+C throws away a message that has been typed into.
+    pub confirm_abort: bool,
+    /// Type the message inline; when false the external editor opens right away.
+    pub inline: bool,
+    pub types: Vec<String>,
+    /// Types removed from `types` once everything is merged, e.g. built-ins a team doesn't use.
+    pub disabled_types: Vec<String>,
+    /// Types listed in a section of their own at the top of the type list.
+    pub favorite_types: Vec<String>,
+    pub signoff: bool,
+    pub co_authors: Vec<String>,
+    /// Trailers added to every message, e.g. `Refs: {env.TICKET}`; one with a variable that
+    /// isn't set is left out.
+    pub footers: Vec<String>,
+    /// Trailers to pick from after the message, e.g. `Reviewed-by: Alice <alice@example.com>`
+    /// or `Refs: #{}`, whose `{}` is asked for.
+    pub trailer_presets: Vec<String>,
+    /// Turn tabs in the message into spaces, with stops every 8 columns as `git log` shows them.
+    pub expand_tabs: bool,
+    /// Print today's commits, the branch's and its upstream status after committing.
+    pub commit_stats: bool,
+    /// Count the types, subjects and aborted runs in `.git` for `gcz stats`.
+    pub record_usage: bool,
+    /// List the types committed most often in this repository first.
+    pub sort_types_by_use: bool,
+    /// Branch globs that ask for confirmation before committing on them, e.g. `release/*`.
+    pub protected_branches: Vec<String>,
+    /// Regular expressions one of which the current branch must match to commit on it.
+    pub branch_patterns: Vec<String>,
+    /// Domains the commit email must be at, e.g. `acme.com`, which also covers its
+    /// subdomains; any email is fine when empty.
+    pub email_domains: Vec<String>,
+    /// Emoji per commit type, over the built-in ones.
+    pub type_emojis: Vec<(String, String)>,
+    /// `before_type`, `after_colon` or `instead_of_type`.
+    pub emoji_position: String,
+    /// Pick the emoji from the full set after the type instead of using the type's one.
+    pub emoji_picker: bool,
+    /// Draw the emojis in a fixed column right of the type and emoji names instead of before them.
+    pub emoji_column: bool,
+    /// Write the emoji toggled on the type screen to the config, not only for this run.
+    pub save_emoji_toggle: bool,
+    /// Ask for a scope after the type.
+    pub ask_scope: bool,
+    /// With `ask_scope`, pick the scope in a pane beside the types instead of a step of its own.
+    pub scope_pane: bool,
+    /// Scopes suggested by the scope step; new ones can be added to the repository's `.gcz.toml`.
+    pub scopes: Vec<String>,
+    /// Subject prefill per commit type.
+    pub templates: Vec<(String, String)>,
+    /// Path glob to commit type, used to pre-select the type; the first matching rule wins.
+    pub type_rules: Vec<(String, String)>,
+    /// Emoji per scope, used in the header along with or instead of the type's.
+    pub scope_emojis: Vec<(String, String)>,
+    /// Path glob to emoji, used like `scope_emojis` when every staged path agrees.
+    pub path_emojis: Vec<(String, String)>,
+    /// `add` puts the emoji of `scope_emojis` or `path_emojis` after the type's, `replace`
+    /// uses it instead.
+    pub mapped_emoji: String,
+    /// Push the branch after committing.
+    pub push: bool,
+    /// Push, then open a pull request with `gh pr create`.
+    pub pull_request: bool,
+    /// Ask for a git note to attach to the new commit.
+    pub ask_note: bool,
+    /// Let Enter use the filter as the type when it matches none of `types`.
+    pub custom_types: bool,
+    /// Short names that pick a type as soon as they are typed in the selector.
+    pub type_aliases: Vec<(String, String)>,
+    /// Words that must not appear in the message.
+    pub forbidden_words: Vec<String>,
+    /// `error`, `warn` or `off`.
+    pub secret_detection: String,
+    /// Mark misspelled words in the message.
+    pub spell_check: bool,
+    /// File of extra words for `spell_check`, one per line.
+    pub dictionary: Option<String>,
+    /// Scan the staged diff for leftover debug code and conflict markers.
+    pub staged_checks: bool,
+    /// Extra patterns for `staged_checks`.
+    pub staged_patterns: Vec<String>,
+    /// Stash unstaged changes and untracked files while committing, so hooks only see what
+    /// is committed.
+    pub stash_unstaged: bool,
+    /// Staged files larger than this many bytes are reported; 0 disables the check.
+    pub large_file_threshold: u64,
+    /// Globs of files that likely belong in Git LFS.
+    pub lfs_patterns: Vec<String>,
+    /// Seconds before a git command is killed; 0 waits forever.
+    pub git_timeout: u64,
+    /// Command for the external editor; git's editor when unset.
+    pub editor: Option<String>,
+    pub keymap: Keymap,
+    /// Draw only ASCII; detected from the locale and terminal when unset.
+    pub ascii: Option<bool>,
+    /// Use the kitty keyboard protocol where the terminal supports it.
+    pub keyboard_protocol: bool,
+    /// Show the keys of the current step at the bottom of the screen.
+    pub status_line: bool,
+    /// Show the header as it will be committed, with its length, under the message being typed.
+    pub header_preview: bool,
+    /// How the terminal measures emoji: `unicode` gives a whole sequence such as `♻️` two
+    /// columns, `legacy` counts each code point like `wcwidth`.
+    pub emoji_width: String,
+    /// Names of the `gcz-<name>` plugins asked during the wizard.
+    pub plugins: Vec<String>,
+    /// Lua script that may rewrite or veto the final message.
+    pub script: Option<String>,
+    /// Shell command that reads the description and body on stdin and prints their
+    /// translation, offered before the confirmation screen.
+    pub translate_command: Option<String>,
+    /// Tera template for `gcz changelog`; the built-in one when unset.
+    pub changelog_template: Option<String>,
+    pub profile: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            emoji: Some(false),
+            compact: false,
+            plain: false,
+            confirm: true,
+            confirm_abort: true,
+            inline: true,
+            types: COMMIT_TYPES.iter().map(|t| t.to_string()).collect(),
+            disabled_types: Vec::new(),
+            favorite_types: Vec::new(),
+            signoff: false,
+            co_authors: Vec::new(),
+            footers: Vec::new(),
+            trailer_presets: Vec::new(),
+            expand_tabs: false,
+            commit_stats: false,
+            record_usage: true,
+            sort_types_by_use: false,
+            protected_branches: vec![
+                "main".to_string(),
+                "master".to_string(),
+                "release/*".to_string(),
+            ],
+            branch_patterns: Vec::new(),
+            email_domains: Vec::new(),
+            type_emojis: Vec::new(),
+            emoji_position: "after_colon".to_string(),
+            emoji_picker: false,
+            emoji_column: false,
+            save_emoji_toggle: false,
+            ask_scope: false,
+            scope_pane: false,
+            scopes: Vec::new(),
+            templates: Vec::new(),
+            type_rules: Vec::new(),
+            scope_emojis: Vec::new(),
+            path_emojis: Vec::new(),
+            mapped_emoji: "add".to_string(),
+            push: false,
+            pull_request: false,
+            ask_note: false,
+            custom_types: true,
+            type_aliases: Vec::new(),
+            forbidden_words: Vec::new(),
+            secret_detection: "error".to_string(),
+            spell_check: false,
+            dictionary: None,
+            staged_checks: false,
+            staged_patterns: Vec::new(),
+            stash_unstaged: false,
+            large_file_threshold: 5 * 1024 * 1024,
+            lfs_patterns: DEFAULT_LFS_PATTERNS.iter().map(|p| p.to_string()).collect(),
+            git_timeout: 300,
+            editor: None,
+            keymap: Keymap::default(),
+            ascii: None,
+            keyboard_protocol: true,
+            status_line: true,
+            header_preview: true,
+            emoji_width: "unicode".to_string(),
+            plugins: Vec::new(),
+            script: None,
+            translate_command: None,
+            changelog_template: None,
+            profile: None,
+        }
+    }
+}
+
+impl Config {
+    fn apply(&mut self, key: &str, item: &Item) {
+        match key {
+            "emoji" => self.emoji = bool_or_auto(item).unwrap_or(self.emoji),
+            "compact" => self.compact = item.as_bool().unwrap_or(self.compact),
+            "plain" => self.plain = item.as_bool().unwrap_or(self.plain),
+            "confirm" => self.confirm = item.as_bool().unwrap_or(self.confirm),
+            "confirm_abort" => self.confirm_abort = item.as_bool().unwrap_or(self.confirm_abort),
+            "inline" => self.inline = item.as_bool().unwrap_or(self.inline),
+            "types" => self.types = string_list(item),
+            "disabled_types" => self.disabled_types = string_list(item),
+            "favorite_types" => self.favorite_types = string_list(item),
+            "signoff" => self.signoff = item.as_bool().unwrap_or(self.signoff),
+            "co_authors" => self.co_authors = string_list(item),
+            "expand_tabs" => self.expand_tabs = item.as_bool().unwrap_or(self.expand_tabs),
+            "footers" => self.footers = string_list(item),
+            "trailer_presets" => self.trailer_presets = string_list(item),
+            "commit_stats" => self.commit_stats = item.as_bool().unwrap_or(self.commit_stats),
+            "record_usage" => self.record_usage = item.as_bool().unwrap_or(self.record_usage),
+            "sort_types_by_use" => {
+                self.sort_types_by_use = item.as_bool().unwrap_or(self.sort_types_by_use)
+            }
+            "protected_branches" => self.protected_branches = string_list(item),
+            "branch_patterns" => self.branch_patterns = string_list(item),
+            "email_domains" => self.email_domains = string_list(item),
+            "emoji_position" => set_string(&mut self.emoji_position, item),
+            "emoji_picker" => self.emoji_picker = item.as_bool().unwrap_or(self.emoji_picker),
+            "emoji_column" => self.emoji_column = item.as_bool().unwrap_or(self.emoji_column),
+            "save_emoji_toggle" => {
+                self.save_emoji_toggle = item.as_bool().unwrap_or(self.save_emoji_toggle)
+            }
+            "ask_scope" => self.ask_scope = item.as_bool().unwrap_or(self.ask_scope),
+            "scope_pane" => self.scope_pane = item.as_bool().unwrap_or(self.scope_pane),
+            "scopes" => self.scopes = string_list(item),
+            "type_emojis" => merge_string_map(&mut self.type_emojis, item),
+            "templates" => merge_string_map(&mut self.templates, item),
+            "type_rules" => merge_string_map(&mut self.type_rules, item),
+            "scope_emojis" => merge_string_map(&mut self.scope_emojis, item),
+            "path_emojis" => merge_string_map(&mut self.path_emojis, item),
+            "mapped_emoji" => set_string(&mut self.mapped_emoji, item),
+            "push" => self.push = item.as_bool().unwrap_or(self.push),
+            "pull_request" => self.pull_request = item.as_bool().unwrap_or(self.pull_request),
+            "ask_note" => self.ask_note = item.as_bool().unwrap_or(self.ask_note),
+            "custom_types" => self.custom_types = item.as_bool().unwrap_or(self.custom_types),
+            "type_aliases" => merge_string_map(&mut self.type_aliases, item),
+            "forbidden_words" => self.forbidden_words = string_list(item),
+            "secret_detection" => set_string(&mut self.secret_detection, item),
+            "spell_check" => self.spell_check = item.as_bool().unwrap_or(self.spell_check),
+            "dictionary" => self.dictionary = item.as_str().map(String::from),
+            "staged_checks" => self.staged_checks = item.as_bool().unwrap_or(self.staged_checks),
+            "staged_patterns" => self.staged_patterns = string_list(item),
+            "stash_unstaged" => self.stash_unstaged = item.as_bool().unwrap_or(self.stash_unstaged),
+            "large_file_threshold" => {
+                if let Some(size) = parse_size(item) {
+                    self.large_file_threshold = size;
+                }
+            }
+            "lfs_patterns" => self.lfs_patterns = string_list(item),
+            "git_timeout" => {
+                if let Some(seconds) = parse_duration(item) {
+                    self.git_timeout = seconds;
+                }
+            }
+            "editor" => self.editor = item.as_str().map(String::from),
+            "keys" => self.keymap.rebind(item),
+            "ascii" => self.ascii = item.as_bool().or(self.ascii),
+            "keyboard_protocol" => {
+                self.keyboard_protocol = item.as_bool().unwrap_or(self.keyboard_protocol)
+            }
+            "status_line" => self.status_line = item.as_bool().unwrap_or(self.status_line),
+            "header_preview" => self.header_preview = item.as_bool().unwrap_or(self.header_preview),
+            "emoji_width" => set_string(&mut self.emoji_width, item),
+            "plugins" => self.plugins = string_list(item),
+            "script" => self.script = item.as_str().map(String::from),
+            "translate_command" => self.translate_command = item.as_str().map(String::from),
+            "changelog_template" => self.changelog_template = item.as_str().map(String::from),
+            "profile" => self.profile = item.as_str().map(String::from),
+            _ => {}
+        }
+    }
+
+    fn apply_table(&mut self, table: &dyn TableLike) {
+        for (key, item) in table.iter() {
+            self.apply(key, item);
+        }
+    }
+
+    /// The subject prefill configured for `commit_type`, if any.
+    pub fn template_for(&self, commit_type: &str) -> Option<&str> {
+        self.templates
+            .iter()
+            .find(|(t, _)| t == commit_type)
+            .map(|(_, template)| template.as_str())
+    }
+
+    /// Adds a one-off type from `--extra-type name[:emoji]`.
+    pub fn add_extra_type(&mut self, spec: &str) {
+        let (name, emoji) = match spec.split_once(':') {
+            Some((name, emoji)) => (name, Some(emoji)),
+            None => (spec, None),
+        };
+        if !self.types.iter().any(|t| t == name) {
+            self.types.push(name.to_string());
+        }
+        if let Some(emoji) = emoji.filter(|emoji| !emoji.is_empty()) {
+            self.type_emojis.retain(|(t, _)| t != name);
+            self.type_emojis.push((name.to_string(), emoji.to_string()));
+        }
+    }
+
+    /// The type suggested by `type_rules` when every path matches a rule and they all agree.
+    pub fn suggested_type(&self, paths: &[String]) -> Option<&str> {
+        agreed_rule(&self.type_rules, paths)
+    }
+
+    /// The emoji `scope_emojis` gives `scope`, or else the one `path_emojis` gives all of
+    /// `paths`.
+    pub fn emoji_for_changes(&self, scope: Option<&str>, paths: &[String]) -> Option<&str> {
+        scope
+            .and_then(|scope| self.scope_emojis.iter().find(|(s, _)| s == scope))
+            .map(|(_, emoji)| emoji.as_str())
+            .or_else(|| agreed_rule(&self.path_emojis, paths))
+    }
+}
+
+/// The value of the first glob in `rules` matching each path, when every path has one and
+/// they are all the same.
+fn agreed_rule<'a>(rules: &'a [(String, String)], paths: &[String]) -> Option<&'a str> {
+    let mut agreed = None;
+    for path in paths {
+        let value = rules
+            .iter()
+            .find(|(pattern, _)| glob::matches(pattern, path))
+            .map(|(_, value)| value.as_str())?;
+        if agreed.is_some_and(|v| v != value) {
+            return None;
+        }
+        agreed = Some(value);
+    }
+    agreed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Where a config value came from.
+#[derive(Debug)]
+pub enum Location {
+    File {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+    },
+    Env(String),
+    Profile(String),
+    CommandLine,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::File { path, line, column } => {
+                write!(f, "{}:{}:{}", path.display(), line, column)
+            }
+            Location::Env(name) => write!(f, "${}", name),
+            Location::Profile(name) => write!(f, "profile `{}`", name),
+            Location::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub location: Location,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}: {}", self.location, severity, self.message)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/gcz/config.toml`, falling back to `~/.config/gcz/config.toml`.
+pub fn global_config_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("gcz").join("config.toml"))
+}
+
+/// `.gcz.toml` at the root of the current work tree.
+pub fn repo_config_path() -> Result<Option<PathBuf>, GczError> {
+    Ok(git::toplevel()?.map(|root| root.join(".gcz.toml")))
+}
+
+/// Appends `scope` to the `scopes` of the repository's `.gcz.toml`, where the team shares it.
+pub fn remember_scope(scope: &str) -> Result<(), GczError> {
+    let path = repo_config_path()?.ok_or(GczError::NotARepository)?;
+    let mut scopes = fs::read_to_string(&path)
+        .ok()
+        .and_then(|raw| Document::parse(raw).ok())
+        .and_then(|doc| get_path(doc.as_item(), "scopes")?.as_array().cloned())
+        .unwrap_or_default();
+    scopes.push(scope);
+    set_values(&path, &[("scopes", Item::Value(Value::Array(scopes)))])
+}
+
+/// Paths in the config (`script`, `dictionary`, `changelog_template`) are relative to the repository root,
+/// where `.gcz.toml` lives.
+pub fn repo_relative(path: &str) -> Result<PathBuf, GczError> {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    Ok(git::toplevel()?.unwrap_or_default().join(path))
+}
+
+/// Existing config files, lowest precedence first.
+pub fn config_paths() -> Result<Vec<PathBuf>, GczError> {
+    Ok(global_config_path()
+        .into_iter()
+        .chain(repo_config_path()?)
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+/// Sets keys in the config file at `path`, creating it if needed and keeping everything
+/// else, comments included. Dotted keys such as `templates.feat` reach into tables.
+pub fn set_values(path: &Path, values: &[(&str, Item)]) -> Result<(), GczError> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let mut doc: DocumentMut = match raw.parse() {
+        Ok(doc) => doc,
+        Err(_) => return Err(GczError::InvalidConfig(check_source(path, &raw))),
+    };
+
+    for (key, item) in values {
+        set_path(&mut doc, key, item.clone());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, doc.to_string())?;
+    Ok(())
+}
+
+fn set_path(doc: &mut DocumentMut, key: &str, value: Item) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().unwrap_or_default();
+    let mut item = doc.as_item_mut();
+
+    for part in parts {
+        if !item.get(part).is_some_and(|child| child.is_table_like()) {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            item[part] = Item::Table(table);
+        }
+        item = &mut item[part];
+    }
+
+    // keep the spacing and trailing comment of the value being replaced
+    let mut value = value;
+    if let (Some(old), Some(new)) = (
+        item.get(last).and_then(Item::as_value),
+        value.as_value_mut(),
+    ) {
+        *new.decor_mut() = old.decor().clone();
+    }
+    item[last] = value;
+}
+
+fn get_path<'a>(item: &'a Item, key: &str) -> Option<&'a Item> {
+    key.split('.').try_fold(item, |item, part| item.get(part))
+}
+
+/// Which file `gcz config set` writes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Repo,
+}
+
+/// Entry point of `gcz config get`: prints the value in effect, merging the files,
+/// the profile and `GCZ_*` overrides the same way [`load`] does.
+pub fn get(key: &str, profile: Option<&str>) -> Result<(), GczError> {
+    match effective_value(key, profile)? {
+        Some(item) => println!("{}", display_value(&item)),
+        None => eprintln!("`{}` is not set; gcz uses its default", key),
+    }
+    Ok(())
+}
+
+fn effective_value(key: &str, profile: Option<&str>) -> Result<Option<Item>, GczError> {
+    let (spec, entry) = resolve_key(key)?;
+    let profile = match profile {
+        Some(name) => Some(name.to_string()),
+        None if spec.name != "profile" => {
+            effective_value("profile", None)?.and_then(|item| item.as_str().map(String::from))
+        }
+        None => None,
+    };
+
+    let mut docs = Vec::new();
+    for path in config_paths()? {
+        let raw = fs::read_to_string(&path)?;
+        match Document::parse(raw.clone()) {
+            Ok(doc) => {
+                let doc = drop_shared_commands(doc, &path, &mut Vec::new());
+                docs.extend(extends::load(&doc, &path, &mut Vec::new()));
+                docs.push(doc);
+            }
+            Err(_) => return Err(GczError::InvalidConfig(check_source(&path, &raw))),
+        }
+    }
+
+    // in the order `load` applies them
+    let mut layers: Vec<Item> = docs
+        .iter()
+        .filter_map(|doc| doc.get(spec.name).cloned())
+        .collect();
+    layers.extend(
+        remote_tables(&docs)
+            .into_iter()
+            .filter_map(|table| table.get(spec.name).cloned()),
+    );
+    if let Some(name) = &profile {
+        layers.extend(docs.iter().filter_map(|doc| {
+            get_path(doc.as_item(), &format!("profiles.{}.{}", name, spec.name)).cloned()
+        }));
+    }
+    if let Ok(raw) = env::var(env_var_name(spec.name)) {
+        layers.extend(parse_env_value(spec.kind, &raw).map(Item::Value));
+    }
+
+    let mut layers = layers.into_iter().filter_map(|item| match entry {
+        Some(entry) => item.get(entry).cloned(),
+        None => Some(item),
+    });
+    if entry.is_some() || !matches!(spec.kind, Kind::StringMap | Kind::Keys) {
+        return Ok(layers.next_back());
+    }
+
+    // tables merge entry by entry, like `merge_string_map`
+    let mut table = InlineTable::new();
+    for layer in layers {
+        for (name, value) in layer.as_table_like().into_iter().flat_map(|t| t.iter()) {
+            if let Some(value) = value.as_value() {
+                table.insert(name, value.clone());
+            }
+        }
+    }
+    Ok((!table.is_empty()).then(|| Item::Value(Value::InlineTable(table))))
+}
+
+/// Strings print bare so scripts can use them; everything else prints as TOML.
+fn display_value(item: &Item) -> String {
+    if let Some(text) = item.as_str() {
+        return text.to_string();
+    }
+    if let Some(table) = item.as_table_like() {
+        return table
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, display_value_toml(value)))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    display_value_toml(item)
+}
+
+fn display_value_toml(item: &Item) -> String {
+    match item.as_value() {
+        Some(value) => value.clone().decorated("", "").to_string(),
+        None => item.to_string().trim().to_string(),
+    }
+}
+
+/// Entry point of `gcz config set`. Without a `scope` the value goes to the file that
+/// already sets the key, so the change takes effect, or else to the global config.
+pub fn set(
+    key: &str,
+    raw: &str,
+    scope: Option<Scope>,
+    profile: Option<&str>,
+) -> Result<(), GczError> {
+    let (spec, entry) = resolve_key(key)?;
+    let value = parse_argument(spec, entry, raw).ok_or_else(|| {
+        argument_error(format!(
+            "`{}` must be {}, found {:?}",
+            key,
+            value_kind(spec, entry),
+            raw
+        ))
+    })?;
+    let key = match profile {
+        Some(name) if spec.name == "profile" => {
+            return Err(argument_error(format!(
+                "`profile` cannot be set inside profile `{}`",
+                name
+            )))
+        }
+        Some(name) => format!("profiles.{}.{}", name, key),
+        None => key.to_string(),
+    };
+
+    let path = match scope {
+        Some(Scope::Global) => global_config_path(),
+        Some(Scope::Repo) => match repo_config_path()? {
+            Some(path) => Some(path),
+            None => return Err(GczError::NotARepository),
+        },
+        None => defining_file(&key)?.or_else(global_config_path),
+    };
+    let path = match path {
+        Some(path) => path,
+        None => {
+            return Err(argument_error(
+                "no home directory for the global config".into(),
+            ))
+        }
+    };
+
+    set_values(&path, &[(&key, Item::Value(value))])?;
+    println!("Updated {}", path.display());
+    Ok(())
+}
+
+/// Entry point of `gcz export-config`: prints every key that is set, with the value in
+/// effect, as one config file. Profiles and remote blocks are applied, not copied.
+pub fn export(profile: Option<&str>) -> Result<(), GczError> {
+    let mut doc = DocumentMut::new();
+    for spec in SCHEMA {
+        if matches!(spec.kind, Kind::Profiles) || spec.name == "profile" {
+            continue;
+        }
+        match effective_value(spec.name, profile)? {
+            Some(Item::Value(Value::InlineTable(table))) => {
+                set_path(&mut doc, spec.name, Item::Table(table.into_table()))
+            }
+            Some(item) => set_path(&mut doc, spec.name, item),
+            None => {}
+        }
+    }
+    println!("# gcz config in effect; share it as .gcz.toml or with `gcz import-config`");
+    print!("{}", doc);
+    Ok(())
+}
+
+/// Entry point of `gcz import-config`: merges the config file at `source` into the global
+/// config, or the repository's `.gcz.toml` with [`Scope::Repo`]. Its keys replace the ones
+/// already set, tables are merged entry by entry, and the rest of the file is kept.
+pub fn import(source: &Path, scope: Scope) -> Result<(), GczError> {
+    let raw = fs::read_to_string(source)?;
+    let diagnostics = check_source(source, &raw);
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        return Err(GczError::InvalidConfig(diagnostics));
+    }
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+    let imported: DocumentMut = raw
+        .parse()
+        .map_err(|_| GczError::InvalidConfig(diagnostics))?;
+
+    let path = match scope {
+        Scope::Global => global_config_path()
+            .ok_or_else(|| argument_error("no home directory for the global config".into()))?,
+        Scope::Repo => repo_config_path()?.ok_or(GczError::NotARepository)?,
+    };
+    let existing = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    let mut doc: DocumentMut = existing
+        .parse()
+        .map_err(|_| GczError::InvalidConfig(check_source(&path, &existing)))?;
+
+    merge_table(doc.as_table_mut(), imported.as_table());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, doc.to_string())?;
+    println!(
+        "Imported {} key(s) into {}",
+        imported.as_table().len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Copies the keys of `source` into `target`, descending into tables both have.
+fn merge_table(target: &mut dyn TableLike, source: &dyn TableLike) {
+    for (key, item) in source.iter() {
+        let Some(existing) = target.get_mut(key) else {
+            target.insert(key, item.clone());
+            continue;
+        };
+        if let (Some(existing), Some(source)) = (existing.as_table_like_mut(), item.as_table_like())
+        {
+            merge_table(existing, source);
+            continue;
+        }
+        // keep the comments around the value being replaced, as `set_path` does
+        let mut item = item.clone();
+        if let (Some(old), Some(new)) = (existing.as_value(), item.as_value_mut()) {
+            *new.decor_mut() = old.decor().clone();
+        }
+        *existing = item;
+    }
+}
+
+/// Writes `key` where `gcz config set` would without a scope, quietly.
+pub fn remember(key: &str, value: Value) -> Result<(), GczError> {
+    match defining_file(key)?.or_else(global_config_path) {
+        Some(path) => set_values(&path, &[(key, Item::Value(value))]),
+        None => Ok(()),
+    }
+}
+
+/// The config file with the highest precedence that sets `key`.
+fn defining_file(key: &str) -> Result<Option<PathBuf>, GczError> {
+    for path in config_paths()?.into_iter().rev() {
+        let raw = fs::read_to_string(&path)?;
+        if let Ok(doc) = Document::parse(raw) {
+            if get_path(doc.as_item(), key).is_some() {
+                return Ok(Some(path));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Splits `templates.feat` into the schema key and the table entry.
+fn resolve_key(key: &str) -> Result<(&'static KeySpec, Option<&str>), GczError> {
+    let (name, entry) = match key.split_once('.') {
+        Some((name, entry)) => (name, Some(entry)),
+        None => (key, None),
+    };
+    let spec = match SCHEMA.iter().find(|spec| spec.name == name) {
+        Some(spec) => spec,
+        None => {
+            let hint = closest_key(name)
+                .map(|name| format!(" (did you mean `{}`?)", name))
+                .unwrap_or_default();
+            return Err(argument_error(format!("unknown key `{}`{}", name, hint)));
+        }
+    };
+
+    match (spec.kind, entry) {
+        (Kind::Profiles, _) if spec.name == "remotes" => Err(argument_error(
+            "remote blocks can only be changed in the config files".into(),
+        )),
+        (Kind::Profiles, _) => Err(argument_error(
+            "set profile keys with `--profile <name>`, e.g. `gcz --profile work config set emoji true`"
+                .into(),
+        )),
+        (Kind::Keys, Some(action)) if !keymap::action_names().any(|name| name == action) => {
+            Err(argument_error(format!(
+                "unknown action `{}`; expected one of {}",
+                action,
+                keymap::action_names().collect::<Vec<_>>().join(", ")
+            )))
+        }
+        (Kind::StringMap | Kind::Keys, _) | (_, None) => Ok((spec, entry)),
+        (kind, Some(_)) => Err(argument_error(format!("`{}` is {}, not a table", name, kind))),
+    }
+}
+
+/// Parses a command-line value like an environment override; a single table entry is a
+/// plain string.
+fn parse_argument(spec: &KeySpec, entry: Option<&str>, raw: &str) -> Option<Value> {
+    match entry {
+        None => parse_env_value(spec.kind, raw),
+        Some(entry) => {
+            let mut table = InlineTable::new();
+            table.insert(entry, Value::from(raw));
+            matches_kind(&Item::Value(Value::InlineTable(table)), spec.kind)
+                .then(|| Value::from(raw))
+        }
+    }
+}
+
+fn value_kind(spec: &KeySpec, entry: Option<&str>) -> String {
+    match (spec.kind, entry) {
+        (Kind::Keys, Some(_)) => "space-separated keys such as \"ctrl+e\"".to_string(),
+        (_, Some(_)) => "a string".to_string(),
+        (kind, None) => kind.to_string(),
+    }
+}
+
+fn argument_error(message: String) -> GczError {
+    GczError::InvalidConfig(vec![Diagnostic {
+        location: Location::CommandLine,
+        severity: Severity::Error,
+        message,
+    }])
+}
+
+/// Keys naming a command gcz runs through the shell. Only the global config, `GCZ_*` and
+/// the command line set them: from a repository's `.gcz.toml` or a shared `extends` config,
+/// anyone who can commit there could run code on every contributor's machine.
+const COMMAND_KEYS: [&str; 2] = ["editor", "translate_command"];
+
+/// `doc` without the [`COMMAND_KEYS`], at the top level and in its profiles and remote
+/// blocks, warning about each one dropped.
+pub fn drop_commands(
+    doc: Document<String>,
+    path: &Path,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Document<String> {
+    let mut tables: Vec<(Vec<&str>, &dyn TableLike)> = vec![(Vec::new(), doc.as_table())];
+    for section in ["profiles", "remotes"] {
+        if let Some(blocks) = doc.get(section).and_then(Item::as_table_like) {
+            for (name, block) in blocks.iter() {
+                if let Some(block) = block.as_table_like() {
+                    tables.push((vec![section, name], block));
+                }
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for (parents, table) in &tables {
+        for name in COMMAND_KEYS {
+            if let Some((key, _)) = table.get_key_value(name) {
+                let offset = key.span().map_or(0, |span| span.start);
+                let (line, column) = line_column(doc.raw(), offset);
+                diagnostics.push(Diagnostic {
+                    location: Location::File {
+                        path: path.to_path_buf(),
+                        line,
+                        column,
+                    },
+                    severity: Severity::Warning,
+                    message: format!(
+                        "`{}` is ignored here; set it in the global config or with GCZ_{}",
+                        name,
+                        name.to_uppercase()
+                    ),
+                });
+                found.push((parents.clone(), name));
+            }
+        }
+    }
+    if found.is_empty() {
+        return doc;
+    }
+
+    let found: Vec<(Vec<String>, &str)> = found
+        .into_iter()
+        .map(|(parents, name)| (parents.into_iter().map(String::from).collect(), name))
+        .collect();
+    let mut doc = doc.into_mut();
+    for (parents, name) in found {
+        let mut item = doc.as_item_mut();
+        for parent in &parents {
+            item = &mut item[parent.as_str()];
+        }
+        if let Some(table) = item.as_table_like_mut() {
+            table.remove(name);
+        }
+    }
+    Document::parse(doc.to_string()).expect("a config without some keys still parses")
+}
+
+/// [`drop_commands`] for every config file but the global one.
+fn drop_shared_commands(
+    doc: Document<String>,
+    path: &Path,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Document<String> {
+    if global_config_path().as_deref() == Some(path) {
+        return doc;
+    }
+    drop_commands(doc, path, diagnostics)
+}
+
+/// Loads and validates every config file, then applies the blocks of `remotes` matching
+/// the repository's remote, the selected profile and `GCZ_*` environment overrides.
+/// Files with errors are not applied.
+///
+/// `profile` comes from `--profile` and wins over `GCZ_PROFILE` and the `profile` key.
+pub fn load(profile: Option<&str>) -> Result<(Config, Vec<Diagnostic>), GczError> {
+    let mut config = Config::default();
+    let mut diagnostics = Vec::new();
+    let mut docs = Vec::new();
+
+    for path in config_paths()? {
+        let raw = fs::read_to_string(&path)?;
+        let file_diagnostics = check_source(&path, &raw);
+        let has_errors = file_diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error);
+
+        diagnostics.extend(file_diagnostics);
+        if !has_errors {
+            if let Ok(doc) = Document::parse(raw) {
+                let doc = drop_shared_commands(doc, &path, &mut diagnostics);
+                // the shared config comes first, so the file's own keys win
+                if let Some(shared) = extends::load(&doc, &path, &mut diagnostics) {
+                    config.apply_table(shared.as_table());
+                    docs.push(shared);
+                }
+                config.apply_table(doc.as_table());
+                docs.push(doc);
+            }
+        }
+    }
+    for table in remote_tables(&docs) {
+        config.apply_table(table);
+    }
+
+    let env = env_values(&mut diagnostics);
+    let profile = profile
+        .map(String::from)
+        .or_else(|| {
+            env.iter()
+                .find(|(key, _)| *key == "profile")
+                .and_then(|(_, value)| value.as_str().map(String::from))
+        })
+        .or_else(|| config.profile.clone());
+
+    if let Some(name) = profile {
+        let tables: Vec<&dyn TableLike> = docs
+            .iter()
+            .filter_map(|doc| {
+                doc.get("profiles")
+                    .and_then(|profiles| profiles.as_table_like())
+                    .and_then(|profiles| profiles.get(&name))
+                    .and_then(|profile| profile.as_table_like())
+            })
+            .collect();
+
+        if tables.is_empty() {
+            diagnostics.push(Diagnostic {
+                location: Location::Profile(name.clone()),
+                severity: Severity::Error,
+                message: format!("no such profile; define it under [profiles.{}]", name),
+            });
+        }
+        for table in tables {
+            config.apply_table(table);
+        }
+        config.profile = Some(name);
+    }
+
+    for (key, value) in env {
+        config.apply(key, &value);
+    }
+    let disabled = &config.disabled_types;
+    config.types.retain(|t| !disabled.contains(t));
+
+    Ok((config, diagnostics))
+}
+
+/// The `[remotes."<pattern>"]` tables whose pattern matches the URL of the repository's
+/// remote, in file order.
+fn remote_tables(docs: &[Document<String>]) -> Vec<&dyn TableLike> {
+    let blocks: Vec<(&str, &dyn TableLike)> = docs
+        .iter()
+        .filter_map(|doc| {
+            doc.get("remotes")
+                .and_then(|remotes| remotes.as_table_like())
+        })
+        .flat_map(|remotes| remotes.iter())
+        .filter_map(|(pattern, table)| Some((pattern, table.as_table_like()?)))
+        .collect();
+    if blocks.is_empty() {
+        return Vec::new();
+    }
+    let Some(url) = git::remote_url().ok().flatten() else {
+        return Vec::new();
+    };
+    blocks
+        .into_iter()
+        .filter(|(pattern, _)| remote_matches(pattern, &url))
+        .map(|(_, table)| table)
+        .collect()
+}
+
+/// Matches a remote URL against a glob such as `github.com/acme/*`. SSH, scp-like and
+/// HTTPS URLs of a repository all read as `host/owner/repo`, and a pattern without `/`
+/// matches every repository on that host.
+fn remote_matches(pattern: &str, url: &str) -> bool {
+    let url = url.trim();
+    let location = match url.split_once("://") {
+        Some((_, rest)) => {
+            let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+            let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+            // ssh://git@host:2222/owner/repo
+            let host = host.split_once(':').map_or(host, |(host, _)| host);
+            format!("{}/{}", host, path)
+        }
+        // git@host:owner/repo
+        None => {
+            let rest = url.split_once('@').map_or(url, |(_, rest)| rest);
+            rest.replacen(':', "/", 1)
+        }
+    };
+    let location = location.trim_end_matches('/');
+    let location = location.strip_suffix(".git").unwrap_or(location);
+
+    let pattern = pattern.trim().trim_end_matches('/');
+    let pattern = pattern.strip_suffix(".git").unwrap_or(pattern);
+    if pattern.contains('/') {
+        glob::matches(pattern, location)
+    } else {
+        glob::matches(&format!("{}/**", pattern), location)
+    }
+}
+
+/// Reads the `GCZ_*` overrides of every schema key, reporting unparsable ones.
+fn env_values(diagnostics: &mut Vec<Diagnostic>) -> Vec<(&'static str, Item)> {
+    let mut values = Vec::new();
+
+    for spec in SCHEMA.iter().filter(|spec| spec.kind != Kind::Profiles) {
+        let name = env_var_name(spec.name);
+        let raw = match env::var(&name) {
+            Ok(raw) => raw,
+            Err(_) => continue,
+        };
+
+        match parse_env_value(spec.kind, &raw) {
+            Some(value) => values.push((spec.name, Item::Value(value))),
+            None => diagnostics.push(Diagnostic {
+                location: Location::Env(name),
+                severity: Severity::Error,
+                message: format!("`{}` must be {}, found {:?}", spec.name, spec.kind, raw),
+            }),
+        }
+    }
+    values
+}
+
+/// `types` is overridden by `GCZ_TYPES`.
+pub fn env_var_name(key: &str) -> String {
+    format!("GCZ_{}", key.to_uppercase())
+}
+
+/// Parses an environment variable or a `gcz config set` value the way the schema
+/// expects: booleans accept `1/0`, `true/false`, `yes/no` and `on/off`; lists are
+/// comma-separated and tables are comma-separated `key=value` pairs.
+fn parse_env_value(kind: Kind, raw: &str) -> Option<Value> {
+    match kind {
+        Kind::Bool => match raw.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(Value::from(true)),
+            "0" | "false" | "no" | "off" => Some(Value::from(false)),
+            _ => None,
+        },
+        Kind::BoolOrAuto => match raw.trim().to_lowercase().as_str() {
+            "auto" => Some(Value::from("auto")),
+            raw => parse_env_value(Kind::Bool, raw),
+        },
+        Kind::String => Some(Value::from(raw)),
+        Kind::Enum(values) => values
+            .contains(&raw.trim())
+            .then(|| Value::from(raw.trim())),
+        Kind::Size => {
+            let value = Value::from(raw.trim());
+            parse_size(&Item::Value(value.clone())).map(|_| value)
+        }
+        Kind::Duration => {
+            let value = Value::from(raw.trim());
+            parse_duration(&Item::Value(value.clone())).map(|_| value)
+        }
+        Kind::StringMap | Kind::Keys => raw
+            .split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .map(|entry| {
+                entry
+                    .split_once('=')
+                    .map(|(k, v)| (k.trim().to_string(), Value::from(v)))
+            })
+            .collect::<Option<toml_edit::InlineTable>>()
+            .map(Value::InlineTable)
+            .filter(|value| matches_kind(&Item::Value(value.clone()), kind)),
+        Kind::Profiles => None,
+        Kind::StringList | Kind::NameList | Kind::RegexList => {
+            let value = Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .collect(),
+            );
+            matches_kind(&Item::Value(value.clone()), kind).then_some(value)
+        }
+    }
+}
+
+/// Entry point of `gcz config check`: fails when any config file has a problem.
+pub fn check() -> Result<(), GczError> {
+    let mut diagnostics = Vec::new();
+    let paths = config_paths()?;
+
+    for path in &paths {
+        let raw = fs::read_to_string(path)?;
+        diagnostics.extend(check_source(path, &raw));
+        if let Ok(doc) = Document::parse(raw) {
+            let doc = drop_shared_commands(doc, path, &mut diagnostics);
+            extends::load(&doc, path, &mut diagnostics);
+        }
+    }
+    env_values(&mut diagnostics);
+
+    if !diagnostics.is_empty() {
+        return Err(GczError::InvalidConfig(diagnostics));
+    }
+    for path in &paths {
+        println!("{}: ok", path.display());
+    }
+    Ok(())
+}
+
+/// Validates a config file against [`SCHEMA`].
+pub fn check_source(path: &Path, raw: &str) -> Vec<Diagnostic> {
+    let diagnostic = |offset: usize, severity: Severity, message: String| {
+        let (line, column) = line_column(raw, offset);
+        Diagnostic {
+            location: Location::File {
+                path: path.to_path_buf(),
+                line,
+                column,
+            },
+            severity,
+            message,
+        }
+    };
+
+    let doc = match Document::parse(raw) {
+        Ok(doc) => doc,
+        Err(err) => {
+            let offset = err.span().map_or(0, |span| span.start);
+            return vec![diagnostic(
+                offset,
+                Severity::Error,
+                err.message().to_string(),
+            )];
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    check_table(doc.as_table(), None, &mut diagnostics, &diagnostic);
+    diagnostics
+}
+
+/// Checks the keys of the root table, or of the profile or remote block `within` names.
+fn check_table(
+    table: &dyn TableLike,
+    within: Option<&str>,
+    diagnostics: &mut Vec<Diagnostic>,
+    diagnostic: &dyn Fn(usize, Severity, String) -> Diagnostic,
+) {
+    for (key, item) in table.iter() {
+        let offset = table
+            .key(key)
+            .and_then(|k| k.span())
+            .map_or(0, |span| span.start);
+
+        let spec = match SCHEMA.iter().find(|spec| spec.name == key) {
+            Some(spec) => spec,
+            None => {
+                let hint = closest_key(key)
+                    .map(|name| format!(" (did you mean `{}`?)", name))
+                    .unwrap_or_default();
+                diagnostics.push(diagnostic(
+                    offset,
+                    Severity::Warning,
+                    format!("unknown key `{}`{}", key, hint),
+                ));
+                continue;
+            }
+        };
+
+        // a remote block may pick the profile, a profile can't pick another
+        let nested = spec.kind == Kind::Profiles || (key == "profile" && within == Some("profile"));
+        if let Some(within) = within.filter(|_| nested) {
+            diagnostics.push(diagnostic(
+                offset,
+                Severity::Error,
+                format!("`{}` cannot be set inside a {}", key, within),
+            ));
+            continue;
+        }
+
+        let value_offset = item.span().map_or(offset, |span| span.start);
+        if spec.kind == Kind::Profiles {
+            let block = if key == "remotes" {
+                "remote block"
+            } else {
+                "profile"
+            };
+            match item.as_table_like() {
+                Some(blocks) => {
+                    for (name, table) in blocks.iter() {
+                        match table.as_table_like() {
+                            Some(table) => check_table(table, Some(block), diagnostics, diagnostic),
+                            None => diagnostics.push(diagnostic(
+                                table.span().map_or(value_offset, |span| span.start),
+                                Severity::Error,
+                                format!("{} `{}` must be a table", block, name),
+                            )),
+                        }
+                    }
+                }
+                None => diagnostics.push(diagnostic(
+                    value_offset,
+                    Severity::Error,
+                    format!("`{}` must be {}", key, spec.kind),
+                )),
+            }
+            continue;
+        }
+
+        if !matches_kind(item, spec.kind) {
+            let found = match (spec.kind, item.as_str()) {
+                (Kind::Enum(_), Some(value)) => format!("{:?}", value),
+                _ => item_type_name(item).to_string(),
+            };
+            diagnostics.push(diagnostic(
+                value_offset,
+                Severity::Error,
+                format!("`{}` must be {}, found {}", key, spec.kind, found),
+            ));
+        }
+    }
+
+    if let Some(types) = table.get("types").and_then(|item| item.as_array()) {
+        let mut seen = Vec::new();
+        for value in types.iter() {
+            if let Some(name) = value.as_str() {
+                if seen.contains(&name) {
+                    diagnostics.push(diagnostic(
+                        value.span().map_or(0, |span| span.start),
+                        Severity::Error,
+                        format!("type `{}` is listed more than once in `types`", name),
+                    ));
+                }
+                seen.push(name);
+            }
+        }
+    }
+    check_conflicts(table, within, diagnostics, diagnostic);
+}
+
+/// Options that contradict each other within one table. Types are only compared with a
+/// `types` list set in the same table, since another file may define the rest.
+fn check_conflicts(
+    table: &dyn TableLike,
+    within: Option<&str>,
+    diagnostics: &mut Vec<Diagnostic>,
+    diagnostic: &dyn Fn(usize, Severity, String) -> Diagnostic,
+) {
+    let offset = |value: &Value| value.span().map_or(0, |span| span.start);
+
+    if let Some(types) = table.get("types").and_then(Item::as_array) {
+        let types: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+        let mut named: Vec<(&str, &Value)> = Vec::new();
+        if let Some(favorites) = table.get("favorite_types").and_then(Item::as_array) {
+            named.extend(favorites.iter().map(|value| ("favorite_types", value)));
+        }
+        if let Some(aliases) = table.get("type_aliases").and_then(Item::as_table_like) {
+            named.extend(
+                aliases
+                    .iter()
+                    .filter_map(|(_, item)| Some(("type_aliases", item.as_value()?))),
+            );
+        }
+        for (key, value) in named {
+            if let Some(name) = value.as_str().filter(|name| !types.contains(name)) {
+                diagnostics.push(diagnostic(
+                    offset(value),
+                    Severity::Warning,
+                    format!("`{}` names `{}`, which isn't in `types`", key, name),
+                ));
+            }
+        }
+
+        if let Some(disabled) = table.get("disabled_types").and_then(Item::as_array) {
+            let disabled: Vec<&str> = disabled.iter().filter_map(Value::as_str).collect();
+            if types.iter().all(|name| disabled.contains(name)) {
+                diagnostics.push(diagnostic(
+                    table
+                        .get("disabled_types")
+                        .and_then(Item::span)
+                        .map_or(0, |span| span.start),
+                    Severity::Error,
+                    "`disabled_types` leaves no type to pick from `types`".to_string(),
+                ));
+            }
+        }
+    }
+
+    // a profile may rely on the `ask_scope` of the file's top level
+    let ask_scope = table.get("ask_scope").and_then(Item::as_bool);
+    let scope_pane = table.get_key_value("scope_pane");
+    if let Some((key, item)) = scope_pane.filter(|(_, item)| item.as_bool() == Some(true)) {
+        if ask_scope == Some(false) || (within.is_none() && ask_scope.is_none()) {
+            diagnostics.push(diagnostic(
+                item.span()
+                    .or_else(|| key.span())
+                    .map_or(0, |span| span.start),
+                Severity::Warning,
+                "`scope_pane` has no effect without `ask_scope = true`".to_string(),
+            ));
+        }
+    }
+}
+
+fn matches_kind(item: &Item, kind: Kind) -> bool {
+    match kind {
+        Kind::Bool => item.is_bool(),
+        Kind::BoolOrAuto => item.is_bool() || item.as_str() == Some("auto"),
+        Kind::String => item.is_str(),
+        Kind::Enum(values) => item.as_str().is_some_and(|v| values.contains(&v)),
+        Kind::Profiles => false,
+        Kind::StringList => item
+            .as_array()
+            .is_some_and(|array| array.iter().all(|v| v.is_str())),
+        Kind::NameList => item.as_array().is_some_and(|array| {
+            array
+                .iter()
+                .all(|v| v.as_str().is_some_and(plugin::is_valid_name))
+        }),
+        Kind::RegexList => item.as_array().is_some_and(|array| {
+            array
+                .iter()
+                .all(|v| v.as_str().is_some_and(|re| regex::Regex::new(re).is_ok()))
+        }),
+        Kind::Size => parse_size(item).is_some(),
+        Kind::Duration => parse_duration(item).is_some(),
+        Kind::Keys => item.as_table_like().is_some_and(|table| {
+            table.iter().all(|(action, v)| {
+                v.as_str()
+                    .is_some_and(|keys| keymap::is_valid_binding(action, keys))
+            })
+        }),
+        Kind::StringMap => item
+            .as_table_like()
+            .is_some_and(|table| table.iter().all(|(_, v)| v.is_str())),
+    }
+}
+
+fn item_type_name(item: &Item) -> &'static str {
+    match item.as_value() {
+        Some(Value::String(_)) => "a string",
+        Some(Value::Integer(_)) => "an integer",
+        Some(Value::Float(_)) => "a float",
+        Some(Value::Boolean(_)) => "a boolean",
+        Some(Value::Datetime(_)) => "a datetime",
+        Some(Value::Array(_)) => "an array",
+        Some(Value::InlineTable(_)) | None => "a table",
+    }
+}
+
+/// Reads a byte count from an integer or a string with a `B`, `KB`, `MB` or `GB` suffix.
+fn parse_size(item: &Item) -> Option<u64> {
+    if let Some(bytes) = item.as_integer() {
+        return u64::try_from(bytes).ok();
+    }
+
+    let raw = item.as_str()?.trim().to_uppercase();
+    let digits_end = raw
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(raw.len());
+    let number: f64 = raw[..digits_end].parse().ok()?;
+    let multiplier = match raw[digits_end..].trim() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+fn parse_duration(item: &Item) -> Option<u64> {
+    if let Some(seconds) = item.as_integer() {
+        return u64::try_from(seconds).ok();
+    }
+
+    let raw = item.as_str()?.trim().to_lowercase();
+    let digits_end = raw.find(|c: char| !c.is_ascii_digit()).unwrap_or(raw.len());
+    let number: u64 = raw[..digits_end].parse().ok()?;
+    let multiplier = match raw[digits_end..].trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return None,
+    };
+    Some(number * multiplier)
+}
+
+/// `Some(None)` for `"auto"`.
+fn bool_or_auto(item: &Item) -> Option<Option<bool>> {
+    match item.as_str() {
+        Some("auto") => Some(None),
+        _ => item.as_bool().map(Some),
+    }
+}
+
+fn set_string(field: &mut String, item: &Item) {
+    if let Some(value) = item.as_str() {
+        *field = value.to_string();
+    }
+}
+
+/// Later layers override individual entries rather than the whole table.
+fn merge_string_map(map: &mut Vec<(String, String)>, item: &Item) {
+    let table = match item.as_table_like() {
+        Some(table) => table,
+        None => return,
+    };
+
+    for (key, value) in table.iter() {
+        if let Some(value) = value.as_str() {
+            map.retain(|(k, _)| k != key);
+            map.push((key.to_string(), value.to_string()));
+        }
+    }
+}
+
+fn string_list(item: &Item) -> Vec<String> {
+    item.as_array()
+        .map(|array| {
+            array
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn line_column(raw: &str, offset: usize) -> (usize, usize) {
+    let before = &raw[..offset.min(raw.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+fn closest_key(key: &str) -> Option<&'static str> {
+    SCHEMA
+        .iter()
+        .map(|spec| (spec.name, edit_distance(key, spec.name)))
+        .filter(|&(_, distance)| distance <= 2)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(name, _)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_suggest_type_from_rules() {
+        let config = Config {
+            type_rules: vec![
+                ("docs/**".to_string(), "docs".to_string()),
+                ("*.md".to_string(), "docs".to_string()),
+                ("**/*_test.rs".to_string(), "test".to_string()),
+            ],
+            ..Config::default()
+        };
+        let paths = |paths: &[&str]| paths.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            config.suggested_type(&paths(&["docs/intro.md", "README.md"])),
+            Some("docs")
+        );
+        assert_eq!(
+            config.suggested_type(&paths(&["docs/intro.md", "src/a_test.rs"])),
+            None
+        );
+        assert_eq!(
+            config.suggested_type(&paths(&["src/a_test.rs", "src/main.rs"])),
+            None
+        );
+    }
+
+    #[test]
+    fn should_set_dotted_keys_and_keep_comments() {
+        let mut doc: DocumentMut = "# my settings\nemoji = false # off for now\n"
+            .parse()
+            .unwrap();
+        set_path(&mut doc, "emoji", toml_edit::value(true));
+        set_path(&mut doc, "templates.feat", toml_edit::value("add {}"));
+        set_path(&mut doc, "profiles.work.signoff", toml_edit::value(true));
+
+        assert_eq!(
+            doc.to_string(),
+            "# my settings\nemoji = true # off for now\n\n[templates]\nfeat = \"add {}\"\n\n[profiles.work]\nsignoff = true\n"
+        );
+        assert_eq!(
+            get_path(doc.as_item(), "templates.feat").and_then(|item| item.as_str()),
+            Some("add {}")
+        );
+    }
+
+    #[test]
+    fn should_merge_imported_keys_into_tables() {
+        let mut doc: DocumentMut = "# mine\nemoji = true\n\n[templates]\nfeat = \"add {}\"\n"
+            .parse()
+            .unwrap();
+        let imported: DocumentMut =
+            "emoji = false\ntypes = [\"feat\", \"fix\"]\n\n[templates]\nfix = \"fix {}\"\n"
+                .parse()
+                .unwrap();
+        merge_table(doc.as_table_mut(), imported.as_table());
+
+        assert_eq!(
+            doc.to_string(),
+            "# mine\nemoji = false\ntypes = [\"feat\", \"fix\"]\n\n[templates]\nfeat = \"add {}\"\nfix = \"fix {}\"\n"
+        );
+    }
+
+    #[test]
+    fn should_report_unknown_keys_and_wrong_types() {
+        let raw = "emojis = true\ntypes = \"feat\"\n";
+        let diagnostics = check_source(Path::new(".gcz.toml"), raw);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                ".gcz.toml:1:1: warning: unknown key `emojis` (did you mean `emoji`?)",
+                ".gcz.toml:2:9: error: `types` must be an array of strings, found a string",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_refuse_plugin_names_with_paths() {
+        let raw = "plugins = [\"jira\", \"../x\"]\n[profiles.work]\nplugins = [\"a/b\"]\n";
+        let diagnostics = check_source(Path::new(".gcz.toml"), raw);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                ".gcz.toml:1:11: error: `plugins` must be an array of names made of letters, digits, - and _, found an array",
+                ".gcz.toml:3:11: error: `plugins` must be an array of names made of letters, digits, - and _, found an array",
+            ]
+        );
+        assert!(parse_env_value(Kind::NameList, "jira,../x").is_none());
+    }
+
+    #[test]
+    fn should_drop_commands_from_shared_configs() {
+        let raw = "translate_command = \"curl x | sh\"\n[profiles.work]\neditor = \"y\"\nsignoff = true\n";
+        let mut diagnostics = Vec::new();
+        let doc = drop_commands(
+            Document::parse(raw.to_string()).unwrap(),
+            Path::new(".gcz.toml"),
+            &mut diagnostics,
+        );
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                ".gcz.toml:1:1: warning: `translate_command` is ignored here; set it in the global config or with GCZ_TRANSLATE_COMMAND",
+                ".gcz.toml:3:1: warning: `editor` is ignored here; set it in the global config or with GCZ_EDITOR",
+            ]
+        );
+        let mut config = Config::default();
+        config.apply_table(doc.as_table());
+        config.apply_table(doc["profiles"]["work"].as_table_like().unwrap());
+        assert_eq!(config.translate_command, None);
+        assert_eq!(config.editor, None);
+        assert!(config.signoff);
+    }
+
+    #[test]
+    fn should_check_keys_inside_profiles() {
+        let raw = "[profiles.work]\nsignoff = \"yes\"\nprofile = \"oss\"\n";
+        let diagnostics = check_source(Path::new("config.toml"), raw);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "config.toml:2:11: error: `signoff` must be a boolean, found a string",
+                "config.toml:3:1: error: `profile` cannot be set inside a profile",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_match_remote_urls() {
+        let pattern = "github.com/acme/*";
+        assert!(remote_matches(pattern, "git@github.com:acme/api.git"));
+        assert!(remote_matches(pattern, "https://github.com/acme/api.git"));
+        assert!(remote_matches(pattern, "ssh://git@github.com:22/acme/api"));
+        assert!(!remote_matches(pattern, "https://github.com/other/api"));
+        assert!(!remote_matches(pattern, "https://github.com/acme/api/wiki"));
+        assert!(remote_matches(
+            "gitlab.example.com",
+            "git@gitlab.example.com:a/b/c.git"
+        ));
+
+        let raw = "[remotes.\"github.com/acme/*\"]\nprofile = \"work\"\nremotes = {}\n";
+        let diagnostics = check_source(Path::new("config.toml"), raw);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec!["config.toml:3:1: error: `remotes` cannot be set inside a remote block"]
+        );
+    }
+
+    #[test]
+    fn should_add_extra_types() {
+        let mut config = Config::default();
+        config.add_extra_type("deploy::rocket:");
+        config.add_extra_type("wip");
+        config.add_extra_type("feat:🌟");
+
+        assert_eq!(config.types[config.types.len() - 2..], ["deploy", "wip"]);
+        assert_eq!(config.types.iter().filter(|t| *t == "feat").count(), 1);
+        assert_eq!(
+            config.type_emojis,
+            vec![
+                ("deploy".to_string(), ":rocket:".to_string()),
+                ("feat".to_string(), "🌟".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_sizes() {
+        assert_eq!(parse_size(&Item::Value(Value::from(1024))), Some(1024));
+        assert_eq!(
+            parse_size(&Item::Value(Value::from("5MB"))),
+            Some(5 * 1024 * 1024)
+        );
+        assert_eq!(parse_size(&Item::Value(Value::from("1.5k"))), Some(1536));
+        assert_eq!(parse_size(&Item::Value(Value::from("huge"))), None);
+        assert_eq!(parse_duration(&Item::Value(Value::from("5m"))), Some(300));
+        assert_eq!(parse_duration(&Item::Value(Value::from("1.5m"))), None);
+    }
+
+    #[test]
+    fn should_parse_env_values() {
+        assert_eq!(
+            parse_env_value(Kind::Bool, "1").and_then(|v| v.as_bool()),
+            Some(true)
+        );
+        assert_eq!(
+            parse_env_value(Kind::Bool, "Off").and_then(|v| v.as_bool()),
+            Some(false)
+        );
+        assert!(parse_env_value(Kind::Bool, "maybe").is_none());
+        assert!(parse_env_value(Kind::StringMap, "release=v1,deps").is_none());
+        assert_eq!(
+            string_list(&Item::Value(
+                parse_env_value(Kind::StringList, "feat, fix,,chore").unwrap()
+            )),
+            vec!["feat", "fix", "chore"]
+        );
+    }
+
+    #[test]
+    fn should_report_duplicate_types() {
+        let raw = "types = [\"feat\", \"fix\", \"feat\"]\n";
+        let diagnostics = check_source(Path::new(".gcz.toml"), raw);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(
+            diagnostics[0].location.to_string(),
+            ".gcz.toml:1:25".to_string()
+        );
+    }
+
+    #[test]
+    fn should_report_conflicting_options() {
+        let raw = "types = [\"feat\", \"fix\"]\nfavorite_types = [\"docs\"]\n\
+                   disabled_types = [\"feat\", \"fix\"]\nscope_pane = true\n\
+                   [type_aliases]\nf = \"feat\"\nd = \"doc\"\n";
+        let diagnostics = check_source(Path::new(".gcz.toml"), raw);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                ".gcz.toml:2:19: warning: `favorite_types` names `docs`, which isn't in `types`",
+                ".gcz.toml:7:5: warning: `type_aliases` names `doc`, which isn't in `types`",
+                ".gcz.toml:3:18: error: `disabled_types` leaves no type to pick from `types`",
+                ".gcz.toml:4:14: warning: `scope_pane` has no effect without `ask_scope = true`",
+            ]
+        );
+        assert!(check_source(
+            Path::new(".gcz.toml"),
+            "ask_scope = true\nscope_pane = true\n"
+        )
+        .is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use std::fmt;
+
+/// The header line of a conventional commit: `type(scope)!: description`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Header<'a> {
+    pub commit_type: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    MissingType,
+    InvalidType(char),
+    UnclosedScope,
+    EmptyScope,
+    MissingColon,
+    MissingSpace,
+    EmptyDescription,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingType => write!(f, "header must start with a type"),
+            ParseError::InvalidType(c) => write!(f, "unexpected character '{}' in type", c),
+            ParseError::UnclosedScope => write!(f, "scope is missing a closing ')'"),
+            ParseError::EmptyScope => write!(f, "scope must not be empty"),
+            ParseError::MissingColon => write!(f, "expected ':' after the type"),
+            ParseError::MissingSpace => write!(f, "expected a space after ':'"),
+            ParseError::EmptyDescription => write!(f, "description must not be empty"),
+        }
+    }
+}
+
+fn is_type_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Whether `name` can be used as a commit type.
+pub fn is_type_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(is_type_char)
+}
+
+pub fn parse_header(line: &str) -> Result<Header<'_>, ParseError> {
+    let line = strip_emoji(line);
+    let type_end = line.find(|c: char| !is_type_char(c)).unwrap_or(line.len());
+    if type_end == 0 {
+        return Err(ParseError::MissingType);
+    }
+    let commit_type = &line[..type_end];
+    let mut rest = &line[type_end..];
+
+    let mut scope = None;
+    if let Some(after_paren) = rest.strip_prefix('(') {
+        let close = after_paren.find(')').ok_or(ParseError::UnclosedScope)?;
+        if after_paren[..close].trim().is_empty() {
+            return Err(ParseError::EmptyScope);
+        }
+        scope = Some(&after_paren[..close]);
+        rest = &after_paren[close + 1..];
+    }
+
+    let breaking = rest.starts_with('!');
+    if breaking {
+        rest = &rest[1..];
+    }
+
+    let rest = match rest.strip_prefix(':') {
+        Some(rest) => rest,
+        None => {
+            return Err(match rest.chars().next() {
+                Some(c) if c != ' ' && scope.is_none() && !breaking => ParseError::InvalidType(c),
+                _ => ParseError::MissingColon,
+            })
+        }
+    };
+    let description = rest.strip_prefix(' ').ok_or(ParseError::MissingSpace)?;
+    if description.trim().is_empty() {
+        return Err(ParseError::EmptyDescription);
+    }
+
+    Ok(Header {
+        commit_type,
+        scope,
+        breaking,
+        description,
+    })
+}
+
+/// Splits a full message into its header line and the remainder (body and footers).
+pub fn split_message(message: &str) -> (&str, &str) {
+    match message.split_once('\n') {
+        Some((header, rest)) => (header, rest),
+        None => (message, ""),
+    }
+}
+
+/// Splits `header` before its description, e.g. into `feat(api): ` and `add endpoint`; the
+/// prefix is empty when `header` isn't conventional.
+pub fn split_prefix(header: &str) -> (&str, &str) {
+    match parse_header(header) {
+        Ok(parsed) if header.ends_with(parsed.description) => {
+            header.split_at(header.len() - parsed.description.len())
+        }
+        _ => ("", header),
+    }
+}
+
+/// The prefix of `header`, like [`split_prefix`], but also of one whose description hasn't
+/// been typed yet, such as `feat(api): `.
+pub fn prefix(header: &str) -> &str {
+    let (prefix, description) = split_prefix(header);
+    if prefix.is_empty()
+        && description.trim_end().ends_with(':')
+        && parse_header(&format!("{} x", description.trim_end())).is_ok()
+    {
+        return header;
+    }
+    prefix
+}
+
+/// Skips the emojis put before the type, as in `✨ feat: ...` or `:sparkles: 🌐 feat: ...`.
+fn strip_emoji(mut line: &str) -> &str {
+    while let Some((first, rest)) = line.split_once(' ') {
+        if !is_emoji(first) {
+            break;
+        }
+        line = rest;
+    }
+    line
+}
+
+/// Whether a word of a header is an emoji or a `:shortcode:`.
+fn is_emoji(word: &str) -> bool {
+    let is_shortcode = word.len() > 2
+        && word.starts_with(':')
+        && word.ends_with(':')
+        && word[1..word.len() - 1]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_+-".contains(c));
+    is_shortcode || emojis::get(word).is_some()
+}
+
+/// Whether `header` has an emoji anywhere, e.g. `feat: ✨ ...` or `:bug: fix ...`.
+pub fn has_emoji(header: &str) -> bool {
+    header.split_whitespace().any(is_emoji)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_header() {
+        assert_eq!(
+            parse_header("feat(api)!: add endpoint"),
+            Ok(Header {
+                commit_type: "feat",
+                scope: Some("api"),
+                breaking: true,
+                description: "add endpoint",
+            })
+        );
+        assert_eq!(
+            parse_header("fix: typo").map(|h| (h.commit_type, h.scope)),
+            Ok(("fix", None))
+        );
+        assert_eq!(prefix("feat(api): "), "feat(api): ");
+        assert_eq!(prefix("fix: typo"), "fix: ");
+        assert_eq!(prefix("typo"), "");
+        assert_eq!(
+            parse_header("✨ feat(ui): add button").map(|h| (h.commit_type, h.description)),
+            Ok(("feat", "add button"))
+        );
+        assert_eq!(
+            parse_header(":bug: 🌐 fix: typo").map(|h| h.commit_type),
+            Ok("fix")
+        );
+        assert_eq!(
+            split_prefix("✨ feat(api): エンドポイントを追加"),
+            ("✨ feat(api): ", "エンドポイントを追加")
+        );
+        assert_eq!(
+            split_prefix("Merge branch 'main'"),
+            ("", "Merge branch 'main'")
+        );
+        assert!(has_emoji("feat: ✨ add button"));
+        assert!(!has_emoji("docs: a → b"));
+    }
+
+    #[test]
+    fn should_reject_invalid_header() {
+        assert_eq!(parse_header("update readme"), Err(ParseError::MissingColon));
+        assert_eq!(parse_header("feat.x: y"), Err(ParseError::InvalidType('.')));
+        assert_eq!(parse_header(": nothing"), Err(ParseError::MissingType));
+        assert_eq!(parse_header("feat(api: x"), Err(ParseError::UnclosedScope));
+        assert_eq!(parse_header("feat:x"), Err(ParseError::MissingSpace));
+        assert_eq!(parse_header("feat: "), Err(ParseError::EmptyDescription));
+        assert!(is_type_name("deploy"));
+        assert!(!is_type_name("dep ok"));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::config::{self, Config, Severity};
+use crate::{editor, git, glyph, hook, jj, GczError};
+use crossterm::style::{Color, Print, SetForegroundColor};
+use crossterm::{execute, terminal};
+use std::env;
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+/// The oldest git with `git switch` and `git restore`, which gcz runs.
+const MIN_GIT_VERSION: (u32, u32) = (2, 23);
+
+/// Hooks that run on `git commit`.
+const COMMIT_HOOKS: [&str; 3] = ["pre-commit", "prepare-commit-msg", "commit-msg"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// One line of the report; `detail` may continue on indented lines.
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Check {
+        Check {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Entry point of `gcz doctor`: checks git, the repository, the config, the editor, the
+/// terminal, commit signing and the hooks, and fails when any of them has an error.
+pub fn doctor(profile: Option<&str>) -> Result<(), GczError> {
+    let mut checks = vec![check_git()];
+    let in_repo = git::toplevel().ok().flatten();
+    checks.push(match &in_repo {
+        Some(root) if jj::workspace_root().is_some() => Check::new(
+            "repository",
+            Status::Ok,
+            format!("{} (jj workspace)", root.display()),
+        ),
+        Some(root) => Check::new("repository", Status::Ok, root.display().to_string()),
+        None => Check::new(
+            "repository",
+            Status::Warning,
+            "not inside a git repository; the hooks aren't checked",
+        ),
+    });
+
+    let config = match config::load(profile) {
+        Ok((config, diagnostics)) => {
+            checks.push(check_config(&diagnostics));
+            config
+        }
+        Err(err) => {
+            checks.push(Check::new("config", Status::Error, err.to_string()));
+            Config::default()
+        }
+    };
+    checks.push(check_editor(&config));
+    checks.push(check_terminal());
+    // without git these fail too; that is reported rather than cutting the report short
+    checks.push(
+        check_signing().unwrap_or_else(|err| Check::new("signing", Status::Error, err.to_string())),
+    );
+    if in_repo.is_some() {
+        checks.push(
+            check_hooks().unwrap_or_else(|err| Check::new("hooks", Status::Error, err.to_string())),
+        );
+    }
+    if config.pull_request {
+        checks.push(if on_path("gh") {
+            Check::new("gh", Status::Ok, "found, for `pull_request`")
+        } else {
+            Check::new(
+                "gh",
+                Status::Error,
+                "`pull_request` is on, but gh isn't on PATH",
+            )
+        });
+    }
+
+    let stdout = &mut io::stdout();
+    // plain when piped, e.g. into an issue report
+    let colored = stdout.is_terminal();
+    for check in &checks {
+        let (mark, color) = match check.status {
+            Status::Ok => (glyph::get().ok, Color::Green),
+            Status::Warning => (glyph::get().warning, Color::Yellow),
+            Status::Error => (glyph::get().error, Color::Red),
+        };
+        if !colored {
+            println!("{} {:<11} {}", mark, check.name, check.detail);
+            continue;
+        }
+        execute!(
+            stdout,
+            SetForegroundColor(color),
+            Print(mark),
+            SetForegroundColor(Color::Reset),
+            Print(format!(" {:<11} {}\n", check.name, check.detail))
+        )?;
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Error).count();
+    if failed > 0 {
+        return Err(GczError::ChecksFailed(failed));
+    }
+    Ok(())
+}
+
+fn check_git() -> Check {
+    match git::version() {
+        Ok(version) => match parse_git_version(&version) {
+            Some(found) if found < MIN_GIT_VERSION => Check::new(
+                "git",
+                Status::Error,
+                format!(
+                    "{}; gcz needs {}.{} or later",
+                    version, MIN_GIT_VERSION.0, MIN_GIT_VERSION.1
+                ),
+            ),
+            _ => Check::new("git", Status::Ok, version),
+        },
+        Err(err) => Check::new("git", Status::Error, format!("can't run git: {}", err)),
+    }
+}
+
+/// `(major, minor)` from `git version 2.39.3 (Apple Git-146)` or `git version 2.45.1.windows.1`.
+fn parse_git_version(version: &str) -> Option<(u32, u32)> {
+    let number = version
+        .strip_prefix("git version ")?
+        .split_whitespace()
+        .next()?;
+    let mut parts = number.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+fn check_config(diagnostics: &[config::Diagnostic]) -> Check {
+    let paths = config::config_paths().unwrap_or_default();
+    let sources = if paths.is_empty() {
+        "no config files, using the defaults".to_string()
+    } else {
+        paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let status = if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        Status::Error
+    } else if diagnostics.is_empty() {
+        Status::Ok
+    } else {
+        Status::Warning
+    };
+    let detail = diagnostics
+        .iter()
+        .fold(sources, |detail, d| format!("{}\n    {}", detail, d));
+    Check::new("config", status, detail)
+}
+
+fn check_editor(config: &Config) -> Check {
+    // without the inline editor every message goes through it
+    let missing = if config.inline {
+        Status::Warning
+    } else {
+        Status::Error
+    };
+    match editor::resolve(config) {
+        Ok(editor) => match editor.split_whitespace().next() {
+            Some(program) if on_path(program) => Check::new("editor", Status::Ok, editor),
+            _ => Check::new(
+                "editor",
+                missing,
+                format!(
+                    "`{}` isn't on PATH; set `editor`, core.editor or $EDITOR",
+                    editor
+                ),
+            ),
+        },
+        Err(err) => Check::new("editor", missing, err.to_string()),
+    }
+}
+
+fn check_terminal() -> Check {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Check::new(
+            "terminal",
+            Status::Warning,
+            "not a terminal; the prompts need one, `--message` works without",
+        );
+    }
+    let size = match terminal::size() {
+        Ok((columns, rows)) => format!("{}x{}", columns, rows),
+        Err(_) => "unknown size".to_string(),
+    };
+    let term = env::var("TERM").unwrap_or_else(|_| "no TERM".to_string());
+    let symbols = if glyph::detect() {
+        "ASCII only (the locale isn't UTF-8 or the console can't show symbols)"
+    } else {
+        "Unicode"
+    };
+    Check::new(
+        "terminal",
+        Status::Ok,
+        format!("{}, {}, {}", term, size, symbols),
+    )
+}
+
+fn check_signing() -> Result<Check, GczError> {
+    let enabled = git::config_value("commit.gpgsign")?
+        .is_some_and(|value| ["true", "yes", "on", "1"].contains(&value.to_lowercase().as_str()));
+    if !enabled {
+        return Ok(Check::new("signing", Status::Ok, "commits aren't signed"));
+    }
+
+    let format = git::config_value("gpg.format")?.unwrap_or_else(|| "openpgp".to_string());
+    let mut program = git::config_value(&format!("gpg.{}.program", format))?;
+    if program.is_none() && format == "openpgp" {
+        program = git::config_value("gpg.program")?;
+    }
+    let program = program.unwrap_or_else(|| {
+        match format.as_str() {
+            "ssh" => "ssh-keygen",
+            "x509" => "gpgsm",
+            _ => "gpg",
+        }
+        .to_string()
+    });
+    let key = git::config_value("user.signingkey")?;
+
+    Ok(if !on_path(&program) {
+        Check::new(
+            "signing",
+            Status::Error,
+            format!("commit.gpgsign is on, but `{}` isn't on PATH", program),
+        )
+    } else if format == "ssh" && key.is_none() {
+        Check::new(
+            "signing",
+            Status::Error,
+            "ssh signing needs user.signingkey",
+        )
+    } else {
+        Check::new(
+            "signing",
+            Status::Ok,
+            format!(
+                "{} with {}",
+                format,
+                key.as_deref().unwrap_or("the default key")
+            ),
+        )
+    })
+}
+
+fn check_hooks() -> Result<Check, GczError> {
+    let mut found = Vec::new();
+    let mut ignored = Vec::new();
+    for name in COMMIT_HOOKS {
+        match hook::state(name)? {
+            hook::State::Runs => found.push(name),
+            hook::State::NotExecutable => ignored.push(name),
+            hook::State::Missing => {}
+        }
+    }
+
+    let mut detail = if found.is_empty() {
+        "no commit hooks".to_string()
+    } else {
+        found.join(", ")
+    };
+    detail.push_str(&hook::location()?);
+    detail.push_str(if hook::is_installed()? {
+        "; commit-msg runs `gcz lint-msg`"
+    } else {
+        "; `gcz hook install` checks messages made without gcz"
+    });
+    if ignored.is_empty() {
+        return Ok(Check::new("hooks", Status::Ok, detail));
+    }
+    Ok(Check::new(
+        "hooks",
+        Status::Warning,
+        format!(
+            "{}\n    git ignores {} because it isn't executable",
+            detail,
+            ignored.join(", ")
+        ),
+    ))
+}
+
+/// Whether `program` is a path to a file, or found in one of the `PATH` directories.
+fn on_path(program: &str) -> bool {
+    find_on_path(program).is_some()
+}
+
+/// The file `program` names: itself when it is a path, otherwise the first match in `PATH`.
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    if program.contains(['/', '\\']) {
+        return Some(PathBuf::from(program)).filter(|path| path.is_file());
+    }
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths).find_map(|dir| {
+        [
+            dir.join(program),
+            dir.join(format!("{}{}", program, env::consts::EXE_SUFFIX)),
+        ]
+        .into_iter()
+        .find(|path| path.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_git_versions() {
+        assert_eq!(parse_git_version("git version 2.43.0"), Some((2, 43)));
+        assert_eq!(
+            parse_git_version("git version 2.39.3 (Apple Git-146)"),
+            Some((2, 39))
+        );
+        assert_eq!(
+            parse_git_version("git version 2.45.1.windows.1"),
+            Some((2, 45))
+        );
+        assert_eq!(parse_git_version("not git"), None);
+    }
+}
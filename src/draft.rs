@@ -0,0 +1,92 @@
+use crate::git;
+use crate::GczError;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Messages kept in the history, newest first.
+const HISTORY_SIZE: usize = 10;
+
+/// The message being composed, saved under `.git` so it survives a crash or power loss.
+/// Messages that were aborted or failed to commit are also kept in a short history, for
+/// `gcz --resume`.
+#[derive(Debug)]
+pub struct Draft {
+    path: PathBuf,
+    history: PathBuf,
+}
+
+impl Draft {
+    pub fn open() -> Result<Draft, GczError> {
+        Ok(Draft {
+            path: git::git_path("GCZ_DRAFT")?,
+            history: git::git_path("GCZ_DRAFTS")?,
+        })
+    }
+
+    /// The message left behind by a run that didn't get to commit, if any.
+    pub fn load(&self) -> Option<String> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .filter(|text| !text.trim().is_empty())
+    }
+
+    pub fn save(&self, text: &str) -> io::Result<()> {
+        write_atomically(&self.path, text)
+    }
+
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds the draft to the history, where it stays after the draft is cleared.
+    pub fn remember(&self) -> io::Result<()> {
+        let Some(text) = self.load() else {
+            return Ok(());
+        };
+        let mut history = self.history();
+        history.retain(|message| *message != text);
+        history.insert(0, text);
+        history.truncate(HISTORY_SIZE);
+        self.write_history(&history)
+    }
+
+    /// Clears the draft, keeping it in the history.
+    pub fn discard(&self) -> io::Result<()> {
+        self.remember()?;
+        self.clear()
+    }
+
+    /// The aborted and failed messages, newest first.
+    pub fn history(&self) -> Vec<String> {
+        fs::read_to_string(&self.history)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Takes the message at `index` out of the history and makes it the draft.
+    pub fn resume(&self, index: usize) -> io::Result<String> {
+        let mut history = self.history();
+        let text = history.remove(index);
+        self.write_history(&history)?;
+        self.save(&text)?;
+        Ok(text)
+    }
+
+    fn write_history(&self, history: &[String]) -> io::Result<()> {
+        write_atomically(&self.history, &serde_json::to_string(history)?)
+    }
+}
+
+/// Writes to a temporary file first so a crash mid-write can't leave a truncated file.
+pub fn write_atomically(path: &Path, text: &str) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    let mut file = File::create(&temporary)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    fs::rename(&temporary, path)
+}
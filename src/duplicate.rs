@@ -0,0 +1,108 @@
+use crate::config::{Config, Severity};
+use crate::lint::Finding;
+use crate::{git, hook, protect, GczError};
+use std::io;
+use std::process::Command;
+
+/// Commits searched for the subject being committed.
+const HISTORY: usize = 50;
+
+/// A recent commit whose subject is the one about to be committed, which usually means the
+/// staged changes belong in it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Duplicate {
+    pub hash: String,
+    pub subject: String,
+    /// Commits since; 0 for `HEAD`.
+    pub age: usize,
+}
+
+impl Duplicate {
+    pub fn finding(&self) -> Finding {
+        let message = match self.age {
+            0 => format!("same subject as the last commit ({})", short(&self.hash)),
+            1 => format!("same subject as {}, 1 commit ago", short(&self.hash)),
+            age => format!("same subject as {}, {} commits ago", short(&self.hash), age),
+        };
+        Finding {
+            severity: Severity::Warning,
+            message,
+        }
+    }
+
+    /// What [`fold`] does, for the key on the confirmation screen.
+    pub fn action(&self) -> String {
+        match self.age {
+            0 => "amend the last commit instead".to_string(),
+            _ => format!("commit as a fixup of {}", short(&self.hash)),
+        }
+    }
+}
+
+/// The most recent of the last commits with `subject`, if any.
+pub fn find(subject: &str) -> Result<Option<Duplicate>, GczError> {
+    if git::resolve_commit("HEAD")?.is_none() {
+        return Ok(None);
+    }
+    Ok(find_in(subject, &git::recent_log(HISTORY)?))
+}
+
+fn find_in(subject: &str, log: &[git::LogEntry]) -> Option<Duplicate> {
+    let subject = subject.trim();
+    log.iter()
+        .enumerate()
+        .find(|(_, entry)| entry.message.lines().next().map(str::trim) == Some(subject))
+        .map(|(age, entry)| Duplicate {
+            hash: entry.hash.clone(),
+            subject: subject.to_string(),
+            age,
+        })
+}
+
+/// Puts the staged changes into `duplicate` rather than a commit of their own: `HEAD` is
+/// amended keeping its message when it isn't on the upstream yet, an older commit gets a
+/// `fixup!` commit for `git rebase --autosquash`.
+pub fn fold(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    duplicate: &Duplicate,
+) -> Result<(), GczError> {
+    if duplicate.age > 0 {
+        return hook::commit_with_triage(stdout, config, &format!("fixup! {}", duplicate.subject));
+    }
+    protect::check_rewrite(&duplicate.hash)?;
+    git::commit_with_spinner(
+        Command::new("git").args(["commit", "--amend", "--no-edit"]),
+        "Amending",
+    )
+}
+
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_the_latest_commit_with_the_subject() {
+        let entry = |hash: &str, message: &str| git::LogEntry {
+            hash: hash.to_string(),
+            author: "Jane".to_string(),
+            date: "2024-05-01".to_string(),
+            message: message.to_string(),
+        };
+        let log = [
+            entry("aaaaaaaaaa", "fix: typo"),
+            entry("bbbbbbbbbb", "feat: add endpoint\n\nWith tests."),
+            entry("cccccccccc", "feat: add endpoint"),
+        ];
+
+        let found = find_in("feat: add endpoint ", &log).unwrap();
+        assert_eq!((found.hash.as_str(), found.age), ("bbbbbbbbbb", 1));
+        assert_eq!(found.action(), "commit as a fixup of bbbbbbb");
+        assert_eq!(find_in("fix: typo", &log).unwrap().age, 0);
+        assert_eq!(find_in("fix: other typo", &log), None);
+    }
+}
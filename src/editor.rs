@@ -0,0 +1,230 @@
+use crate::config::Config;
+use crate::git;
+use crate::GczError;
+use std::fs;
+use std::process::Command;
+
+const INSTRUCTIONS: [&str; 2] = [
+    "Edit the commit message above. Lines starting with '{}' are ignored.",
+    "Save and close the editor to return to gcz; an empty message keeps the old one.",
+];
+
+/// The instructions when comment lines are kept, below a scissors line.
+const SCISSORS_INSTRUCTIONS: [&str; 2] = [
+    "Do not modify or remove the line above; everything below it is ignored.",
+    "Save and close the editor to return to gcz; an empty message keeps the old one.",
+];
+
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// What `core.commentChar = auto` picks from, in git's order.
+const AUTO_COMMENT_CHARS: &[char] = &['#', ';', '@', '!', '$', '%', '^', '&', '|', ':'];
+
+/// git's `commit.cleanup` modes, applied to the message read back from the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cleanup {
+    /// Whitespace, and comment lines are dropped; git's default when editing.
+    Strip,
+    /// Trailing whitespace and runs of blank lines go, comment lines stay.
+    Whitespace,
+    /// Nothing is changed.
+    Verbatim,
+    /// Like `Whitespace`, and everything from the scissors line on is cut.
+    Scissors,
+}
+
+impl Cleanup {
+    /// `commit.cleanup` from the git config; `default`, unset and unknown values are `Strip`
+    /// as for a message git opens in the editor.
+    pub fn configured() -> Result<Cleanup, GczError> {
+        Ok(match git::config_value("commit.cleanup")?.as_deref() {
+            Some("whitespace") => Cleanup::Whitespace,
+            Some("verbatim") => Cleanup::Verbatim,
+            Some("scissors") => Cleanup::Scissors,
+            _ => Cleanup::Strip,
+        })
+    }
+}
+
+/// The editor command: the `editor` option (or `GCZ_EDITOR`), then whatever git would use
+/// (`GIT_EDITOR`, `core.editor`, `VISUAL`, `EDITOR`).
+pub fn resolve(config: &Config) -> Result<String, GczError> {
+    if let Some(editor) = config.editor.as_deref().filter(|e| !e.trim().is_empty()) {
+        return Ok(editor.to_string());
+    }
+
+    let output = Command::new("git").args(["var", "GIT_EDITOR"]).output()?;
+    let editor = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if editor.is_empty() {
+        "vi".to_string()
+    } else {
+        editor
+    })
+}
+
+/// Opens `text` in the external editor and returns the result, or `text` unchanged when
+/// the message is left empty.
+pub fn edit(config: &Config, text: &str) -> Result<String, GczError> {
+    let path = git::git_path("GCZ_EDITMSG")?;
+    let comment = comment_for(text)?;
+    let mode = Cleanup::configured()?;
+    // comment lines that are kept can't hold the instructions, a scissors line can
+    let instructions = match mode {
+        Cleanup::Strip => INSTRUCTIONS
+            .iter()
+            .map(|line| format!("{} {}\n", comment, line.replace("{}", &comment)))
+            .collect::<String>(),
+        _ => std::iter::once(SCISSORS)
+            .chain(SCISSORS_INSTRUCTIONS)
+            .map(|line| format!("{} {}\n", comment, line))
+            .collect(),
+    };
+    fs::write(&path, format!("{}\n\n{}", text, instructions))?;
+
+    // run through the shell like git does, so editors with arguments ("code --wait") work
+    let editor = resolve(config)?;
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg("editor")
+        .arg(&path)
+        .status()?;
+
+    let edited = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+    if !status.success() {
+        return Err(GczError::EditorFailed {
+            editor,
+            code: status.code(),
+        });
+    }
+
+    let edited = match mode {
+        Cleanup::Strip => edited.as_str(),
+        _ => cut_at_scissors(&edited, &comment),
+    };
+    let message = cleanup(edited, mode, &comment);
+    Ok(if message.trim().is_empty() {
+        text.to_string()
+    } else {
+        // the blank line before the instructions, which `Verbatim` keeps
+        message.trim_end_matches('\n').to_string()
+    })
+}
+
+/// The comment prefix git would use for a buffer holding `text`: `core.commentString` or
+/// `core.commentChar`, `#` when neither is set, and with `auto` the first character of
+/// [`AUTO_COMMENT_CHARS`] that no line of `text` starts with.
+pub fn comment_for(text: &str) -> Result<String, GczError> {
+    Ok(match configured_comment()? {
+        Some(comment) if comment == "auto" => AUTO_COMMENT_CHARS
+            .iter()
+            .find(|c| !text.lines().any(|line| line.starts_with(**c)))
+            .unwrap_or(&'#')
+            .to_string(),
+        Some(comment) => comment,
+        None => "#".to_string(),
+    })
+}
+
+/// The comment prefix of a buffer git already wrote, such as the file a `commit-msg` hook
+/// gets. With `auto` it is guessed from the last line, where git puts its comments.
+pub fn comment_in(raw: &str) -> Result<String, GczError> {
+    Ok(match configured_comment()? {
+        Some(comment) if comment == "auto" => raw
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| line.chars().next())
+            .filter(|c| AUTO_COMMENT_CHARS.contains(c))
+            .unwrap_or('#')
+            .to_string(),
+        Some(comment) => comment,
+        None => "#".to_string(),
+    })
+}
+
+fn configured_comment() -> Result<Option<String>, GczError> {
+    let comment = match git::config_value("core.commentString")? {
+        Some(comment) => Some(comment),
+        None => git::config_value("core.commentChar")?,
+    };
+    Ok(comment.filter(|comment| !comment.is_empty()))
+}
+
+/// Cleans up `raw` the way `git commit --cleanup=<mode>` does: trailing whitespace is
+/// removed, runs of blank lines become one and blank lines at either end go, except with
+/// `Verbatim`. Paragraphs stay apart.
+pub fn cleanup(raw: &str, mode: Cleanup, comment: &str) -> String {
+    let raw = match mode {
+        Cleanup::Verbatim => return raw.to_string(),
+        Cleanup::Scissors => cut_at_scissors(raw, comment),
+        Cleanup::Strip | Cleanup::Whitespace => raw,
+    };
+    let mut lines: Vec<&str> = Vec::new();
+    for line in raw.lines() {
+        if mode == Cleanup::Strip && line.starts_with(comment) {
+            continue;
+        }
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// `raw` up to its scissors line, if it has one.
+fn cut_at_scissors<'a>(raw: &'a str, comment: &str) -> &'a str {
+    let scissors = format!("{} {}", comment, SCISSORS);
+    let mut start = 0;
+    for line in raw.split_inclusive('\n') {
+        if line.trim_end() == scissors {
+            return &raw[..start];
+        }
+        start += line.len();
+    }
+    raw
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_strip_comments() {
+        let raw = "feat: add login\n\nBody text\n\n# Edit the message";
+        assert_eq!(
+            cleanup(raw, Cleanup::Strip, "#"),
+            "feat: add login\n\nBody text"
+        );
+
+        let raw = "docs: add usage\n\n# Usage\n\n; Edit the message";
+        assert_eq!(
+            cleanup(raw, Cleanup::Strip, ";"),
+            "docs: add usage\n\n# Usage"
+        );
+    }
+
+    #[test]
+    fn should_clean_up_like_git() {
+        let raw = "\nfix: crash  \n\n\n# Cause\n\nA race.\t\n\n# see #12\n\
+                   # ------------------------ >8 ------------------------\n# hint\n";
+
+        assert_eq!(cleanup(raw, Cleanup::Strip, "#"), "fix: crash\n\nA race.");
+        assert_eq!(
+            cleanup(raw, Cleanup::Scissors, "#"),
+            "fix: crash\n\n# Cause\n\nA race.\n\n# see #12"
+        );
+        assert_eq!(
+            cleanup(raw, Cleanup::Whitespace, "#"),
+            "fix: crash\n\n# Cause\n\nA race.\n\n# see #12\n\
+             # ------------------------ >8 ------------------------\n# hint"
+        );
+        assert_eq!(cleanup(raw, Cleanup::Verbatim, "#"), raw);
+    }
+}
@@ -0,0 +1,336 @@
+use crate::config::{self, Config};
+use crate::keymap::{Action, Screen};
+use crate::term::{self, enable_raw_mode};
+use crate::{
+    draw_progress, draw_status_line, emoji_for, finalize, glyph, show_help, text, GczError,
+};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// At most this many matches are listed.
+const MAX_RESULTS: usize = 10;
+
+/// Emoji turned on or off on the type screen, which holds until gcz exits.
+static SESSION: Mutex<Option<bool>> = Mutex::new(None);
+
+/// Whether messages get an emoji: the config's setting unless it was toggled.
+pub fn enabled(config: &Config) -> bool {
+    let session = *SESSION.lock().unwrap_or_else(|e| e.into_inner());
+    session.unwrap_or(config.emoji == Some(true))
+}
+
+/// Turns the emoji on or off for the rest of the run, and in the config with
+/// `save_emoji_toggle`.
+pub fn toggle(config: &Config) -> Result<(), GczError> {
+    let on = !enabled(config);
+    *SESSION.lock().unwrap_or_else(|e| e.into_inner()) = Some(on);
+    if config.save_emoji_toggle {
+        config::remember("emoji", on.into())?;
+    }
+    Ok(())
+}
+
+/// `config` with the emoji as toggled.
+pub fn with_toggle(config: &Config) -> Config {
+    Config {
+        emoji: Some(enabled(config)),
+        ..config.clone()
+    }
+}
+
+/// An emoji with the words it can be found by, through `text::fold`.
+struct Candidate {
+    emoji: &'static emojis::Emoji,
+    keywords: Vec<String>,
+}
+
+impl Candidate {
+    /// What goes into the message: the emoji, or its shortcode in ASCII mode.
+    fn text(&self) -> String {
+        match self.emoji.shortcode() {
+            Some(shortcode) if glyph::is_ascii() => format!(":{}:", shortcode),
+            _ => self.emoji.as_str().to_string(),
+        }
+    }
+}
+
+/// The emoji step: searches every emoji by name, shortcode and the commit types it stands
+/// for. `mapped`, then the emoji of `commit_type` are listed first until something is typed.
+pub fn pick(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    commit_type: &str,
+    mapped: Option<&str>,
+) -> Result<Option<String>, GczError> {
+    let candidates = candidates(config);
+    let suggested: Vec<usize> = config
+        .types
+        .iter()
+        .filter_map(|t| emoji_for(config, t))
+        .filter_map(|text| position(&candidates, text))
+        .collect();
+    let first = [mapped, emoji_for(config, commit_type)]
+        .into_iter()
+        .flatten()
+        .filter_map(|text| position(&candidates, text));
+    let mut suggested: Vec<usize> = first.chain(suggested).collect();
+    dedup(&mut suggested);
+
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    let picked = input_emoji(stdout, config, &candidates, &suggested, commit_type)
+        .and_then(|picked| finalize(picked, stdout))?;
+    Ok(picked.map(|i| candidates[i].text()))
+}
+
+fn candidates(config: &Config) -> Vec<Candidate> {
+    emojis::iter()
+        .map(|emoji| {
+            let mut keywords = vec![text::fold(emoji.name())];
+            keywords.extend(emoji.shortcodes().map(text::fold));
+            keywords.extend(
+                config
+                    .types
+                    .iter()
+                    .filter(|t| emoji_for(config, t).is_some_and(|e| is_same(emoji, e)))
+                    .map(|t| text::fold(t)),
+            );
+            Candidate { emoji, keywords }
+        })
+        .collect()
+}
+
+/// The emoji `text` stands for, written as an emoji or a `:shortcode:`.
+fn lookup(text: &str) -> Option<&'static emojis::Emoji> {
+    match text.strip_prefix(':').and_then(|t| t.strip_suffix(':')) {
+        Some(shortcode) => emojis::get_by_shortcode(shortcode),
+        None => emojis::get(text),
+    }
+}
+
+fn is_same(emoji: &emojis::Emoji, text: &str) -> bool {
+    lookup(text) == Some(emoji)
+}
+
+/// Whether `a` and `b` are the same emoji, e.g. `✨` and `:sparkles:`.
+pub fn same(a: &str, b: &str) -> bool {
+    a == b || lookup(a).is_some_and(|emoji| is_same(emoji, b))
+}
+
+fn position(candidates: &[Candidate], text: &str) -> Option<usize> {
+    candidates.iter().position(|c| is_same(c.emoji, text))
+}
+
+fn dedup(indices: &mut Vec<usize>) {
+    let mut seen = Vec::new();
+    indices.retain(|i| {
+        let new = !seen.contains(i);
+        seen.push(*i);
+        new
+    });
+}
+
+/// The best matches for `query`, or the `suggested` ones while it is empty.
+fn search(query: &str, candidates: &[Candidate], suggested: &[usize]) -> Vec<usize> {
+    let query = text::fold(query);
+    if query.is_empty() {
+        return suggested.iter().copied().take(MAX_RESULTS).collect();
+    }
+    let mut scored: Vec<(u32, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(i, candidate)| {
+            let score = candidate
+                .keywords
+                .iter()
+                .filter_map(|keyword| fuzzy_score(&query, keyword))
+                .max()?;
+            Some((score, i))
+        })
+        .collect();
+    // stable, so equally good matches keep the Unicode order
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored
+        .into_iter()
+        .map(|(_, i)| i)
+        .take(MAX_RESULTS)
+        .collect()
+}
+
+/// How well `query` matches `keyword`, or `None` when its characters don't all appear in
+/// order. Whole keywords beat prefixes, which beat matches at a word start, which beat
+/// matches anywhere; scattered characters come last.
+fn fuzzy_score(query: &str, keyword: &str) -> Option<u32> {
+    if keyword == query {
+        return Some(400);
+    }
+    if keyword.starts_with(query) {
+        return Some(300);
+    }
+    if let Some(at) = keyword.find(query) {
+        let word_start = keyword[..at].ends_with([' ', '_', '-']);
+        return Some(if word_start { 250 } else { 200 });
+    }
+
+    let mut gaps = 0;
+    let mut rest = keyword.chars();
+    for wanted in query.chars() {
+        loop {
+            match rest.next() {
+                Some(c) if c == wanted => break,
+                Some(_) => gaps += 1,
+                None => return None,
+            }
+        }
+    }
+    Some(100u32.saturating_sub(gaps))
+}
+
+fn input_emoji(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    candidates: &[Candidate],
+    suggested: &[usize],
+    commit_type: &str,
+) -> Result<Option<usize>, GczError> {
+    let keymap = &config.keymap;
+    let mut query = String::new();
+    let mut selected = 0;
+
+    loop {
+        let results = search(&query, candidates, suggested);
+        if term::should_redraw()? {
+            render_results(
+                stdout,
+                config,
+                commit_type,
+                &query,
+                candidates,
+                &results,
+                selected,
+            )?;
+        }
+
+        let key_event = match event::read()? {
+            Event::Key(key_event) => key_event,
+            _ => continue,
+        };
+        match keymap.action(Screen::Emoji, &key_event) {
+            Some(Action::Abort) => return Err(GczError::UserInterrupt),
+            Some(Action::Help) => show_help(stdout, keymap, &[Screen::Emoji])?,
+            Some(Action::Up) => selected = selected.saturating_sub(1),
+            Some(Action::Down) if selected + 1 < results.len() => selected += 1,
+            // Enter without a match leaves the emoji out
+            Some(Action::Confirm) => return Ok(results.get(selected).copied()),
+            Some(Action::DeleteBack) => {
+                query.pop();
+                selected = 0;
+            }
+            Some(Action::Clear) => {
+                query.clear();
+                selected = 0;
+            }
+            _ => match key_event.code {
+                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    query.push(c);
+                    selected = 0;
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+fn render_results(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    commit_type: &str,
+    query: &str,
+    candidates: &[Candidate],
+    results: &[usize],
+    selected: usize,
+) -> Result<(), GczError> {
+    let keymap = &config.keymap;
+    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    draw_progress(stdout, Screen::Emoji)?;
+    execute!(
+        stdout,
+        Print(format!(
+            "Emoji for {} (type to search, {}: help): {}",
+            commit_type,
+            keymap.describe(Screen::Emoji, Action::Help),
+            query
+        )),
+        cursor::MoveToNextLine(1)
+    )?;
+    let name_width = results
+        .iter()
+        .map(|&i| text::width(candidates[i].emoji.name()))
+        .max()
+        .unwrap_or(0);
+    for (row, &i) in results.iter().enumerate() {
+        let candidate = &candidates[i];
+        if row == selected {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print(format!("{} ", glyph::get().pointer))
+            )?;
+        } else {
+            execute!(stdout, Print("  "))?;
+        }
+        if config.emoji_column {
+            // placed rather than padded, so an emoji drawn wider or narrower than measured
+            // can't push the others out of line
+            execute!(
+                stdout,
+                Print(candidate.emoji.name()),
+                cursor::MoveToColumn((name_width + 3) as u16),
+                Print(candidate.text())
+            )?;
+        } else {
+            execute!(
+                stdout,
+                Print(format!("{}  {}", candidate.text(), candidate.emoji.name()))
+            )?;
+        }
+        execute!(
+            stdout,
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+    }
+    draw_status_line(stdout, keymap, Screen::Emoji)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_rank_emojis_by_how_well_they_match() {
+        let config = Config::default();
+        let candidates = candidates(&config);
+        let names = |query: &str| -> Vec<&str> {
+            search(query, &candidates, &[])
+                .into_iter()
+                .map(|i| candidates[i].emoji.as_str())
+                .collect()
+        };
+
+        assert_eq!(names("sparkles")[0], "✨");
+        // the commit types are keywords too
+        assert_eq!(names("fix")[0], "🐛");
+        assert!(names("rckt").contains(&"🚀"));
+        assert!(names("zzzzqx").is_empty());
+    }
+}
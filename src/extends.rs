@@ -0,0 +1,224 @@
+use crate::config::{self, Diagnostic, Location, Severity};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use std::{env, fs};
+use toml_edit::Document;
+
+/// How long a fetched config is used before it is fetched again. One pinned with
+/// `extends_sha256` is kept for as long as it matches.
+const MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The shared config that `extends` in `doc`, the config file at `path`, points to: an
+/// `https://` URL, `git+<repository>#<file>`, or a file relative to `path`. Problems are
+/// added to `diagnostics` and leave the shared config out. Its own `extends` is ignored.
+pub fn load(
+    doc: &Document<String>,
+    path: &Path,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<Document<String>> {
+    let item = doc.get("extends")?;
+    let source = item.as_str()?;
+    let pinned = doc.get("extends_sha256").and_then(|item| item.as_str());
+    let offset = item.span().map_or(0, |span| span.start);
+    let (line, column) = config::line_column(doc.raw(), offset);
+    let mut report = |severity: Severity, message: String| {
+        diagnostics.push(Diagnostic {
+            location: Location::File {
+                path: path.to_path_buf(),
+                line,
+                column,
+            },
+            severity,
+            message,
+        })
+    };
+
+    let raw = if is_remote(source) {
+        let cache = cache_path(source);
+        let cached = cache
+            .as_ref()
+            .and_then(|cache| fs::read_to_string(cache).ok());
+        let fresh = match (&cached, pinned) {
+            (Some(cached), Some(pinned)) => verify(cached, pinned).is_ok(),
+            (Some(_), None) => cache
+                .as_ref()
+                .and_then(|cache| fs::metadata(cache).and_then(|m| m.modified()).ok())
+                .is_some_and(|modified| modified.elapsed().is_ok_and(|age| age < MAX_AGE)),
+            (None, _) => false,
+        };
+        match cached {
+            Some(cached) if fresh => cached,
+            cached => match (fetch(source), cached) {
+                (Ok(raw), _) => {
+                    if let Some(cache) = &cache {
+                        // a cache that can't be written only means fetching again next time
+                        let _ = cache
+                            .parent()
+                            .map_or(Ok(()), fs::create_dir_all)
+                            .and_then(|_| fs::write(cache, &raw));
+                    }
+                    raw
+                }
+                (Err(reason), Some(cached)) => {
+                    report(
+                        Severity::Warning,
+                        format!(
+                            "can't fetch `{}` ({}); using the cached copy",
+                            source, reason
+                        ),
+                    );
+                    cached
+                }
+                (Err(reason), None) => {
+                    report(
+                        Severity::Error,
+                        format!("can't fetch `{}`: {}", source, reason),
+                    );
+                    return None;
+                }
+            },
+        }
+    } else {
+        let file = path.parent().unwrap_or(Path::new("")).join(source);
+        match fs::read_to_string(&file) {
+            Ok(raw) => raw,
+            Err(err) => {
+                report(
+                    Severity::Error,
+                    format!("can't read `{}`: {}", file.display(), err),
+                );
+                return None;
+            }
+        }
+    };
+
+    if let Some(pinned) = pinned {
+        if let Err(actual) = verify(&raw, pinned) {
+            report(
+                Severity::Error,
+                format!(
+                    "`{}` has sha256 {}, but `extends_sha256` expects {}",
+                    source, actual, pinned
+                ),
+            );
+            return None;
+        }
+    }
+    let problems = config::check_source(Path::new(source), &raw);
+    let has_errors = problems.iter().any(|d| d.severity == Severity::Error);
+    diagnostics.extend(problems);
+    if has_errors {
+        return None;
+    }
+    Document::parse(raw)
+        .ok()
+        .map(|shared| config::drop_commands(shared, Path::new(source), diagnostics))
+}
+
+fn is_remote(source: &str) -> bool {
+    ["https://", "http://", "git+"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+}
+
+/// Downloads `source` with curl, or clones the repository of `git+<url>#<file>` to read
+/// the file.
+fn fetch(source: &str) -> Result<String, String> {
+    let Some(repository) = source.strip_prefix("git+") else {
+        let output = Command::new("curl")
+            .args(["--fail", "--silent", "--show-error", "--location"])
+            .args(["--max-time", "10", source])
+            .output()
+            .map_err(|err| format!("can't run curl: {}", err))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    };
+
+    let (url, file) = repository
+        .split_once('#')
+        .ok_or("expected `git+<repository>#<file>`")?;
+    if !is_inside(file) {
+        return Err(format!(
+            "`{}` must be a relative path inside the repository",
+            file
+        ));
+    }
+    let checkout = env::temp_dir().join(format!("gcz-extends-{}", std::process::id()));
+    let output = Command::new("git")
+        // `--` so a URL like `--upload-pack=...` can't pass for an option
+        .args(["clone", "--quiet", "--depth", "1", "--", url])
+        .arg(&checkout)
+        .output()
+        .map_err(|err| format!("can't run git: {}", err))?;
+    let raw = if output.status.success() {
+        fs::read_to_string(checkout.join(file)).map_err(|err| format!("{}: {}", file, err))
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    };
+    let _ = fs::remove_dir_all(&checkout);
+    raw
+}
+
+/// Whether `file` stays inside the directory it is relative to.
+fn is_inside(file: &str) -> bool {
+    Path::new(file)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// Checks `raw` against the hex SHA-256 `pinned`, returning the actual hash on a mismatch.
+fn verify(raw: &str, pinned: &str) -> Result<(), String> {
+    let actual = sha256(raw);
+    if actual.eq_ignore_ascii_case(pinned.trim()) {
+        Ok(())
+    } else {
+        Err(actual)
+    }
+}
+
+fn sha256(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// `$XDG_CACHE_HOME/gcz/extends/<hash of source>.toml`, falling back to `~/.cache`.
+fn cache_path(source: &str) -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(
+        dir.join("gcz")
+            .join("extends")
+            .join(format!("{}.toml", &sha256(source)[..16])),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_verify_the_pinned_hash() {
+        let hash = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(verify("abc", hash), Ok(()));
+        assert_eq!(verify("abc", &hash.to_uppercase()), Ok(()));
+        assert_eq!(
+            verify("abd", hash).map_err(|actual| actual == hash),
+            Err(false)
+        );
+        assert!(is_remote(
+            "git+https://github.com/acme/conventions#gcz.toml"
+        ));
+        assert!(!is_remote("../shared/gcz.toml"));
+        assert!(is_inside("config/gcz.toml"));
+        assert!(!is_inside("../gcz.toml"));
+        assert!(!is_inside("/etc/passwd"));
+    }
+}
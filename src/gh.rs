@@ -0,0 +1,25 @@
+use crate::conventional;
+use crate::GczError;
+use std::io;
+use std::process::Command;
+
+/// Opens a pull request for the pushed branch with the GitHub CLI, titled with the header
+/// of `message` and described by its body. gh prints the link itself.
+pub fn create_pull_request(message: &str) -> Result<(), GczError> {
+    let (title, body) = conventional::split_message(message);
+    let status = Command::new("gh")
+        .args(["pr", "create", "--title", title, "--body", body.trim()])
+        .status();
+    let reason = match status {
+        Ok(status) if status.success() => return Ok(()),
+        Ok(status) => match status.code() {
+            Some(code) => format!("`gh pr create` exited with code {}", code),
+            None => "`gh pr create` was killed by a signal".to_string(),
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            "gh, the GitHub CLI, isn't installed".to_string()
+        }
+        Err(err) => return Err(err.into()),
+    };
+    Err(GczError::PullRequestFailed(reason))
+}
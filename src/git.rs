@@ -0,0 +1,783 @@
+use crate::{glyph, GczError};
+use std::env;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Seconds before a git command is killed; 0 waits forever. Set once from the config.
+static TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+
+/// Quick commands finish before the spinner shows up, so it doesn't flicker.
+const SPINNER_DELAY: Duration = Duration::from_millis(300);
+
+pub fn set_timeout(seconds: u64) {
+    TIMEOUT_SECS.store(seconds, Ordering::Relaxed);
+}
+
+/// Runs `command` with its output captured, killing it after the configured timeout.
+/// With a `label`, a spinner is drawn on stderr while it runs.
+pub fn run(command: &mut Command, label: Option<&str>) -> Result<Output, GczError> {
+    let child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    wait(child, &describe(command), label)
+}
+
+/// Runs a command that may trigger hooks behind a spinner, then prints what it wrote.
+pub fn run_with_spinner(command: &mut Command, label: &str) -> Result<ExitStatus, GczError> {
+    let output = run(command, Some(label))?;
+    io::stdout().write_all(&output.stdout)?;
+    io::stderr().write_all(&output.stderr)?;
+    Ok(output.status)
+}
+
+/// Runs a `git commit` behind a spinner and prints what it wrote; it fails like
+/// [`try_commit`].
+pub fn commit_with_spinner(command: &mut Command, label: &str) -> Result<(), GczError> {
+    let output = try_commit(command, Some(label))?;
+    io::stdout().write_all(&output.stdout)?;
+    io::stderr().write_all(&output.stderr)?;
+    Ok(())
+}
+
+/// Runs a `git commit` and returns what it wrote. When it fails and git itself didn't
+/// complain, the commit hooks are the ones that refused.
+pub fn try_commit(command: &mut Command, label: Option<&str>) -> Result<Output, GczError> {
+    let output = run(command, label)?;
+    if output.status.success() {
+        return Ok(output);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let from_git = stderr
+        .lines()
+        .any(|line| line.starts_with("fatal: ") || line.starts_with("error: "));
+    if !from_git && has_commit_hooks()? {
+        let hook_output = String::from_utf8_lossy(&output.stdout) + stderr;
+        return Err(GczError::HookRejected {
+            output: hook_output.trim_end().to_string(),
+        });
+    }
+    Err(failure(&describe(command), &output))
+}
+
+fn has_commit_hooks() -> Result<bool, GczError> {
+    for hook in ["pre-commit", "prepare-commit-msg", "commit-msg"] {
+        if git_path(&format!("hooks/{}", hook))?.is_file() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn failure(description: &str, output: &Output) -> GczError {
+    GczError::GitCommandFailed {
+        command: description.to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr)
+            .trim_end()
+            .to_string(),
+        code: output.status.code(),
+    }
+}
+
+/// Like [`run`] with a spinner, feeding `input` to the command's stdin.
+pub fn run_with_input(
+    command: &mut Command,
+    input: Vec<u8>,
+    label: &str,
+) -> Result<Output, GczError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // written from a thread so a command that answers before reading everything can't deadlock
+    let stdin = child.stdin.take();
+    thread::spawn(move || stdin.map(|mut stdin| stdin.write_all(&input)));
+    wait(child, &describe(command), Some(label))
+}
+
+fn wait(mut child: Child, description: &str, label: Option<&str>) -> Result<Output, GczError> {
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+    let timeout = match TIMEOUT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        seconds => Some(Duration::from_secs(seconds)),
+    };
+    let label = label.filter(|_| io::stderr().is_terminal());
+
+    let start = Instant::now();
+    let mut spinning = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        let elapsed = start.elapsed();
+        if timeout.is_some_and(|timeout| elapsed >= timeout) {
+            // a hung hook may keep the pipes open, so the readers are left behind
+            let _ = child.kill();
+            child.wait()?;
+            clear_spinner(spinning);
+            return Err(GczError::Timeout {
+                command: description.to_string(),
+                seconds: elapsed.as_secs(),
+            });
+        }
+
+        if let Some(label) = label.filter(|_| elapsed >= SPINNER_DELAY) {
+            let frames = glyph::get().spinner;
+            let frame = frames[(elapsed.as_millis() / 80) as usize % frames.len()];
+            eprint!(
+                "\r\x1b[2K{} {} {}s (Ctrl+C to abort)",
+                frame,
+                label,
+                elapsed.as_secs()
+            );
+            io::stderr().flush()?;
+            spinning = true;
+        }
+        thread::sleep(if elapsed < SPINNER_DELAY {
+            Duration::from_millis(5)
+        } else {
+            Duration::from_millis(80)
+        });
+    };
+    clear_spinner(spinning);
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+fn clear_spinner(spinning: bool) {
+    if spinning {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+/// `git <subcommand>`, for error messages.
+fn describe(command: &Command) -> String {
+    let program = command.get_program().to_string_lossy();
+    match command.get_args().next() {
+        Some(subcommand) => format!("{} {}", program, subcommand.to_string_lossy()),
+        None => program.into_owned(),
+    }
+}
+
+fn git(args: &[&str]) -> Result<Output, GczError> {
+    run(Command::new("git").args(args), None)
+}
+
+/// Like [`git`] for commands that aren't expected to fail, returning git's stderr when they do.
+fn git_checked(args: &[&str]) -> Result<Output, GczError> {
+    let mut command = Command::new("git");
+    command.args(args);
+    let output = run(&mut command, None)?;
+    if !output.status.success() {
+        return Err(failure(&describe(&command), &output));
+    }
+    Ok(output)
+}
+
+pub fn is_inside_git_dir() -> Result<Output, GczError> {
+    git(&["rev-parse", "--is-inside-work-tree"])
+}
+
+pub fn exist_stages_changes() -> Result<ExitStatus, GczError> {
+    // just check the status
+    Ok(git(&["diff", "--cached", "--exit-code", "--quiet"])?.status)
+}
+
+pub fn staged_files() -> Result<Vec<String>, GczError> {
+    list_files(&["diff", "--cached", "--name-only", "-z"])
+}
+
+pub fn unstaged_files() -> Result<Vec<String>, GczError> {
+    list_files(&["diff", "--name-only", "-z"])
+}
+
+fn list_files(args: &[&str]) -> Result<Vec<String>, GczError> {
+    let output = git_checked(args)?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Paths added with `git add --intent-to-add`: in the index without content, so a commit
+/// leaves them out until they are staged for real.
+pub fn intent_to_add_files() -> Result<Vec<String>, GczError> {
+    list_files(&["diff", "--name-only", "--diff-filter=A", "-z"])
+}
+
+pub fn untracked_files() -> Result<Vec<String>, GczError> {
+    list_files(&["ls-files", "--others", "--exclude-standard", "-z"])
+}
+
+/// The submodules, recursively, as paths relative to the current directory.
+pub fn submodules() -> Result<Vec<String>, GczError> {
+    let output = git(&[
+        "submodule",
+        "--quiet",
+        "foreach",
+        "--recursive",
+        "echo $displaypath",
+    ])?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// The working trees of the repository, the main one first.
+pub fn worktrees() -> Result<Vec<PathBuf>, GczError> {
+    let output = git(&["worktree", "list", "--porcelain"])?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("worktree "))
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Staged and changed (staged, unstaged or untracked) files of the repository at `dir`.
+pub fn change_counts(dir: &Path) -> Result<(usize, usize), GczError> {
+    let output = run(
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["status", "--porcelain"]),
+        None,
+    )?;
+    let status = String::from_utf8_lossy(&output.stdout);
+    let staged = status
+        .lines()
+        .filter(|line| !matches!(line.as_bytes().first(), Some(b' ' | b'?')))
+        .count();
+    Ok((staged, status.lines().count()))
+}
+
+/// Pushes the current branch, setting its upstream on the first push: to
+/// `remote.pushDefault`, `origin` or the only remote, in that order.
+pub fn push() -> Result<(), GczError> {
+    let mut command = Command::new("git");
+    command.arg("push");
+    let upstream = git(&["rev-parse", "--abbrev-ref", "@{upstream}"])?;
+    if !upstream.status.success() {
+        command.args(["--set-upstream", &push_remote()?, "HEAD"]);
+    }
+
+    let output = run(&mut command, Some("Pushing"))?;
+    if !output.status.success() {
+        return Err(failure(&describe(&command), &output));
+    }
+    // git reports what it pushed, and hosts their links, on stderr
+    io::stderr().write_all(&output.stderr)?;
+    Ok(())
+}
+
+/// The upstream of the current branch, e.g. `origin/main`, if it has one.
+pub fn upstream() -> Result<Option<String>, GczError> {
+    let output = git(&["rev-parse", "--abbrev-ref", "@{upstream}"])?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Whether `commit` is `descendant` or one of its ancestors.
+pub fn is_ancestor(commit: &str, descendant: &str) -> Result<bool, GczError> {
+    Ok(git(&["merge-base", "--is-ancestor", commit, descendant])?
+        .status
+        .success())
+}
+
+/// The name of the checked-out branch, or `None` on a detached `HEAD`.
+pub fn current_branch() -> Result<Option<String>, GczError> {
+    let output = git(&["symbolic-ref", "--quiet", "--short", "HEAD"])?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// The branch work is merged into: what the remote's `HEAD` points to, e.g.
+/// `origin/main`, or else a local `main` or `master`.
+pub fn default_branch() -> Result<Option<String>, GczError> {
+    if let Ok(remote) = push_remote() {
+        let output = git(&[
+            "symbolic-ref",
+            "--quiet",
+            "--short",
+            &format!("refs/remotes/{}/HEAD", remote),
+        ])?;
+        if output.status.success() {
+            return Ok(Some(
+                String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            ));
+        }
+    }
+    for name in ["main", "master"] {
+        if resolve_commit(&format!("refs/heads/{}", name))?.is_some() {
+            return Ok(Some(name.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Commits `HEAD` has that `base` doesn't, and the other way round.
+pub fn ahead_behind(base: &str) -> Result<(usize, usize), GczError> {
+    let output = git_checked(&[
+        "rev-list",
+        "--left-right",
+        "--count",
+        &format!("HEAD...{}", base),
+    ])?;
+    let counts = String::from_utf8_lossy(&output.stdout);
+    let mut counts = counts.split_whitespace().map(|n| n.parse().unwrap_or(0));
+    Ok((counts.next().unwrap_or(0), counts.next().unwrap_or(0)))
+}
+
+/// The local dates (`YYYY-MM-DD`) of the commits by `email` on any branch, newest first.
+pub fn commit_dates(email: &str) -> Result<Vec<String>, GczError> {
+    let output = git_checked(&[
+        "log",
+        "--all",
+        "--date-order",
+        "--format=%cd",
+        "--date=format-local:%Y-%m-%d",
+        "--fixed-strings",
+        &format!("--author=<{}>", email),
+    ])?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+/// The URL of the remote [`push`] would use, if there is one.
+pub fn remote_url() -> Result<Option<String>, GczError> {
+    match push_remote() {
+        Ok(remote) => config_value(&format!("remote.{}.url", remote)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn push_remote() -> Result<String, GczError> {
+    if let Some(remote) = config_value("remote.pushDefault")? {
+        return Ok(remote);
+    }
+    let output = git_checked(&["remote"])?;
+    let remotes = String::from_utf8_lossy(&output.stdout);
+    let mut names = remotes.lines();
+    match (names.clone().find(|name| *name == "origin"), names.next()) {
+        (Some(origin), _) => Ok(origin.to_string()),
+        (None, Some(first)) if names.next().is_none() => Ok(first.to_string()),
+        _ => Err(GczError::NoRemote),
+    }
+}
+
+/// The value of a git config key, or `None` when it isn't set.
+pub fn config_value(key: &str) -> Result<Option<String>, GczError> {
+    let output = git(&["config", key])?;
+    Ok(output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+}
+
+/// Sets a git config key in the repository, or for all repositories with `global`.
+pub fn set_config(key: &str, value: &str, global: bool) -> Result<(), GczError> {
+    let mut args = vec!["config"];
+    if global {
+        args.push("--global");
+    }
+    git_checked(&[args.as_slice(), &[key, value]].concat())?;
+    Ok(())
+}
+
+/// `git --version`, e.g. `git version 2.43.0`.
+pub fn version() -> Result<String, GczError> {
+    let output = git_checked(&["--version"])?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether git accepts `name` as a branch name.
+pub fn is_valid_branch_name(name: &str) -> Result<bool, GczError> {
+    Ok(git(&["check-ref-format", "--branch", name])?
+        .status
+        .success())
+}
+
+/// Attaches `text` to HEAD under git's default notes ref, `refs/notes/commits`.
+pub fn add_note(text: &str) -> Result<(), GczError> {
+    git_checked(&["notes", "add", "--message", text, "HEAD"])?;
+    Ok(())
+}
+
+/// Creates the branch `name` at `HEAD`, switching to it when `switch` is set.
+pub fn create_branch(name: &str, switch: bool) -> Result<(), GczError> {
+    if switch {
+        git_checked(&["switch", "--create", name])?;
+    } else {
+        git_checked(&["branch", name])?;
+    }
+    Ok(())
+}
+
+/// Fills the `index` file with `HEAD` plus the work tree's `paths`, so committing it leaves
+/// whatever else is staged out.
+pub fn stage_only(index: &Path, paths: &[String]) -> Result<(), GczError> {
+    let mut read_tree = Command::new("git");
+    if resolve_commit("HEAD")?.is_some() {
+        read_tree.args(["read-tree", "HEAD"]);
+    } else {
+        read_tree.args(["read-tree", "--empty"]);
+    }
+    let mut add = Command::new("git");
+    add.args(["add", "--"]).args(paths);
+    for command in [&mut read_tree, &mut add] {
+        let output = run(command.env("GIT_INDEX_FILE", index), None)?;
+        if !output.status.success() {
+            return Err(failure(&describe(command), &output));
+        }
+    }
+    Ok(())
+}
+
+/// Creates the annotated tag `name` at `HEAD`, with the name as its message.
+pub fn create_tag(name: &str) -> Result<(), GczError> {
+    git_checked(&["tag", "-a", name, "-m", name])?;
+    Ok(())
+}
+
+pub fn stage_files(paths: &[String]) -> Result<ExitStatus, GczError> {
+    let output = run(Command::new("git").args(["add", "--"]).args(paths), None)?;
+    io::stderr().write_all(&output.stderr)?;
+    Ok(output.status)
+}
+
+pub fn partially_staged_files(staged: &[String], unstaged: &[String]) -> Vec<String> {
+    staged
+        .iter()
+        .filter(|path| unstaged.contains(path))
+        .cloned()
+        .collect()
+}
+
+/// Stashes the unstaged changes and untracked files, leaving the work tree as the index has
+/// it. Returns false when there was nothing to stash.
+pub fn stash_unstaged() -> Result<bool, GczError> {
+    if git(&["diff", "--quiet"])?.status.success() && untracked_files()?.is_empty() {
+        return Ok(false);
+    }
+    git_checked(&[
+        "stash",
+        "push",
+        "--keep-index",
+        "--include-untracked",
+        "--quiet",
+        "--message",
+        "gcz: unstaged changes",
+    ])?;
+    Ok(true)
+}
+
+/// Puts the work tree back the way [`stash_unstaged`] found it and drops the stash. The
+/// files are restored from the stash rather than popped, which could conflict with the
+/// staged changes now in `HEAD`.
+pub fn restore_stash() -> Result<(), GczError> {
+    git_checked(&["restore", "--source=stash@{0}", "--worktree", "--", ":/"])?;
+    if resolve_commit("stash@{0}^3")?.is_some() {
+        git_checked(&[
+            "restore",
+            "--overlay",
+            "--source=stash@{0}^3",
+            "--worktree",
+            "--",
+            ":/",
+        ])?;
+    }
+    git_checked(&["stash", "drop", "--quiet"])?;
+    Ok(())
+}
+
+/// Sizes of the staged blobs of added, copied, modified and renamed files.
+pub fn staged_blob_sizes() -> Result<Vec<(String, u64)>, GczError> {
+    let paths = list_files(&[
+        "diff",
+        "--cached",
+        "--name-only",
+        "-z",
+        "--diff-filter=ACMR",
+    ])?;
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut command = Command::new("git");
+    command.args(["cat-file", "--batch-check=%(objectsize)"]);
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // feed stdin from another thread so a large output can't fill the pipe and deadlock
+    let mut stdin = child.stdin.take().expect("stdin should be piped");
+    let input: String = paths.iter().map(|path| format!(":{}\n", path)).collect();
+    let writer = thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = wait(child, &describe(&command), None)?;
+    writer.join().expect("stdin writer panicked")?;
+
+    Ok(paths
+        .into_iter()
+        .zip(String::from_utf8_lossy(&output.stdout).lines())
+        .filter_map(|(path, size)| size.parse().ok().map(|size| (path, size)))
+        .collect())
+}
+
+/// The subset of `paths` whose `filter` attribute is `lfs`.
+pub fn lfs_tracked(paths: &[String]) -> Result<Vec<String>, GczError> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let output = run(
+        Command::new("git")
+            .args(["check-attr", "-z", "filter", "--"])
+            .args(paths),
+        None,
+    )?;
+
+    // -z output is a flat sequence of <path> NUL <attribute> NUL <value> NUL
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let fields: Vec<&str> = stdout.split('\0').collect();
+    Ok(fields
+        .chunks(3)
+        .filter(|chunk| chunk.len() == 3 && chunk[2] == "lfs")
+        .map(|chunk| chunk[0].to_string())
+        .collect())
+}
+
+/// The staged changes without context lines, for scanning what is about to be committed.
+pub fn staged_diff() -> Result<String, GczError> {
+    let output = git_checked(&["diff", "--cached", "-U0", "--no-color", "--no-ext-diff"])?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Each staged path with its section of the staged diff. Renames are listed as a deletion
+/// and an addition so every section belongs to exactly one path.
+pub fn staged_file_diffs() -> Result<Vec<(String, String)>, GczError> {
+    let paths = list_files(&["diff", "--cached", "--no-renames", "--name-only", "-z"])?;
+    let output = git_checked(&[
+        "diff",
+        "--cached",
+        "--no-renames",
+        "--no-color",
+        "--no-ext-diff",
+        // the sections are applied with `-p1`, whatever `diff.noprefix` says
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ])?;
+
+    let diff = String::from_utf8_lossy(&output.stdout);
+    let mut sections: Vec<String> = Vec::new();
+    for line in diff.split_inclusive('\n') {
+        match sections.last_mut() {
+            Some(section) if !line.starts_with("diff --git ") => section.push_str(line),
+            _ => sections.push(line.to_string()),
+        }
+    }
+    Ok(paths.into_iter().zip(sections).collect())
+}
+
+/// Puts `paths` back to their `HEAD` state in the `index` file, leaving the work tree alone.
+pub fn reset_in_index(index: &Path, paths: &[String]) -> Result<ExitStatus, GczError> {
+    let mut command = Command::new("git");
+    if resolve_commit("HEAD")?.is_some() {
+        command.args(["reset", "-q", "HEAD", "--"]);
+    } else {
+        command.args(["rm", "--cached", "-q", "--ignore-unmatch", "--"]);
+    }
+    command.args(paths).env("GIT_INDEX_FILE", index);
+    Ok(run(&mut command, None)?.status)
+}
+
+/// Applies `patch` to the `index` file only.
+pub fn apply_to_index(index: &Path, patch: &str) -> Result<Output, GczError> {
+    run_with_input(
+        Command::new("git")
+            .args(["apply", "--cached", "-"])
+            .env("GIT_INDEX_FILE", index),
+        patch.as_bytes().to_vec(),
+        "Applying",
+    )
+}
+
+/// `message` with `trailers` added by `git interpret-trailers`, which follows the `trailer.*`
+/// settings; `None` when git refuses.
+pub fn interpret_trailers(message: &str, trailers: &[String]) -> Result<Option<String>, GczError> {
+    let mut command = Command::new("git");
+    command.arg("interpret-trailers");
+    for trailer in trailers {
+        command.args(["--trailer", trailer]);
+    }
+    // without the final newline, a lone header would be read as a trailer block
+    let input = format!("{}\n", message).into_bytes();
+    let output = run_with_input(&mut command, input, "Adding trailers")?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let with_trailers = String::from_utf8_lossy(&output.stdout);
+    Ok(Some(with_trailers.trim_end_matches('\n').to_string()))
+}
+
+/// Subjects of the last `count` commits; empty outside a repository or before the first commit.
+pub fn recent_subjects(count: usize) -> Result<Vec<String>, GczError> {
+    let output = git(&["log", &format!("-{}", count), "--format=%s"])?;
+
+    if !output.status.success() {
+        return Ok(Vec::new());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect())
+}
+
+/// Root of the current work tree, or `None` outside of one.
+pub fn toplevel() -> Result<Option<PathBuf>, GczError> {
+    let output = git(&["rev-parse", "--show-toplevel"])?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(PathBuf::from(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+    )))
+}
+
+/// Resolves a revision to a full commit id, or `None` when it doesn't name a commit.
+pub fn resolve_commit(reference: &str) -> Result<Option<String>, GczError> {
+    let output = git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", reference),
+    ])?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+pub fn has_parent(commit: &str) -> Result<bool, GczError> {
+    Ok(resolve_commit(&format!("{}^", commit))?.is_some())
+}
+
+pub fn commit_message(commit: &str) -> Result<String, GczError> {
+    let output = git_checked(&["log", "-1", "--format=%B", commit])?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string())
+}
+
+/// The most recent tag reachable from `reference`, if any.
+pub fn latest_tag(reference: &str) -> Result<Option<String>, GczError> {
+    let output = git(&["describe", "--tags", "--abbrev=0", reference])?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// A commit as listed by [`log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub hash: String,
+    pub author: String,
+    /// Committer date as `YYYY-MM-DD`.
+    pub date: String,
+    pub message: String,
+}
+
+/// Commits in `range` (anything `git log` accepts), newest first, merges excluded.
+pub fn log(range: &str) -> Result<Vec<LogEntry>, GczError> {
+    log_entries(&[range])
+}
+
+/// The last `count` commits of `HEAD`, newest first, merges excluded.
+pub fn recent_log(count: usize) -> Result<Vec<LogEntry>, GczError> {
+    log_entries(&[&format!("--max-count={}", count), "HEAD"])
+}
+
+fn log_entries(args: &[&str]) -> Result<Vec<LogEntry>, GczError> {
+    let mut log_args = vec!["log", "--no-merges", "--format=%H%x00%an%x00%cs%x00%B%x1e"];
+    log_args.extend(args);
+    let output = git_checked(&log_args)?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(4, '\0');
+            Some(LogEntry {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                message: fields.next()?.trim_end().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Resolves a path inside the `.git` directory, e.g. `git_path("gcz.lock")`.
+pub fn git_path(name: &str) -> Result<PathBuf, GczError> {
+    let output = git_checked(&["rev-parse", "--git-path", name])?;
+
+    // git answers relative to the current directory
+    Ok(env::current_dir()?.join(String::from_utf8_lossy(&output.stdout).trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_partially_staged_files() {
+        let staged = vec!["src/main.rs".to_string(), "README.md".to_string()];
+        let unstaged = vec!["README.md".to_string(), "Cargo.toml".to_string()];
+
+        assert_eq!(
+            partially_staged_files(&staged, &unstaged),
+            vec!["README.md".to_string()]
+        );
+    }
+}
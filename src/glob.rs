@@ -0,0 +1,53 @@
+/// Matches a path against a gitignore-style glob.
+///
+/// `*` and `?` don't cross `/`, `**` matches any number of directories, and a
+/// pattern without `/` is matched against the file name only.
+pub fn matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim().trim_start_matches('/');
+    if !pattern.contains('/') {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        return match_segment(pattern.as_bytes(), name.as_bytes());
+    }
+
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                match_segment(first.as_bytes(), name.as_bytes()) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_globs() {
+        assert!(matches("*.psd", "design/logo.psd"));
+        assert!(matches("docs/**", "docs/guide/intro.md"));
+        assert!(matches("**/*_test.rs", "src/parser_test.rs"));
+        assert!(matches("**/*_test.rs", "parser_test.rs"));
+        assert!(matches(".github/**", ".github/workflows/test.yml"));
+        assert!(!matches("docs/*", "docs/guide/intro.md"));
+        assert!(!matches("src/*.rs", "tests/main.rs"));
+    }
+}
@@ -0,0 +1,112 @@
+use std::env;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Symbols the screens draw, in a Unicode and a plain ASCII flavor.
+pub struct Glyphs {
+    pub pointer: &'static str,
+    pub ok: &'static str,
+    pub error: &'static str,
+    pub warning: &'static str,
+    pub scroll_left: &'static str,
+    pub scroll_right: &'static str,
+    pub arrow: &'static str,
+    pub dash: &'static str,
+    pub up: &'static str,
+    pub down: &'static str,
+    pub left: &'static str,
+    pub right: &'static str,
+    pub separator: &'static str,
+    pub spinner: &'static [&'static str],
+}
+
+const UNICODE: Glyphs = Glyphs {
+    pointer: "❯",
+    ok: "✔",
+    error: "✖",
+    warning: "⚠",
+    scroll_left: "…",
+    scroll_right: "…",
+    arrow: "→",
+    dash: "—",
+    up: "↑",
+    down: "↓",
+    left: "←",
+    right: "→",
+    separator: " · ",
+    spinner: &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"],
+};
+
+const ASCII: Glyphs = Glyphs {
+    pointer: ">",
+    ok: "+",
+    error: "x",
+    warning: "!",
+    scroll_left: "<",
+    scroll_right: ">",
+    arrow: "->",
+    dash: "-",
+    up: "Up",
+    down: "Down",
+    left: "Left",
+    right: "Right",
+    separator: " | ",
+    spinner: &["|", "/", "-", "\\"],
+};
+
+/// Set once at startup from `--ascii`, the config or `detect`.
+static USE_ASCII: AtomicBool = AtomicBool::new(false);
+
+pub fn set_ascii(ascii: bool) {
+    USE_ASCII.store(ascii, Ordering::Relaxed);
+}
+
+pub fn is_ascii() -> bool {
+    USE_ASCII.load(Ordering::Relaxed)
+}
+
+pub fn get() -> &'static Glyphs {
+    if is_ascii() {
+        &ASCII
+    } else {
+        &UNICODE
+    }
+}
+
+/// Guesses whether the terminal can't show anything beyond ASCII: a locale that isn't
+/// UTF-8, the Linux console, or the classic Windows console.
+pub fn detect() -> bool {
+    if cfg!(windows) {
+        return env::var_os("WT_SESSION").is_none() && env::var_os("TERM_PROGRAM").is_none();
+    }
+    if env::var("TERM").is_ok_and(|term| term == "linux" || term == "dumb") {
+        return true;
+    }
+    // the first one set wins, as in setlocale(3)
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    !locale.is_some_and(|locale| is_utf8_locale(&locale))
+}
+
+/// `en_US.UTF-8`, or just `UTF-8` as macOS sets `LC_CTYPE`.
+fn is_utf8_locale(locale: &str) -> bool {
+    let name = locale.split('@').next().unwrap_or("");
+    let codeset = name.rsplit('.').next().unwrap_or("").to_lowercase();
+    codeset == "utf-8" || codeset == "utf8"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_recognize_utf8_locales() {
+        assert!(is_utf8_locale("en_US.UTF-8"));
+        assert!(is_utf8_locale("ja_JP.utf8"));
+        assert!(is_utf8_locale("de_DE.UTF-8@euro"));
+        assert!(is_utf8_locale("UTF-8"));
+        assert!(!is_utf8_locale("C"));
+        assert!(!is_utf8_locale("POSIX"));
+        assert!(!is_utf8_locale("en_US.ISO-8859-1"));
+    }
+}
@@ -0,0 +1,383 @@
+use crate::config::{Config, Severity};
+use crate::{commit_staged, git, lint, select_option, GczError};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{env, fmt, fs};
+
+/// Marks hooks gcz wrote, so it never overwrites someone else's.
+const MARKER: &str = "# installed by gcz";
+
+/// The line a `commit-msg` hook script runs, with git's message file as `$1`.
+const LINT_COMMAND: &str = "gcz lint-msg \"$1\"";
+
+/// Runs [`LINT_COMMAND`] from lefthook's config; `{1}` is the message file.
+const LEFTHOOK_SNIPPET: &str = "\
+commit-msg:
+  commands:
+    gcz:
+      run: gcz lint-msg {1}
+";
+
+/// A local hook for the pre-commit framework, which passes it the message file.
+const PRE_COMMIT_SNIPPET: &str = "\
+# install with `pre-commit install --hook-type commit-msg`
+repos:
+  - repo: local
+    hooks:
+      - id: gcz-lint-msg
+        name: gcz lint-msg
+        entry: gcz lint-msg
+        language: system
+        stages: [commit-msg]
+";
+
+/// What `gcz hook print` can print a snippet for.
+pub const TARGETS: [&str; 4] = ["lefthook", "pre-commit", "husky", "git"];
+
+/// The config or script that runs `gcz lint-msg` on every commit under `target`, one of
+/// [`TARGETS`].
+pub fn snippet(target: &str) -> String {
+    match target {
+        "lefthook" => LEFTHOOK_SNIPPET.to_string(),
+        "pre-commit" => PRE_COMMIT_SNIPPET.to_string(),
+        // husky 9 runs `.husky/commit-msg` with sh
+        "husky" => format!("{}\n", LINT_COMMAND),
+        _ => format!("#!/bin/sh\nexec {}\n", LINT_COMMAND),
+    }
+}
+
+/// The hooks `git commit --no-verify` doesn't run.
+const VERIFY_HOOKS: [&str; 2] = ["pre-commit", "commit-msg"];
+
+/// Whether `--no-verify` was given. Set once from the command line.
+static NO_VERIFY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_no_verify(no_verify: bool) {
+    NO_VERIFY.store(no_verify, Ordering::Relaxed);
+}
+
+/// Whether commits skip the hooks in [`VERIFY_HOOKS`].
+pub fn no_verify() -> bool {
+    NO_VERIFY.load(Ordering::Relaxed)
+}
+
+/// A tool that generates the repository's git hooks from its own scripts or config, so
+/// a hook gcz wrote next to them would be replaced.
+pub enum Manager {
+    /// husky, running the scripts in this directory, usually `.husky`.
+    Husky(PathBuf),
+    /// lefthook, running the commands in this config file.
+    Lefthook(PathBuf),
+}
+
+impl fmt::Display for Manager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Manager::Husky(_) => write!(f, "husky"),
+            Manager::Lefthook(_) => write!(f, "lefthook"),
+        }
+    }
+}
+
+/// The hook manager the repository uses: husky when `core.hooksPath` points into a
+/// `.husky` directory, lefthook when its config file is at the top of the work tree.
+pub fn manager() -> Result<Option<Manager>, GczError> {
+    if git::config_value("core.hooksPath")?.is_some() {
+        let hooks = git::git_path("hooks")?;
+        // husky 9 points core.hooksPath to `.husky/_`, older versions to `.husky`
+        let dir = if hooks.ends_with("_") {
+            hooks.parent().map(Path::to_path_buf).unwrap_or(hooks)
+        } else {
+            hooks
+        };
+        if dir.ends_with(".husky") {
+            return Ok(Some(Manager::Husky(dir)));
+        }
+    }
+    let Some(root) = git::toplevel()? else {
+        return Ok(None);
+    };
+    Ok(LEFTHOOK_CONFIGS
+        .iter()
+        .map(|name| root.join(name))
+        .find(|path| path.is_file())
+        .map(Manager::Lefthook))
+}
+
+const LEFTHOOK_CONFIGS: [&str; 4] = [
+    "lefthook.yml",
+    ".lefthook.yml",
+    "lefthook.yaml",
+    ".lefthook.yaml",
+];
+
+#[derive(PartialEq, Eq)]
+pub enum State {
+    Missing,
+    Runs,
+    /// git skips it because it isn't executable.
+    NotExecutable,
+}
+
+/// Whether git runs the `name` hook, looking in `core.hooksPath` or `.git/hooks`, and in
+/// husky's directory for the scripts its generated hooks run.
+pub fn state(name: &str) -> Result<State, GczError> {
+    if let Some(Manager::Husky(dir)) = manager()? {
+        // husky generates every hook and runs the script of the same name, if there is one
+        return Ok(if dir.join(name).is_file() {
+            State::Runs
+        } else {
+            State::Missing
+        });
+    }
+    let path = git::git_path(&format!("hooks/{}", name))?;
+    Ok(if !path.is_file() {
+        State::Missing
+    } else if is_executable(&path) {
+        State::Runs
+    } else {
+        State::NotExecutable
+    })
+}
+
+/// Where the hooks are when it isn't `.git/hooks`, and who manages them, e.g.
+/// ` in .husky/_ (husky)`; empty for plain `.git/hooks`.
+pub fn location() -> Result<String, GczError> {
+    let mut location = String::new();
+    if let Some(hooks_path) = git::config_value("core.hooksPath")? {
+        location.push_str(&format!(" in {}", hooks_path));
+    }
+    if let Some(manager) = manager()? {
+        location.push_str(&format!(" ({})", manager));
+    }
+    Ok(location)
+}
+
+/// A warning naming the hooks that would have run, when `--no-verify` skips any.
+pub fn skipped_finding() -> Result<Option<lint::Finding>, GczError> {
+    if !no_verify() {
+        return Ok(None);
+    }
+    let mut skipped = Vec::new();
+    for name in VERIFY_HOOKS {
+        if state(name)? == State::Runs {
+            skipped.push(name);
+        }
+    }
+    if skipped.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(lint::Finding {
+        severity: Severity::Warning,
+        message: format!(
+            "--no-verify skips the {} hook{}{}",
+            skipped.join(" and "),
+            if skipped.len() == 1 { "" } else { "s" },
+            location()?
+        ),
+    }))
+}
+
+pub enum Install {
+    Installed(PathBuf),
+    /// Added to a script or config of the hook manager.
+    Appended(PathBuf, Manager),
+    AlreadyInstalled(PathBuf),
+    /// A hook gcz didn't write is in the way.
+    Conflict(PathBuf),
+    /// lefthook's config already has a `commit-msg` section to add the command to.
+    LefthookSection(PathBuf),
+}
+
+impl fmt::Display for Install {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Install::Installed(path) => write!(f, "Installed {}", path.display()),
+            Install::Appended(path, Manager::Husky(_)) => {
+                write!(f, "Added `gcz lint-msg` to {}", path.display())
+            }
+            Install::Appended(path, Manager::Lefthook(_)) => write!(
+                f,
+                "Added a commit-msg command running `gcz lint-msg` to {}; run `lefthook install` to update the hooks",
+                path.display()
+            ),
+            Install::AlreadyInstalled(path) => write!(f, "{} is already installed", path.display()),
+            Install::Conflict(path) => write!(
+                f,
+                "{} already exists; add `{}` to it to check messages",
+                path.display(),
+                LINT_COMMAND
+            ),
+            Install::LefthookSection(path) => write!(
+                f,
+                "{} already configures commit-msg; add the command `gcz hook print lefthook` shows to it",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Where [`install`] puts the `commit-msg` hook: husky's script or lefthook's config when
+/// one of them manages the hooks, the hook itself otherwise.
+fn target() -> Result<(PathBuf, Option<Manager>), GczError> {
+    Ok(match manager()? {
+        Some(Manager::Husky(dir)) => (dir.join("commit-msg"), Some(Manager::Husky(dir))),
+        Some(Manager::Lefthook(config)) => (config.clone(), Some(Manager::Lefthook(config))),
+        None => (git::git_path("hooks/commit-msg")?, None),
+    })
+}
+
+/// Whether the `commit-msg` hook runs what [`install`] adds.
+pub fn is_installed() -> Result<bool, GczError> {
+    let (path, _) = target()?;
+    Ok(fs::read_to_string(path).is_ok_and(|hook| hook.contains(MARKER)))
+}
+
+/// Installs a `commit-msg` hook that runs `gcz lint-msg` on every commit, including ones
+/// made without gcz. Under husky or lefthook, the command is appended to their
+/// `commit-msg` script or config instead, which they would otherwise regenerate over it.
+pub fn install() -> Result<Install, GczError> {
+    let (path, manager) = target()?;
+    let existing = fs::read_to_string(&path).ok();
+    if existing
+        .as_deref()
+        .is_some_and(|text| text.contains(MARKER))
+    {
+        return Ok(Install::AlreadyInstalled(path));
+    }
+
+    let addition = match (&manager, &existing) {
+        (None, Some(_)) => return Ok(Install::Conflict(path)),
+        (None, None) => format!("#!/bin/sh\n{}\nexec {}\n", MARKER, LINT_COMMAND),
+        (Some(Manager::Husky(_)), None) => format!("#!/bin/sh\n{}\n{}\n", MARKER, LINT_COMMAND),
+        (Some(Manager::Husky(_)), Some(_)) => format!("\n{}\n{}\n", MARKER, LINT_COMMAND),
+        (Some(Manager::Lefthook(_)), text) => {
+            // adding to an existing `commit-msg:` section would need a YAML editor
+            if text
+                .as_deref()
+                .is_some_and(|text| text.lines().any(|line| line.starts_with("commit-msg:")))
+            {
+                return Ok(Install::LefthookSection(path));
+            }
+            format!("\n{}\n{}", MARKER, LEFTHOOK_SNIPPET)
+        }
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(addition.as_bytes())?;
+    match manager {
+        Some(Manager::Lefthook(_)) => {}
+        _ => make_executable(&path)?,
+    }
+    Ok(match (manager, existing) {
+        (Some(manager), Some(_)) => Install::Appended(path, manager),
+        _ => Install::Installed(path),
+    })
+}
+
+/// Commits the index; when a hook rejects the commit, asks whether to read its output,
+/// fix the files in a shell and retry, retry with `--no-verify`, or give up. Giving up
+/// returns the hook's error, so the caller keeps the message.
+pub fn commit_with_triage(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    message: &str,
+) -> Result<(), GczError> {
+    let mut no_verify = false;
+    loop {
+        let output = match commit_staged(config, message, no_verify) {
+            Err(GczError::HookRejected { output }) => output,
+            result => return result,
+        };
+        let title = match output.lines().find(|line| !line.trim().is_empty()) {
+            Some(line) => format!("A git hook rejected the commit: {}", line.trim()),
+            None => "A git hook rejected the commit".to_string(),
+        };
+        let options = [
+            "View the full output".to_string(),
+            "Fix the files in a shell, then retry".to_string(),
+            "Retry with --no-verify".to_string(),
+            "Abort and keep the message".to_string(),
+        ];
+        loop {
+            match select_option(stdout, &title, &options, &config.keymap)? {
+                0 => page(&output)?,
+                1 => {
+                    open_shell()?;
+                    break;
+                }
+                2 => {
+                    no_verify = true;
+                    break;
+                }
+                _ => return Err(GczError::HookRejected { output }),
+            }
+        }
+    }
+}
+
+/// Shows `text` in git's pager.
+fn page(text: &str) -> Result<(), GczError> {
+    let pager = Command::new("git").args(["var", "GIT_PAGER"]).output()?;
+    let pager = String::from_utf8_lossy(&pager.stdout).trim().to_string();
+    if pager.is_empty() || pager == "cat" {
+        // the menu would clear it right away
+        println!("{}\n\nPress Enter to go back", text);
+        io::stdin().read_line(&mut String::new())?;
+        return Ok(());
+    }
+    // through the shell like git does, so pagers with arguments ("less -R") work
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(&pager)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the pager may quit before reading everything
+        let _ = writeln!(stdin, "{}", text);
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Runs the user's shell until they exit it.
+fn open_shell() -> Result<(), GczError> {
+    let shell = env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    println!("Fix the files and stage them with `git add`, then `exit` to retry the commit");
+    Command::new(shell).status()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn make_executable(path: &PathBuf) -> Result<(), GczError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &PathBuf) -> Result<(), GczError> {
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use crate::config::Config;
+use crate::{ask_line, git, select_option, GczError};
+use crossterm::{
+    cursor, execute,
+    terminal::{Clear, ClearType},
+};
+use std::{env, fmt, io};
+
+/// Why git would refuse the commit's author, or `email_domains` does.
+pub enum Problem {
+    /// This git config key isn't set.
+    Missing(&'static str),
+    /// The email isn't at any of `email_domains`.
+    Domain { email: String, domains: Vec<String> },
+}
+
+impl Problem {
+    fn key(&self) -> &'static str {
+        match self {
+            Problem::Missing(key) => key,
+            Problem::Domain { .. } => "user.email",
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Missing(key) => write!(f, "`{}` isn't set, so git can't make the commit", key),
+            Problem::Domain { email, domains } => write!(
+                f,
+                "`{}` isn't at {} (`email_domains`)",
+                email,
+                domains.join(" or ")
+            ),
+        }
+    }
+}
+
+/// The first problem with the identity commits are made with, which git takes from
+/// `GIT_AUTHOR_NAME` and `GIT_AUTHOR_EMAIL` or from `user.name` and `user.email`.
+pub fn problem(config: &Config) -> Result<Option<Problem>, GczError> {
+    if value("GIT_AUTHOR_NAME", "user.name")?.is_none() {
+        return Ok(Some(Problem::Missing("user.name")));
+    }
+    let Some(email) = value("GIT_AUTHOR_EMAIL", "user.email")? else {
+        return Ok(Some(Problem::Missing("user.email")));
+    };
+    if config.email_domains.is_empty() || at_domain(&email, &config.email_domains) {
+        return Ok(None);
+    }
+    Ok(Some(Problem::Domain {
+        email,
+        domains: config.email_domains.clone(),
+    }))
+}
+
+fn value(var: &str, key: &str) -> Result<Option<String>, GczError> {
+    let set = |value: &String| !value.trim().is_empty();
+    match env::var(var).ok().filter(set) {
+        Some(value) => Ok(Some(value)),
+        None => Ok(git::config_value(key)?.filter(set)),
+    }
+}
+
+/// Whether `email` is at one of `domains` or a subdomain of one.
+fn at_domain(email: &str, domains: &[String]) -> bool {
+    let Some((_, host)) = email.rsplit_once('@') else {
+        return false;
+    };
+    let host = host.trim_end_matches('>').to_lowercase();
+    domains.iter().any(|domain| {
+        let domain = domain.trim_start_matches('@').to_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    })
+}
+
+/// Fails with the first identity problem, for commits made without prompts.
+pub fn check(config: &Config) -> Result<(), GczError> {
+    match problem(config)? {
+        Some(problem) => Err(GczError::Identity {
+            key: problem.key(),
+            reason: problem.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Offers to set the name or email while one is missing or at the wrong domain, before
+/// the message is written rather than when git refuses the commit. Without confirmations
+/// the problem is an error, as with [`check`].
+pub fn confirm(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    while let Some(problem) = problem(config)? {
+        if !config.confirm {
+            return check(config);
+        }
+        let key = problem.key();
+        let mut options = vec!["Set it for this repository".to_string()];
+        // a work email usually shouldn't go to every repository
+        if let Problem::Missing(_) = problem {
+            options.push("Set it for all repositories".to_string());
+        }
+        options.push("Abort".to_string());
+        let title = format!("{}. Set `{}` now?", problem, key);
+        let global = match select_option(stdout, &title, &options, &config.keymap)? {
+            0 => false,
+            1 if options.len() == 3 => true,
+            _ => return Err(GczError::UserInterrupt),
+        };
+
+        execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        let question = match key {
+            "user.name" => "Your name for commits (leave empty to go back):",
+            _ => "Your email for commits (leave empty to go back):",
+        };
+        let value = ask_line(stdout, question, &config.keymap)?;
+        if !value.trim().is_empty() {
+            git::set_config(key, value.trim(), global)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_email_domains() {
+        let domains = ["acme.com".to_string()];
+        assert!(at_domain("jane@acme.com", &domains));
+        assert!(at_domain("jane@EU.Acme.com", &domains));
+        assert!(!at_domain("jane@notacme.com", &domains));
+        assert!(!at_domain("jane@gmail.com", &domains));
+        assert!(!at_domain("acme.com", &domains));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::git;
+use crate::GczError;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+
+/// Root of the jj workspace around the current directory. Colocated repositories, with
+/// `.jj` next to `.git`, are treated as jj too.
+pub fn workspace_root() -> Option<PathBuf> {
+    env::current_dir()
+        .ok()?
+        .ancestors()
+        .find(|dir| dir.join(".jj").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// `jj` run from the workspace root, so the paths it prints match the ones git would.
+fn jj_command(args: &[&str]) -> Command {
+    let mut command = Command::new("jj");
+    command.args(args).args(["--no-pager", "--color=never"]);
+    if let Some(root) = workspace_root() {
+        command.current_dir(root);
+    }
+    command
+}
+
+fn jj(args: &[&str]) -> Result<Output, GczError> {
+    git::run(&mut jj_command(args), None)
+}
+
+/// Files changed in the working-copy change, which jj commits as a whole.
+pub fn changed_files() -> Result<Vec<String>, GczError> {
+    let output = jj(&["diff", "--name-only"])?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|path| !path.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// The working-copy diff in git's format without context, like `git::staged_diff`.
+pub fn diff() -> Result<String, GczError> {
+    let output = jj(&["diff", "--git", "--context=0"])?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The description of `revision`, or `None` when it doesn't name a single change.
+pub fn description(revision: &str) -> Result<Option<String>, GczError> {
+    let output = jj(&["log", "--no-graph", "-r", revision, "-T", "description"])?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(
+        String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string(),
+    ))
+}
+
+/// `Name <email>` from jj's `user.name` and `user.email`, for sign-offs.
+pub fn user() -> Result<Option<String>, GczError> {
+    let get = |key: &str| -> Result<String, GczError> {
+        let output = jj(&["config", "get", key])?;
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let (name, email) = (get("user.name")?, get("user.email")?);
+
+    Ok((!name.is_empty() && !email.is_empty()).then(|| format!("{} <{}>", name, email)))
+}
+
+/// Describes the working-copy change with `message` and starts a new one on top.
+pub fn commit(message: &str) -> Result<ExitStatus, GczError> {
+    git::run_with_spinner(&mut jj_command(&["commit", "-m", message]), "Committing")
+}
+
+/// Replaces the description of `revision`; jj rebases its descendants on its own.
+pub fn describe(revision: &str, message: &str) -> Result<ExitStatus, GczError> {
+    git::run_with_spinner(
+        &mut jj_command(&["describe", "-r", revision, "-m", message]),
+        "Describing",
+    )
+}
@@ -0,0 +1,742 @@
+use crate::glyph;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+use toml_edit::Item;
+
+/// A step of the wizard with its own set of keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    TypeSelect,
+    Scope,
+    /// The types and scopes side by side, with `scope_pane`.
+    TypeAndScope,
+    Emoji,
+    Untracked,
+    Message,
+    /// The trailers picked from `trailer_presets`.
+    Trailers,
+    Confirm,
+    Menu,
+    PartialStaging,
+    Split,
+    Browse,
+}
+
+impl Screen {
+    pub fn title(self) -> &'static str {
+        match self {
+            Screen::TypeSelect => "Commit type",
+            Screen::Scope => "Scope",
+            Screen::TypeAndScope => "Type and scope",
+            Screen::Emoji => "Emoji",
+            Screen::Untracked => "Untracked files",
+            Screen::Message => "Message",
+            Screen::Trailers => "Trailers",
+            Screen::Confirm => "Confirmation",
+            Screen::Menu => "Menu",
+            Screen::PartialStaging => "Partially staged files",
+            Screen::Split => "Splitting changes",
+            Screen::Browse => "Commit history",
+        }
+    }
+
+    /// Plain characters are typed into the message rather than bound to actions.
+    fn takes_text(self) -> bool {
+        matches!(
+            self,
+            Screen::Message | Screen::Scope | Screen::TypeAndScope | Screen::Emoji | Screen::Browse
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    DeleteBack,
+    DeleteForward,
+    DeleteWord,
+    NewLine,
+    Confirm,
+    Clear,
+    Edit,
+    AddBody,
+    Amend,
+    OpenEditor,
+    Complete,
+    ToggleEmoji,
+    Pin,
+    SwitchPane,
+    ToggleUntracked,
+    ToggleFile,
+    AddFiles,
+    StageRest,
+    Help,
+    Abort,
+}
+
+/// Every action with its name in the `[keys]` config table and its default keys.
+const ACTIONS: &[(Action, &str, &[&str])] = &[
+    (Action::Up, "up", &["up"]),
+    (Action::Down, "down", &["down"]),
+    (Action::Left, "left", &["left"]),
+    (Action::Right, "right", &["right"]),
+    (Action::Home, "home", &["home"]),
+    (Action::End, "end", &["end"]),
+    (Action::DeleteBack, "delete_back", &["backspace"]),
+    (Action::DeleteForward, "delete_forward", &["delete"]),
+    (
+        Action::DeleteWord,
+        "delete_word",
+        &["ctrl+backspace", "alt+backspace", "ctrl+w"],
+    ),
+    (Action::NewLine, "new_line", &["alt+enter"]),
+    (Action::Confirm, "confirm", &["enter"]),
+    (Action::Clear, "clear", &["esc"]),
+    (Action::Edit, "edit", &["e"]),
+    (Action::AddBody, "add_body", &["b"]),
+    (Action::Amend, "amend", &["a"]),
+    (
+        Action::OpenEditor,
+        "open_editor",
+        &["ctrl+e", "shift+enter"],
+    ),
+    (Action::Complete, "complete", &["tab"]),
+    (Action::ToggleEmoji, "toggle_emoji", &["ctrl+g"]),
+    (Action::Pin, "pin", &["ctrl+f"]),
+    (Action::SwitchPane, "switch_pane", &["tab"]),
+    (Action::ToggleUntracked, "toggle_untracked", &["tab"]),
+    (Action::ToggleFile, "toggle_file", &["space"]),
+    (Action::AddFiles, "add_files", &["a"]),
+    (Action::StageRest, "stage_rest", &["s"]),
+    (Action::Help, "help", &["?", "f1"]),
+    (Action::Abort, "abort", &["ctrl+c", "ctrl+d"]),
+];
+
+/// What each action does on each screen, in the order the help overlay lists them.
+const SCREEN_ACTIONS: &[(Screen, Action, &str)] = &[
+    (Screen::TypeSelect, Action::Up, "previous type"),
+    (Screen::TypeSelect, Action::Down, "next type"),
+    (Screen::TypeSelect, Action::Left, "previous type"),
+    (Screen::TypeSelect, Action::Right, "next type"),
+    (Screen::TypeSelect, Action::Confirm, "use the selected type"),
+    (
+        Screen::TypeSelect,
+        Action::DeleteBack,
+        "delete a filter character",
+    ),
+    (Screen::TypeSelect, Action::Clear, "clear the filter"),
+    (
+        Screen::TypeSelect,
+        Action::Pin,
+        "pin the type to the favorites, or unpin it",
+    ),
+    (
+        Screen::TypeSelect,
+        Action::ToggleEmoji,
+        "turn the emoji on or off for this run",
+    ),
+    (
+        Screen::TypeSelect,
+        Action::ToggleUntracked,
+        "show or hide untracked files",
+    ),
+    (Screen::TypeSelect, Action::Help, "show this help"),
+    (Screen::TypeSelect, Action::Abort, "abort"),
+    (Screen::Scope, Action::Up, "previous suggestion"),
+    (Screen::Scope, Action::Down, "next suggestion"),
+    (
+        Screen::Scope,
+        Action::Confirm,
+        "use the scope, or none when empty",
+    ),
+    (Screen::Scope, Action::DeleteBack, "delete a character"),
+    (Screen::Scope, Action::Clear, "clear the scope"),
+    (Screen::Scope, Action::Help, "show this help"),
+    (Screen::Scope, Action::Abort, "abort"),
+    (Screen::TypeAndScope, Action::Up, "previous type or scope"),
+    (Screen::TypeAndScope, Action::Down, "next type or scope"),
+    (
+        Screen::TypeAndScope,
+        Action::SwitchPane,
+        "switch between the types and the scopes",
+    ),
+    (
+        Screen::TypeAndScope,
+        Action::Confirm,
+        "use the type and the scope, or none when empty",
+    ),
+    (
+        Screen::TypeAndScope,
+        Action::DeleteBack,
+        "delete a filter character",
+    ),
+    (Screen::TypeAndScope, Action::Clear, "clear the filter"),
+    (Screen::TypeAndScope, Action::Help, "show this help"),
+    (Screen::TypeAndScope, Action::Abort, "abort"),
+    (Screen::Emoji, Action::Up, "previous emoji"),
+    (Screen::Emoji, Action::Down, "next emoji"),
+    (
+        Screen::Emoji,
+        Action::Confirm,
+        "use the emoji, or none without a match",
+    ),
+    (
+        Screen::Emoji,
+        Action::DeleteBack,
+        "delete a search character",
+    ),
+    (Screen::Emoji, Action::Clear, "clear the search"),
+    (Screen::Emoji, Action::Help, "show this help"),
+    (Screen::Emoji, Action::Abort, "abort"),
+    (Screen::Untracked, Action::Up, "previous file"),
+    (Screen::Untracked, Action::Down, "next file"),
+    (
+        Screen::Untracked,
+        Action::ToggleFile,
+        "select or unselect the file",
+    ),
+    (
+        Screen::Untracked,
+        Action::AddFiles,
+        "add the selected files",
+    ),
+    (
+        Screen::Untracked,
+        Action::ToggleUntracked,
+        "hide untracked files",
+    ),
+    (Screen::Message, Action::Up, "move up a row"),
+    (Screen::Message, Action::Down, "move down a row"),
+    (Screen::Message, Action::Left, "move left"),
+    (Screen::Message, Action::Right, "move right"),
+    (Screen::Message, Action::Home, "move to the start"),
+    (Screen::Message, Action::End, "move to the end"),
+    (
+        Screen::Message,
+        Action::DeleteBack,
+        "delete before the cursor",
+    ),
+    (
+        Screen::Message,
+        Action::DeleteForward,
+        "delete under the cursor",
+    ),
+    (
+        Screen::Message,
+        Action::DeleteWord,
+        "delete the word before the cursor",
+    ),
+    (Screen::Message, Action::NewLine, "start a new line"),
+    (
+        Screen::Message,
+        Action::Complete,
+        "complete a name from the staged changes; again for the next one",
+    ),
+    (Screen::Message, Action::Confirm, "accept the message"),
+    (
+        Screen::Message,
+        Action::OpenEditor,
+        "continue in the external editor, e.g. for the body",
+    ),
+    (Screen::Message, Action::Help, "show this help"),
+    (Screen::Message, Action::Abort, "abort"),
+    (Screen::Trailers, Action::Up, "previous trailer"),
+    (Screen::Trailers, Action::Down, "next trailer"),
+    (
+        Screen::Trailers,
+        Action::ToggleFile,
+        "select or unselect the trailer",
+    ),
+    (
+        Screen::Trailers,
+        Action::Confirm,
+        "add the selected trailers",
+    ),
+    (Screen::Trailers, Action::Help, "show this help"),
+    (Screen::Trailers, Action::Abort, "abort"),
+    (Screen::Confirm, Action::Confirm, "commit"),
+    (Screen::Confirm, Action::Edit, "edit the message"),
+    (
+        Screen::Confirm,
+        Action::AddBody,
+        "start the body with a list of the changes",
+    ),
+    (
+        Screen::Confirm,
+        Action::Amend,
+        "amend or fix up the recent commit with the same subject",
+    ),
+    (Screen::Confirm, Action::Help, "show this help"),
+    (Screen::Confirm, Action::Abort, "abort"),
+    (Screen::Menu, Action::Up, "previous option"),
+    (Screen::Menu, Action::Down, "next option"),
+    (Screen::Menu, Action::Confirm, "choose the option"),
+    (Screen::Menu, Action::Help, "show this help"),
+    (Screen::Menu, Action::Abort, "abort"),
+    (
+        Screen::PartialStaging,
+        Action::StageRest,
+        "stage the remaining changes",
+    ),
+    (
+        Screen::PartialStaging,
+        Action::Confirm,
+        "continue without them",
+    ),
+    (Screen::PartialStaging, Action::Help, "show this help"),
+    (Screen::PartialStaging, Action::Abort, "abort"),
+    (Screen::Split, Action::Up, "previous file or hunk"),
+    (Screen::Split, Action::Down, "next file or hunk"),
+    (Screen::Split, Action::Right, "show the file's hunks"),
+    (Screen::Split, Action::Left, "hide the file's hunks"),
+    (
+        Screen::Split,
+        Action::ToggleFile,
+        "include or leave out the file or hunk",
+    ),
+    (
+        Screen::Split,
+        Action::Confirm,
+        "commit the included changes",
+    ),
+    (Screen::Split, Action::Help, "show this help"),
+    (Screen::Split, Action::Abort, "abort"),
+    (Screen::Browse, Action::Up, "previous commit"),
+    (Screen::Browse, Action::Down, "next commit"),
+    (Screen::Browse, Action::Home, "first commit"),
+    (Screen::Browse, Action::End, "last commit"),
+    (
+        Screen::Browse,
+        Action::Confirm,
+        "show or hide the author and body",
+    ),
+    (
+        Screen::Browse,
+        Action::DeleteBack,
+        "delete a filter character",
+    ),
+    (Screen::Browse, Action::Clear, "clear the filter"),
+    (Screen::Browse, Action::Help, "show this help"),
+    (Screen::Browse, Action::Abort, "quit"),
+];
+
+/// Actions sharing a key hint and its label.
+type Hint = (&'static [Action], &'static str);
+
+/// The keys worth knowing on each screen, shown in the status line at the bottom. Actions
+/// grouped together share a label, as `↑↓ select`.
+const STATUS_HINTS: &[(Screen, &[Hint])] = &[
+    (
+        Screen::TypeSelect,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::Confirm], "confirm"),
+            (&[Action::Clear], "clear"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Scope,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::Confirm], "confirm"),
+            (&[Action::Clear], "clear"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::TypeAndScope,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::SwitchPane], "switch"),
+            (&[Action::Confirm], "confirm"),
+            (&[Action::Clear], "clear"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Emoji,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::Confirm], "confirm"),
+            (&[Action::Clear], "clear"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Untracked,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::ToggleFile], "toggle"),
+            (&[Action::AddFiles], "add"),
+            (&[Action::ToggleUntracked], "hide"),
+        ],
+    ),
+    (
+        Screen::Message,
+        &[
+            (&[Action::Confirm], "confirm"),
+            (&[Action::NewLine], "new line"),
+            (&[Action::OpenEditor], "editor"),
+            (&[Action::Complete], "complete"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Trailers,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::ToggleFile], "toggle"),
+            (&[Action::Confirm], "confirm"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Menu,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::Confirm], "confirm"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Split,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::Left, Action::Right], "hunks"),
+            (&[Action::ToggleFile], "toggle"),
+            (&[Action::Confirm], "commit"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "abort"),
+        ],
+    ),
+    (
+        Screen::Browse,
+        &[
+            (&[Action::Up, Action::Down], "select"),
+            (&[Action::Confirm], "details"),
+            (&[Action::Clear], "clear"),
+            (&[Action::Help], "help"),
+            (&[Action::Abort], "quit"),
+        ],
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    /// Parses keys like `enter`, `ctrl+e`, `alt+up`, `f1` or `?`.
+    pub fn parse(raw: &str) -> Option<Key> {
+        let raw = raw.trim().to_lowercase();
+        let (modifier_names, name) = match raw.rsplit_once('+') {
+            // a lone `+` or a chord ending in it, e.g. `ctrl++`
+            Some((rest, "")) => (rest.strip_suffix('+').unwrap_or(rest), "+"),
+            Some((rest, name)) => (rest, name),
+            None => ("", raw.as_str()),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in modifier_names.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let code = match name {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            _ if name.starts_with('f') && name.len() > 1 => {
+                KeyCode::F(name[1..].parse().ok().filter(|n| (1..=12).contains(n))?)
+            }
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        Some(Key { code, modifiers })
+    }
+
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let mut modifiers = event.modifiers;
+        // terminals disagree on whether `?` arrives with Shift held
+        if matches!(event.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        let code = match event.code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        code == self.code && modifiers == self.modifiers
+    }
+
+    fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && (self.modifiers - KeyModifiers::SHIFT).is_empty()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Delete"),
+            KeyCode::Up => write!(f, "{}", glyph::get().up),
+            KeyCode::Down => write!(f, "{}", glyph::get().down),
+            KeyCode::Left => write!(f, "{}", glyph::get().left),
+            KeyCode::Right => write!(f, "{}", glyph::get().right),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Char(c) if !self.modifiers.is_empty() => {
+                write!(f, "{}", c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) => write!(f, "{}", c),
+            _ => write!(f, "?"),
+        }
+    }
+}
+
+/// The keys bound to each action: the defaults, overridden per action by `[keys]`.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            bindings: ACTIONS
+                .iter()
+                .map(|(action, _, defaults)| {
+                    let keys = defaults
+                        .iter()
+                        .map(|key| Key::parse(key).expect("default key should parse"))
+                        .collect();
+                    (*action, keys)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Applies a `[keys]` table; each value is one or more space-separated keys.
+    pub fn rebind(&mut self, item: &Item) {
+        let table = match item.as_table_like() {
+            Some(table) => table,
+            None => return,
+        };
+
+        for (name, value) in table.iter() {
+            let (action, keys) = match (action_named(name), value.as_str().and_then(parse_keys)) {
+                (Some(action), Some(keys)) => (action, keys),
+                _ => continue,
+            };
+            if let Some(binding) = self.bindings.iter_mut().find(|(a, _)| *a == action) {
+                binding.1 = keys;
+            }
+        }
+    }
+
+    /// The action `event` triggers on `screen`, if any.
+    pub fn action(&self, screen: Screen, event: &KeyEvent) -> Option<Action> {
+        SCREEN_ACTIONS
+            .iter()
+            .filter(|(s, _, _)| *s == screen)
+            .map(|(_, action, _)| *action)
+            .find(|action| self.keys(screen, *action).any(|key| key.matches(event)))
+    }
+
+    /// The keys that trigger `action` on `screen`, joined for display, e.g. `? / F1`.
+    pub fn describe(&self, screen: Screen, action: Action) -> String {
+        self.keys(screen, action)
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+
+    /// Key and description of every action on `screen`, for the help overlay.
+    pub fn help(&self, screen: Screen) -> Vec<(String, &'static str)> {
+        SCREEN_ACTIONS
+            .iter()
+            .filter(|(s, _, _)| *s == screen)
+            .map(|(_, action, description)| (self.describe(screen, *action), *description))
+            .filter(|(keys, _)| !keys.is_empty())
+            .collect()
+    }
+
+    /// The status line of `screen`, e.g. `↑↓ select · Enter confirm · Ctrl+C abort`, with the
+    /// first key of each action; empty for screens that list their keys themselves.
+    pub fn status(&self, screen: Screen) -> String {
+        let hints = STATUS_HINTS
+            .iter()
+            .find(|(s, _)| *s == screen)
+            .map_or(&[][..], |(_, hints)| hints);
+        hints
+            .iter()
+            .filter_map(|(actions, label)| {
+                let keys: Vec<String> = actions
+                    .iter()
+                    .filter_map(|action| self.keys(screen, *action).next())
+                    .map(|key| key.to_string())
+                    .collect();
+                if keys.is_empty() {
+                    return None;
+                }
+                // `↑↓`, but `Up/Down` in ASCII
+                let joiner = if keys.iter().all(|key| key.chars().count() == 1) {
+                    ""
+                } else {
+                    "/"
+                };
+                Some(format!("{} {}", keys.join(joiner), label))
+            })
+            .collect::<Vec<_>>()
+            .join(glyph::get().separator)
+    }
+
+    fn keys(&self, screen: Screen, action: Action) -> impl Iterator<Item = &Key> {
+        self.bindings
+            .iter()
+            .filter(move |(a, _)| *a == action)
+            .flat_map(|(_, keys)| keys)
+            .filter(move |key| !(screen.takes_text() && key.is_text()))
+    }
+}
+
+fn action_named(name: &str) -> Option<Action> {
+    ACTIONS
+        .iter()
+        .find(|(_, n, _)| *n == name)
+        .map(|(action, _, _)| *action)
+}
+
+fn parse_keys(raw: &str) -> Option<Vec<Key>> {
+    let keys: Vec<Key> = raw
+        .split_whitespace()
+        .map(Key::parse)
+        .collect::<Option<_>>()?;
+    (!keys.is_empty()).then_some(keys)
+}
+
+/// Whether `name = raw` is a valid `[keys]` entry.
+pub fn is_valid_binding(name: &str, raw: &str) -> bool {
+    action_named(name).is_some() && parse_keys(raw).is_some()
+}
+
+pub fn action_names() -> impl Iterator<Item = &'static str> {
+    ACTIONS.iter().map(|(_, name, _)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn should_parse_keys() {
+        assert_eq!(Key::parse("ctrl+e").unwrap().to_string(), "Ctrl+E");
+        assert_eq!(Key::parse("F1").unwrap().to_string(), "F1");
+        assert_eq!(Key::parse("space").unwrap().to_string(), "Space");
+        assert_eq!(Key::parse("?").unwrap().to_string(), "?");
+        assert!(Key::parse("hyper+x").is_none());
+        assert!(Key::parse("f13").is_none());
+    }
+
+    #[test]
+    fn should_resolve_actions_per_screen() {
+        let mut keymap = Keymap::default();
+        let question = press(KeyCode::Char('?'), KeyModifiers::SHIFT);
+
+        assert_eq!(
+            keymap.action(Screen::Confirm, &question),
+            Some(Action::Help)
+        );
+        // typed into the message instead
+        assert_eq!(keymap.action(Screen::Message, &question), None);
+        assert_eq!(keymap.describe(Screen::Message, Action::Help), "F1");
+
+        let item = toml_edit::Document::parse("edit = \"ctrl+e\"")
+            .unwrap()
+            .as_item()
+            .clone();
+        keymap.rebind(&item);
+        assert_eq!(
+            keymap.action(
+                Screen::Confirm,
+                &press(KeyCode::Char('e'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::Edit)
+        );
+        assert_eq!(
+            keymap.action(
+                Screen::Confirm,
+                &press(KeyCode::Char('e'), KeyModifiers::NONE)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn should_list_the_main_keys_in_the_status_line() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.status(Screen::Scope),
+            "↑↓ select · Enter confirm · Esc clear · F1 help · Ctrl+C abort"
+        );
+        assert_eq!(
+            keymap.status(Screen::Message),
+            "Enter confirm · Alt+Enter new line · Ctrl+E editor · Tab complete · F1 help · Ctrl+C abort"
+        );
+        assert_eq!(keymap.status(Screen::Confirm), "");
+    }
+}
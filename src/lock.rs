@@ -0,0 +1,71 @@
+use crate::git;
+use crate::GczError;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// Held for the whole run so two gcz instances don't race on the index or drafts.
+/// The lock file is removed when this is dropped.
+#[derive(Debug)]
+pub struct RepoLock {
+    path: PathBuf,
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Takes `.git/gcz.lock`, or `None` outside of a repository. With `force`, a lock left by
+/// another run is taken over.
+pub fn acquire(force: bool) -> Result<Option<RepoLock>, GczError> {
+    if git::toplevel()?.is_none() {
+        return Ok(None);
+    }
+
+    let path = git::git_path("gcz.lock")?;
+    if force {
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
+
+    match OpenOptions::new().write(true).create_new(true).open(&path) {
+        Ok(mut file) => {
+            writeln!(file, "{}", process::id())?;
+            Ok(Some(RepoLock { path }))
+        }
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            let pid = fs::read_to_string(&path)
+                .ok()
+                .and_then(|raw| raw.trim().parse::<u32>().ok());
+            Err(GczError::Locked {
+                pid,
+                stale: pid.is_some_and(|pid| !is_running(pid)),
+            })
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    use std::process::{Command, Stdio};
+
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+/// Without a cheap liveness check, every lock is treated as held.
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
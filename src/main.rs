@@ -1,26 +1,162 @@
-use clap::{arg, command};
+use clap::{arg, command, ArgAction, ArgGroup, ArgMatches};
+use config::Config;
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode, KeyModifiers},
     execute,
     style::{Color, Print, SetForegroundColor},
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
-    ExecutableCommand,
+    terminal::{self, Clear, ClearType},
 };
-use std::process::{Command, ExitStatus, Output, Stdio};
+use draft::Draft;
+use keymap::{Action, Keymap, Screen};
+use std::ffi::OsString;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
 use std::{env, io};
-use std::{error::Error, fmt, io::Write, process};
+use std::{error::Error, fmt, io::IsTerminal, io::Write, process};
+use term::{disable_raw_mode, enable_raw_mode};
+use text::TextBuffer;
 use unicode_segmentation::UnicodeSegmentation;
-use unicode_width::UnicodeWidthStr;
 
-pub const COMMIT_TYPES: &'static [&str] = &[
+mod answers;
+mod branch;
+mod browse;
+mod changelog;
+mod complete;
+mod completion;
+mod config;
+mod conventional;
+mod doctor;
+mod draft;
+mod duplicate;
+mod editor;
+mod emoji;
+mod extends;
+mod gh;
+mod git;
+mod glob;
+mod glyph;
+mod hook;
+mod identity;
+mod jj;
+mod keymap;
+mod lint;
+mod lock;
+mod onboarding;
+mod plain;
+mod plugin;
+mod protect;
+mod recurse;
+mod release;
+mod repos;
+mod reword;
+mod scope;
+mod script;
+mod serve;
+mod skeleton;
+mod spell;
+mod split;
+mod stats;
+mod steps;
+mod term;
+mod text;
+mod trailers;
+mod translate;
+mod usage;
+mod version;
+
+pub const COMMIT_TYPES: &[&str] = &[
     "feat", "fix", "docs", "style", "refactor", "perf", "test", "ci", "chore",
 ];
 
+/// The emoji for each type and the shortcode written instead in ASCII mode, which
+/// GitHub and GitLab still render as the emoji.
+pub const TYPE_EMOJIS: &[(&str, &str, &str)] = &[
+    ("feat", "✨", ":sparkles:"),
+    ("fix", "🐛", ":bug:"),
+    ("docs", "📝", ":memo:"),
+    ("style", "🎨", ":art:"),
+    ("refactor", "♻️", ":recycle:"),
+    ("perf", "⚡️", ":zap:"),
+    ("test", "✅", ":white_check_mark:"),
+    ("ci", "👷", ":construction_worker:"),
+    ("chore", "🔧", ":wrench:"),
+];
+
+/// The message being typed is autosaved after this many edits.
+const AUTOSAVE_EVERY: usize = 5;
+
+/// Recent commits whose subjects Up recalls into the message.
+const RECALLED_COMMITS: usize = 50;
+
+/// Number of recent commits `emoji = "auto"` looks at.
+const EMOJI_HISTORY: usize = 20;
+
 #[derive(Debug)]
 enum GczError {
     Io(io::Error),
     UserInterrupt,
+    InvalidConfig(Vec<config::Diagnostic>),
+    Timeout {
+        command: String,
+        seconds: u64,
+    },
+    Locked {
+        pid: Option<u32>,
+        stale: bool,
+    },
+    MessageRejected,
+    UnknownCommand(String),
+    Template(String),
+    /// The file given to `--answers` can't be used.
+    InvalidAnswers {
+        path: String,
+        reason: String,
+    },
+    NoTerminal,
+    CommitFailed,
+    /// `gcz reword` was given a revision that doesn't name a commit.
+    UnknownRevision(String),
+    /// The rebase rewording an older commit stopped before finishing.
+    RebaseFailed,
+    NotARepository,
+    NothingStaged,
+    /// `--resume` found no aborted or failed messages.
+    NoSavedMessages,
+    /// Nothing is staged but these paths added with `git add --intent-to-add`.
+    OnlyIntentToAdd(Vec<String>),
+    /// Amending or rewording `commit` would rewrite what is already on `upstream`.
+    PublishedCommit {
+        commit: String,
+        upstream: String,
+    },
+    /// The author's name or email, the git config `key`, is missing or not allowed.
+    Identity {
+        key: &'static str,
+        reason: String,
+    },
+    /// The current branch matches none of `branch_patterns`.
+    BranchRejected {
+        branch: String,
+        patterns: Vec<String>,
+    },
+    GitCommandFailed {
+        command: String,
+        stderr: String,
+        code: Option<i32>,
+    },
+    EditorFailed {
+        editor: String,
+        code: Option<i32>,
+    },
+    HookRejected {
+        output: String,
+    },
+    NoRemote,
+    PullRequestFailed(String),
+    /// `gcz doctor` found this many errors.
+    ChecksFailed(usize),
 }
 
 impl fmt::Display for GczError {
@@ -28,6 +164,124 @@ impl fmt::Display for GczError {
         match self {
             GczError::Io(err) => write!(f, "IO error: {}", err),
             GczError::UserInterrupt => write!(f, "Interrupted by user"),
+            GczError::InvalidConfig(diagnostics) => {
+                write!(f, "Invalid configuration")?;
+                diagnostics
+                    .iter()
+                    .try_for_each(|diagnostic| write!(f, "\n  {}", diagnostic))
+            }
+            GczError::Timeout { command, seconds } => write!(
+                f,
+                "`{}` did not finish within {}s (see `git_timeout`)",
+                command, seconds
+            ),
+            GczError::MessageRejected => write!(f, "Commit message rejected"),
+            GczError::Template(message) => write!(f, "Template error: {}", message),
+            GczError::InvalidAnswers { path, reason } => {
+                write!(f, "Invalid answers file {}: {}", path, reason)
+            }
+            GczError::CommitFailed => write!(f, "The commit failed"),
+            GczError::UnknownRevision(revision) => write!(f, "Unknown revision: {}", revision),
+            GczError::RebaseFailed => write!(
+                f,
+                "The rebase stopped; finish it with `git rebase --continue` or undo it with `git rebase --abort`"
+            ),
+            GczError::NotARepository => write!(f, "Not a git repository"),
+            GczError::NoRemote => write!(
+                f,
+                "The commit is made, but there is no remote to push it to; add one with `git remote add`"
+            ),
+            GczError::PullRequestFailed(reason) => write!(
+                f,
+                "{}; the branch is pushed, so the pull request can still be opened by hand",
+                reason
+            ),
+            GczError::ChecksFailed(count) => match count {
+                1 => write!(f, "1 check failed"),
+                _ => write!(f, "{} checks failed", count),
+            },
+            GczError::NothingStaged => {
+                write!(f, "No staged changes; stage some with `git add` first")
+            }
+            GczError::NoSavedMessages => write!(
+                f,
+                "No aborted or failed messages to resume in this repository"
+            ),
+            GczError::OnlyIntentToAdd(paths) => write!(
+                f,
+                "No staged changes: {} {} added with `git add -N`, which stages no content; \
+                 stage {} with `git add`",
+                paths.join(", "),
+                if paths.len() == 1 { "was" } else { "were" },
+                if paths.len() == 1 { "it" } else { "them" }
+            ),
+            GczError::BranchRejected { branch, patterns } => write!(
+                f,
+                "The branch `{}` doesn't match `branch_patterns` (`{}`); rename it with \
+                 `git branch -m <name>` or start one with `gcz branch`",
+                branch,
+                patterns.join("`, `")
+            ),
+            GczError::Identity { key, reason } => {
+                write!(f, "{}; set it with `git config {} <value>`", reason, key)
+            }
+            GczError::PublishedCommit { commit, upstream } => write!(
+                f,
+                "{} is already on {}, so rewriting it changes published history; rerun with \
+                 --force-intent to do it anyway",
+                commit, upstream
+            ),
+            GczError::GitCommandFailed {
+                command,
+                stderr,
+                code,
+            } => {
+                match code {
+                    Some(code) => write!(f, "`{}` failed with exit code {}", command, code)?,
+                    None => write!(f, "`{}` was killed by a signal", command)?,
+                }
+                if !stderr.is_empty() {
+                    write!(f, ":\n{}", stderr)?;
+                }
+                Ok(())
+            }
+            GczError::EditorFailed { editor, code } => match code {
+                Some(code) => write!(f, "The editor `{}` exited with code {}", editor, code),
+                None => write!(f, "The editor `{}` was killed by a signal", editor),
+            },
+            GczError::HookRejected { output } => {
+                write!(f, "A git hook rejected the commit")?;
+                if !output.is_empty() {
+                    write!(f, ":\n{}", output)?;
+                }
+                Ok(())
+            }
+            GczError::NoTerminal => write!(
+                f,
+                "gcz needs a terminal for its prompts; to commit without one, pass the message \
+                 with `--message` or `--file` (and the type with `--type`), or every answer with `--answers`"
+            ),
+            GczError::UnknownCommand(name) => write!(
+                f,
+                "Unknown command `{}`: no built-in command or `gcz-{}` plugin on PATH",
+                name, name
+            ),
+            GczError::Locked { pid, stale } => {
+                let owner = pid.map(|pid| format!(" (pid {})", pid)).unwrap_or_default();
+                if *stale {
+                    write!(
+                        f,
+                        "Found a stale lock from an earlier gcz run{}; rerun with --force to remove it",
+                        owner
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Another gcz{} is running in this repository; use --force if it is gone",
+                        owner
+                    )
+                }
+            }
         }
     }
 }
@@ -41,17 +295,361 @@ impl From<io::Error> for GczError {
 }
 
 fn graceful_shutdown(stdout: &mut io::Stdout) -> io::Result<()> {
+    if !stdout.is_terminal() {
+        return Ok(());
+    }
     disable_raw_mode().and_then(|_| execute!(stdout, cursor::Show))
 }
 
 fn main() {
-    let _ = command!()
-        .arg(arg!(-e --emoji "WIP: add emoji to commit template").required(false))
-        .get_matches();
+    let cli = command!()
+        .disable_version_flag(true)
+        .arg(arg!(-V --version "Print the version, the build and the git in use"))
+        .arg(arg!(-e --emoji "Add emoji to commit template").required(false))
+        .arg(
+            arg!(--"no-emoji" "Leave the emoji out, whatever the config says")
+                .conflicts_with("emoji"),
+        )
+        .arg(arg!(-y --yes "Skip the confirmation screens").required(false))
+        .arg(arg!(--compact "Show the commit types on a single line").required(false))
+        .arg(arg!(--plain "Ask with numbered menus and line input, without full-screen prompts"))
+        .arg(arg!(--push "Push the branch after committing"))
+        .arg(arg!(--pr "Push and open a pull request with the GitHub CLI (gh)"))
+        .arg(arg!(--"no-verify" "Skip the pre-commit and commit-msg hooks"))
+        .arg(arg!(--note <TEXT> "Attach TEXT to the new commit as a git note"))
+        .arg(
+            arg!(--recurse <DIR> "Run the wizard in every repository under DIR with staged changes")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(["text", "plain"]),
+        )
+        .arg(
+            arg!(--resume "Pick one of the recently aborted or failed messages to continue with")
+                .conflicts_with_all(["text", "plain", "recurse"]),
+        )
+        .arg(arg!(-t --type <TYPE> "Commit type for --message or --file").requires("text"))
+        .arg(arg!(-s --scope <SCOPE> "Scope for --type").requires("type"))
+        .arg(arg!(-m --message <MESSAGE> "Commit with this message, without any prompts"))
+        .arg(arg!(-F --file <FILE> "Like --message, reading the message from FILE (`-` for stdin)"))
+        .group(ArgGroup::new("text").args(["message", "file"]))
+        .arg(
+            arg!(--answers <FILE> "Commit with the type, scope, subject, body and footers in a TOML file")
+                .conflicts_with_all(["text", "type", "plain", "recurse", "resume"]),
+        )
+        .arg(
+            arg!(--"extra-type" <TYPE> "Offer one more type for this run, as name or name:emoji")
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            arg!(--ascii "Draw only ASCII characters, for terminals that can't show symbols")
+                .global(true),
+        )
+        .arg(arg!(--profile <NAME> "Use a profile from the config file").global(true))
+        .arg(arg!(--force "Take over the repository lock left by another gcz run").global(true))
+        .arg(
+            arg!(--"force-intent" "Amend or reword a commit even when it is already on the upstream")
+                .global(true),
+        )
+        .allow_external_subcommands(true)
+        .subcommand(
+            clap::Command::new("amend-body")
+                .about("Edit only the body of the last commit in the editor and amend it"),
+        )
+        .subcommand(
+            clap::Command::new("branch")
+                .about("Create a branch such as feat/login-page from a type and a description"),
+        )
+        .subcommand(
+            clap::Command::new("browse")
+                .about("List the recent commits by type and scope, filtered as you type"),
+        )
+        .subcommand(
+            clap::Command::new("changelog")
+                .about("Print the changes since the previous tag as Markdown")
+                .arg(arg!([RANGE] "Commits to include, e.g. v1.0.0..v1.1.0"))
+                .arg(arg!(--template <FILE> "Tera template to render instead of the built-in one")),
+        )
+        .subcommand(
+            clap::Command::new("completions")
+                .about("Print a shell completion script that also completes types and scopes")
+                .arg(arg!(<SHELL> "Shell to complete in").value_parser(["bash", "zsh", "fish"])),
+        )
+        .subcommand(
+            clap::Command::new("config")
+                .about("Inspect gcz configuration")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("check").about("Validate config files and report problems"),
+                )
+                .subcommand(
+                    clap::Command::new("get")
+                        .about(
+                            "Print the value in effect for a key, e.g. `types` or `templates.feat`",
+                        )
+                        .arg(arg!(<KEY> "Config key")),
+                )
+                .subcommand(
+                    clap::Command::new("set")
+                        .about("Set a key in a config file, keeping its comments")
+                        .arg(arg!(<KEY> "Config key"))
+                        .arg(arg!(<VALUE> "New value; lists are comma-separated"))
+                        .arg(arg!(--global "Write to the global config").conflicts_with("repo"))
+                        .arg(arg!(--repo "Write to .gcz.toml in the repository")),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("export-config")
+                .about("Print the config in effect as one file, to share with teammates"),
+        )
+        .subcommand(
+            clap::Command::new("import-config")
+                .about("Merge a config file into the global config, or .gcz.toml with --repo")
+                .arg(arg!(<FILE> "Config file to import").value_parser(clap::value_parser!(PathBuf)))
+                .arg(arg!(--repo "Merge into .gcz.toml in the repository")),
+        )
+        .subcommand(
+            clap::Command::new("doctor")
+                .about("Check git, the editor, the terminal, signing, hooks and the config"),
+        )
+        .subcommand(
+            clap::Command::new("hook")
+                .about("Manage the git hooks gcz provides")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("install")
+                        .about("Install a commit-msg hook that runs `gcz lint-msg`"),
+                )
+                .subcommand(
+                    clap::Command::new("print")
+                        .about("Print the config or script that runs `gcz lint-msg` under a hook manager")
+                        .arg(
+                            arg!(<MANAGER> "lefthook, pre-commit, husky or git")
+                                .value_parser(hook::TARGETS),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("lint-msg")
+                .about("Check a commit message file, e.g. from a commit-msg hook")
+                .arg(arg!(<FILE> "Message file")),
+        )
+        .subcommand(
+            clap::Command::new("release")
+                .about("Bump the project version and commit it as a release")
+                .arg(arg!(--tag "Create an annotated tag for the release")),
+        )
+        .subcommand(
+            clap::Command::new("reword")
+                .about("Rewrite the message of an older commit through the wizard")
+                .arg(arg!(<REF> "Commit to reword")),
+        )
+        .subcommand(
+            clap::Command::new("serve")
+                .about("Answer JSON-RPC requests from editor plugins")
+                .arg(
+                    arg!(--stdio "Read requests from stdin and write responses to stdout")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("split").about(
+                "Split the staged changes into several commits, file by file or hunk by hunk",
+            ),
+        )
+        .subcommand(
+            clap::Command::new("stats")
+                .about("Show the types, subject lengths and aborted runs gcz recorded here"),
+        )
+        .subcommand(
+            clap::Command::new("__complete")
+                .hide(true)
+                .arg(arg!(<KIND>).value_parser(["types", "scopes"])),
+        )
+        .subcommand(
+            clap::Command::new("__reword-todo")
+                .hide(true)
+                .arg(arg!(<COMMIT>))
+                .arg(arg!(<MESSAGE_FILE>))
+                .arg(arg!(<TODO_FILE>)),
+        );
+    let matches = cli.clone().get_matches();
+    if matches.get_flag("version") {
+        println!("{}", version::report());
+        return;
+    }
 
     let stdout = &mut io::stdout();
+    let force = matches.get_flag("force");
+    protect::set_force_intent(matches.get_flag("force-intent"));
+    hook::set_no_verify(matches.get_flag("no-verify"));
+    glyph::set_ascii(matches.get_flag("ascii") || glyph::detect());
+
+    let result = match matches.subcommand() {
+        Some(("amend-body", _)) => load_config(&matches).and_then(|config| {
+            let _lock = lock::acquire(force)?;
+            reword::amend_body(&config)
+        }),
+        Some(("branch", _)) => load_config(&matches).and_then(|config| {
+            require_terminal()?;
+            branch::branch(stdout, &config)
+        }),
+        Some(("browse", _)) => load_config(&matches).and_then(|config| {
+            require_terminal()?;
+            browse::browse(stdout, &config)
+        }),
+        Some(("changelog", sub)) => load_config(&matches).and_then(|config| {
+            changelog::changelog(
+                &config,
+                sub.get_one::<String>("RANGE").map(String::as_str),
+                sub.get_one::<String>("template").map(String::as_str),
+            )
+        }),
+        Some(("doctor", _)) => {
+            doctor::doctor(matches.get_one::<String>("profile").map(String::as_str))
+        }
+        Some(("completions", sub)) => {
+            print!(
+                "{}",
+                completion::script(&cli, sub.get_one::<String>("SHELL").unwrap())
+            );
+            Ok(())
+        }
+        Some(("__complete", sub)) => {
+            // quietly, as the shell calls it on every Tab; broken config files are skipped
+            let profile = matches.get_one::<String>("profile").map(String::as_str);
+            let config = config::load(profile).map_or_else(|_| Config::default(), |(c, _)| c);
+            completion::complete(&config, sub.get_one::<String>("KIND").unwrap())
+        }
+        Some(("config", sub)) => {
+            let profile = matches.get_one::<String>("profile").map(String::as_str);
+            match sub.subcommand() {
+                Some(("get", args)) => config::get(args.get_one::<String>("KEY").unwrap(), profile),
+                Some(("set", args)) => config::set(
+                    args.get_one::<String>("KEY").unwrap(),
+                    args.get_one::<String>("VALUE").unwrap(),
+                    if args.get_flag("global") {
+                        Some(config::Scope::Global)
+                    } else if args.get_flag("repo") {
+                        Some(config::Scope::Repo)
+                    } else {
+                        None
+                    },
+                    profile,
+                ),
+                _ => config::check(),
+            }
+        }
+        Some(("export-config", _)) => {
+            config::export(matches.get_one::<String>("profile").map(String::as_str))
+        }
+        Some(("import-config", sub)) => config::import(
+            sub.get_one::<PathBuf>("FILE").unwrap(),
+            if sub.get_flag("repo") {
+                config::Scope::Repo
+            } else {
+                config::Scope::Global
+            },
+        ),
+        Some(("hook", sub)) => match sub.subcommand() {
+            Some(("print", print)) => {
+                print!(
+                    "{}",
+                    hook::snippet(print.get_one::<String>("MANAGER").unwrap())
+                );
+                Ok(())
+            }
+            _ => hook::install().map(|install| println!("{}", install)),
+        },
+        Some(("lint-msg", sub)) => load_config(&matches)
+            .and_then(|config| lint_msg(&config, sub.get_one::<String>("FILE").unwrap())),
+        Some(("release", sub)) => load_config(&matches).and_then(|config| {
+            require_terminal()?;
+            let _lock = lock::acquire(force)?;
+            release::release(stdout, &config, sub.get_flag("tag"))
+        }),
+        Some(("reword", sub)) => load_config(&matches).and_then(|config| {
+            require_terminal()?;
+            let _lock = lock::acquire(force)?;
+            reword::reword(stdout, &config, sub.get_one::<String>("REF").unwrap())
+        }),
+        Some(("serve", _)) => load_config(&matches).and_then(|config| serve::serve(&config)),
+        Some(("split", _)) => load_config(&matches).and_then(|config| {
+            require_terminal()?;
+            let _lock = lock::acquire(force)?;
+            split::split(stdout, &config)
+        }),
+        Some(("stats", _)) => load_config(&matches).and_then(|config| usage::print(&config)),
+        Some(("__reword-todo", sub)) => reword::edit_todo(
+            sub.get_one::<String>("COMMIT").unwrap(),
+            sub.get_one::<String>("MESSAGE_FILE").unwrap(),
+            sub.get_one::<String>("TODO_FILE").unwrap(),
+        ),
+        Some((name, sub)) => {
+            let args: Vec<OsString> = sub
+                .get_many::<OsString>("")
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+            plugin::run_subcommand(name, &args).map(|status| {
+                if !status.success() {
+                    process::exit(status.code().unwrap_or(1));
+                }
+            })
+        }
+        None if matches.contains_id("text") => load_config(&matches).and_then(|config| {
+            let message = match matches.get_one::<String>("file") {
+                Some(path) => read_message_file(path)?,
+                None => matches.get_one::<String>("message").unwrap().clone(),
+            };
+            let _lock = lock::acquire(force)?;
+            gcz_without_prompts(
+                &config,
+                matches.get_one::<String>("type").map(String::as_str),
+                matches.get_one::<String>("scope").map(String::as_str),
+                false,
+                &message,
+                matches.get_one::<String>("note").map(String::as_str),
+            )
+        }),
+        None if matches.contains_id("answers") => load_config(&matches).and_then(|config| {
+            let answers = answers::read(matches.get_one::<String>("answers").unwrap())?;
+            let _lock = lock::acquire(force)?;
+            gcz_without_prompts(
+                &config,
+                Some(&answers.commit_type),
+                answers.scope.as_deref(),
+                answers.breaking.is_some(),
+                &answers.text(),
+                matches.get_one::<String>("note").map(String::as_str),
+            )
+        }),
+        None => first_run(stdout, &matches)
+            .and_then(|_| load_config(&matches))
+            .and_then(|config| {
+                let note = matches.get_one::<String>("note").map(String::as_str);
+                if config.plain {
+                    let _lock = lock::acquire(force)?;
+                    return plain::plain(&config, note)
+                        .inspect_err(|err| record_abort(&config, err));
+                }
+                require_terminal()?;
+                if let Some(dir) = matches.get_one::<PathBuf>("recurse") {
+                    return recurse::recurse(stdout, dir, force, note, || load_config(&matches));
+                }
+                // the chosen repository may have a config of its own
+                let config = if jj::workspace_root().is_none() && repos::pick(stdout, &config)? {
+                    load_config(&matches)?
+                } else {
+                    config
+                };
+                let _lock = lock::acquire(force)?;
+                gcz(stdout, &config, note, matches.get_flag("resume"))
+                    .inspect_err(|err| record_abort(&config, err))
+            }),
+    };
 
-    match gcz(stdout) {
+    match result {
         Ok(_) => {}
         Err(GczError::UserInterrupt) => {
             graceful_shutdown(stdout).expect("Failed to shutdown");
@@ -65,250 +663,2511 @@ fn main() {
     }
 }
 
-fn gcz(stdout: &mut io::Stdout) -> Result<(), GczError> {
-    if !is_inside_git_dir()?.stdout.starts_with(b"true") {
-        println!("Not a git repository");
-        return Ok(());
+fn record_abort(config: &Config, err: &GczError) {
+    if matches!(err, GczError::UserInterrupt) {
+        usage::record_abort(config);
     }
+}
 
-    if exist_stages_changes()?.success() {
-        println!("No staged changes");
+/// Offers the onboarding questions when there's no config yet, unless `--yes` asked for no
+/// questions or `--plain` for no full-screen prompts.
+fn first_run(stdout: &mut io::Stdout, matches: &ArgMatches) -> Result<(), GczError> {
+    if matches.get_flag("yes") || matches.get_flag("plain") || !onboarding::needed()? {
         return Ok(());
     }
+    onboarding::run(stdout)
+}
 
-    let selected_type = select_commit_type(stdout)?;
-    let message = input_commit_message(stdout, &selected_type)?;
+/// Entry point of `gcz lint-msg`: reports problems with the message in `path` and fails on errors.
+fn lint_msg(config: &Config, path: &str) -> Result<(), GczError> {
+    let raw = std::fs::read_to_string(path)?;
+    let message = editor::cleanup(
+        &raw,
+        editor::Cleanup::configured()?,
+        &editor::comment_in(&raw)?,
+    );
+    report_findings(&lint::check_commit_message(&message, config))
+}
 
-    let status = Command::new("git")
-        .args(&["commit", "-m", &message])
-        .status()?;
+/// Prints `findings` for a run without a confirmation screen, failing when any is an error.
+fn report_findings(findings: &[lint::Finding]) -> Result<(), GczError> {
+    for finding in findings {
+        let mark = match finding.severity {
+            config::Severity::Error => glyph::get().error,
+            config::Severity::Warning => glyph::get().warning,
+        };
+        eprintln!("{} {}", mark, finding.message);
+    }
+    if findings
+        .iter()
+        .any(|f| f.severity == config::Severity::Error)
+    {
+        return Err(GczError::MessageRejected);
+    }
+    Ok(())
+}
 
-    if status.success() {
-        return Ok(());
+/// The message for `--file`, from stdin when `path` is `-`.
+fn read_message_file(path: &str) -> Result<String, GczError> {
+    let text = if path == "-" {
+        io::read_to_string(io::stdin())?
     } else {
-        println!("Commit failed");
-        return Ok(());
+        std::fs::read_to_string(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path, err)))?
+    };
+    Ok(text.trim_end().to_string())
+}
+
+/// The prompts read keys in raw mode, which needs a terminal on both ends.
+fn require_terminal() -> Result<(), GczError> {
+    if io::stdin().is_terminal() && io::stdout().is_terminal() {
+        Ok(())
+    } else {
+        Err(GczError::NoTerminal)
     }
 }
 
-fn is_inside_git_dir() -> Result<Output, GczError> {
-    Command::new("git")
-        .args(&["rev-parse", "--is-inside-work-tree"])
-        .output()
-        .map_err(GczError::from)
+/// Loads config files and layers the command-line flags on top of them.
+fn load_config(matches: &ArgMatches) -> Result<Config, GczError> {
+    let profile = matches.get_one::<String>("profile").map(String::as_str);
+    let (mut config, diagnostics) = config::load(profile)?;
+
+    if diagnostics
+        .iter()
+        .any(|d| d.severity == config::Severity::Error)
+    {
+        return Err(GczError::InvalidConfig(diagnostics));
+    }
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+
+    if matches.get_flag("emoji") {
+        config.emoji = Some(true);
+    }
+    if matches.get_flag("no-emoji") {
+        config.emoji = Some(false);
+    }
+    if matches.get_flag("compact") {
+        config.compact = true;
+    }
+    if matches.get_flag("plain") {
+        config.plain = true;
+    }
+    if matches.get_flag("yes") {
+        config.confirm = false;
+    }
+    if matches.get_flag("push") {
+        config.push = true;
+    }
+    if matches.get_flag("pr") {
+        config.pull_request = true;
+    }
+    if matches.get_flag("ascii") {
+        config.ascii = Some(true);
+    }
+    for spec in matches
+        .get_many::<String>("extra-type")
+        .into_iter()
+        .flatten()
+    {
+        config.add_extra_type(spec);
+    }
+    git::set_timeout(config.git_timeout);
+    glyph::set_ascii(config.ascii.unwrap_or_else(glyph::detect));
+    term::set_keyboard_protocol(config.keyboard_protocol);
+    term::set_status_line(config.status_line);
+    text::set_legacy_width(config.emoji_width == "legacy");
+    if config.emoji.is_none() {
+        config.emoji = Some(history_uses_emoji()?);
+    }
+    Ok(config)
 }
 
-fn exist_stages_changes() -> Result<ExitStatus, GczError> {
-    Command::new("git")
-        .args(&["diff", "--cached", "--exit-code"])
-        .stdout(Stdio::null()) // just check the status
-        .stderr(Stdio::null())
-        .status()
-        .map_err(GczError::from)
+fn emoji_for<'a>(config: &'a Config, commit_type: &str) -> Option<&'a str> {
+    if let Some((_, emoji)) = config.type_emojis.iter().find(|(t, _)| t == commit_type) {
+        return Some(emoji);
+    }
+    TYPE_EMOJIS
+        .iter()
+        .find(|(t, _, _)| *t == commit_type)
+        .map(|(_, emoji, shortcode)| if glyph::is_ascii() { shortcode } else { emoji })
+        .copied()
 }
 
-fn select_commit_type(stdout: &mut io::Stdout) -> Result<String, GczError> {
-    enable_raw_mode()
-        .map_err(GczError::from)
-        .and_then(|_| execute!(stdout, cursor::Hide, Clear(ClearType::All)).map_err(GczError::from))
-        .and_then(|_| handle_commit_type(stdout))
-        .and_then(|input| finalize(input, stdout))
+/// `emoji = "auto"`: on when at least half of the recent commits have an emoji.
+fn history_uses_emoji() -> Result<bool, GczError> {
+    let subjects = git::recent_subjects(EMOJI_HISTORY)?;
+    let with_emoji = subjects
+        .iter()
+        .filter(|subject| conventional::has_emoji(subject))
+        .count();
+    Ok(!subjects.is_empty() && with_emoji * 2 >= subjects.len())
 }
 
-fn handle_commit_type(stdout: &mut io::Stdout) -> Result<String, GczError> {
-    let mut selected_index = 0;
-    let mut input = String::new();
-    let mut is_selected = false;
+/// The emoji `emoji` mode puts in the header: the type's, and the one `scope_emojis` or
+/// `path_emojis` maps the scope or the staged `paths` to, as `mapped_emoji` says.
+fn header_emoji(
+    config: &Config,
+    commit_type: &str,
+    scope: Option<&str>,
+    paths: &[String],
+) -> Option<String> {
+    if config.emoji != Some(true) {
+        return None;
+    }
+    let mapped = config.emoji_for_changes(scope, paths);
+    match (emoji_for(config, commit_type), mapped) {
+        (Some(emoji), Some(mapped)) if emoji::same(emoji, mapped) => Some(emoji.to_string()),
+        (Some(emoji), Some(mapped)) if config.mapped_emoji == "add" => {
+            Some(format!("{} {}", emoji, mapped))
+        }
+        (emoji, mapped) => mapped.or(emoji).map(str::to_string),
+    }
+}
 
-    loop {
-        stdout.execute(Clear(ClearType::All))?;
+/// The start of the header for `commit_type`, e.g. `feat(api): ✨ `, with the emoji
+/// where `emoji_position` puts it.
+fn type_prefix(
+    config: &Config,
+    commit_type: &str,
+    scope: Option<&str>,
+    emoji: Option<&str>,
+) -> String {
+    let scope = scope
+        .map(|scope| format!("({})", scope))
+        .unwrap_or_default();
+    match (emoji, config.emoji_position.as_str()) {
+        (Some(emoji), "before_type") => format!("{} {}{}: ", emoji, commit_type, scope),
+        (Some(emoji), "instead_of_type") if scope.is_empty() => format!("{} ", emoji),
+        (Some(emoji), "instead_of_type") => format!("{} {}: ", emoji, scope),
+        (Some(emoji), _) => format!("{}{}: {} ", commit_type, scope, emoji),
+        (None, _) => format!("{}{}: ", commit_type, scope),
+    }
+}
 
-        if is_selected {
-            execute!(
-                stdout,
-                cursor::MoveTo(0, 0),
-                Print("Selected commit type: "),
-                SetForegroundColor(Color::Cyan),
-                Print(&input),
-                SetForegroundColor(Color::Reset),
-                cursor::MoveToNextLine(1)
-            )?;
-            break Ok(input);
+/// The value of a template variable: `{env.NAME}` reads an environment variable and a
+/// dotted name such as `{user.name}` a git config key. Unset and empty values are `None`.
+fn template_variable(name: &str) -> Option<String> {
+    let value = match name.strip_prefix("env.") {
+        Some(var) => env::var(var).ok(),
+        None if name.contains('.') => git::config_value(name).ok().flatten(),
+        None => None,
+    };
+    value.filter(|value| !value.trim().is_empty())
+}
+
+/// Fills the variables of a subject template and strips its other `{placeholder}`s,
+/// returning the grapheme offset of the first one so the cursor can start there.
+fn expand_template(
+    template: &str,
+    variable: impl Fn(&str) -> Option<String>,
+) -> (String, Option<usize>) {
+    let mut text = String::new();
+    let mut cursor = None;
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        text.push_str(&rest[..start]);
+        match variable(&rest[start + 1..end]) {
+            Some(value) => text.push_str(&value),
+            None => {
+                cursor.get_or_insert_with(|| text.graphemes(true).count());
+            }
         }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
 
-        execute!(
+    (text, cursor)
+}
+
+/// Fills every `{placeholder}` of a footer template, or `None` when one has no value.
+fn expand_footer(footer: &str, variable: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let mut text = String::new();
+    let mut rest = footer;
+
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        text.push_str(&rest[..start]);
+        text.push_str(&variable(&rest[start + 1..end])?);
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+
+    Some(text)
+}
+
+/// Adds `trailers` the way git does, honoring `trailer.*` in the git config (placement,
+/// what to do when one exists, separators). Without git they are simply appended.
+fn add_trailers(message: &str, trailers: &[String]) -> Result<String, GczError> {
+    if trailers.is_empty() {
+        return Ok(message.to_string());
+    }
+    match git::interpret_trailers(message, trailers) {
+        Ok(Some(with_trailers)) => Ok(with_trailers),
+        Ok(None) | Err(GczError::Io(_)) => Ok(trailers
+            .iter()
+            .fold(message.to_string(), |message, trailer| {
+                append_trailer(&message, trailer)
+            })),
+        Err(err) => Err(err),
+    }
+}
+
+/// Adds `trailer` to the trailer block at the end of `message`, starting one if needed.
+fn append_trailer(message: &str, trailer: &str) -> String {
+    let last_paragraph = message.rsplit("\n\n").next().unwrap_or("");
+    let has_trailers = message.contains("\n\n")
+        && last_paragraph.lines().all(|line| {
+            line.split_once(": ")
+                .is_some_and(|(key, _)| !key.is_empty() && !key.contains(' '))
+        });
+
+    if has_trailers {
+        format!("{}\n{}", message, trailer)
+    } else {
+        format!("{}\n\n{}", message, trailer)
+    }
+}
+
+/// The interactive wizard. `note` is attached to the commit as a git note, or asked for
+/// when `ask_note` is on; with `resume`, the message starts from one picked from the
+/// draft history.
+fn gcz(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    note: Option<&str>,
+    resume: bool,
+) -> Result<(), GczError> {
+    let _progress = steps::start(config);
+    if jj::workspace_root().is_some() {
+        return gcz_jj(stdout, config);
+    }
+    if !git::is_inside_git_dir()?.stdout.starts_with(b"true") {
+        return Err(GczError::NotARepository);
+    }
+
+    offer_intent_to_add(stdout, config)?;
+    if git::exist_stages_changes()?.success() {
+        return Err(GczError::NothingStaged);
+    }
+    protect::check_name(config)?;
+    protect::confirm(stdout, config)?;
+    identity::confirm(stdout, config)?;
+
+    let partially_staged =
+        git::partially_staged_files(&git::staged_files()?, &git::unstaged_files()?);
+    if !partially_staged.is_empty() {
+        if !config.confirm {
+            eprintln!(
+                "Warning: unstaged changes in {} will not be committed",
+                partially_staged.join(", ")
+            );
+        } else if warn_partially_staged(stdout, &partially_staged, &config.keymap)? {
+            git::stage_files(&partially_staged)?;
+        }
+    }
+
+    let draft = Draft::open()?;
+    let recovered = if resume {
+        resume_draft(stdout, &draft, &config.keymap)?
+    } else {
+        recover_draft(stdout, &draft, &config.keymap)?
+    };
+    let (text, cursor) = match recovered {
+        Some(text) => (text, None),
+        None => {
+            let mut untracked = UntrackedFiles::new(git::untracked_files()?);
+            start_message(stdout, config, &mut untracked, &git::staged_files()?)?
+        }
+    };
+
+    let staged_findings = check_staged_changes(config)?;
+    let composed = match compose_message(
+        stdout,
+        config,
+        text,
+        cursor,
+        &staged_findings,
+        Some(&git::staged_diff()?),
+        Some(&draft),
+        true,
+    ) {
+        Ok(composed) => composed,
+        Err(err) => {
+            draft.remember()?;
+            return Err(err);
+        }
+    };
+    let note = match note {
+        Some(note) => Some(note.to_string()),
+        None if config.ask_note => match ask_line(
             stdout,
-            cursor::MoveTo(0, 0),
-            Print(format!("Select a commit type: {}", &input)),
-            cursor::MoveToNextLine(1)
-        )?;
+            "Note to attach, e.g. a review link (leave empty for none):",
+            &config.keymap,
+        ) {
+            Ok(note) => Some(note),
+            Err(err) => {
+                draft.remember()?;
+                return Err(err);
+            }
+        },
+        None => None,
+    };
 
-        let filtered_types: Vec<(usize, &'static str)> = filter_type_by_input(&input);
+    let committed = match &composed.fold_into {
+        Some(duplicate) => duplicate::fold(stdout, config, duplicate),
+        None => hook::commit_with_triage(stdout, config, &composed.message),
+    };
+    if let Err(err) = committed {
+        draft.remember()?;
+        println!("The message is kept and offered again on the next run");
+        return Err(err);
+    }
+    draft.clear()?;
+    usage::record_commit(config, &composed.message);
+    add_note(note.as_deref());
+    // an amended commit can only be pushed over the old one with --force
+    if composed.fold_into.is_none() {
+        publish(config, &composed.message)?;
+    }
+    stats::print(config);
+    Ok(())
+}
 
-        for (i, &(_, commit_type)) in filtered_types.iter().enumerate() {
-            if i == selected_index {
-                execute!(
-                    stdout,
-                    SetForegroundColor(Color::Green),
-                    Print(format!("❯ {}", commit_type)),
-                    SetForegroundColor(Color::Reset),
-                    cursor::MoveToNextLine(1),
-                )?;
-            } else {
-                execute!(
-                    stdout,
-                    Print(format!("  {}", commit_type)),
-                    cursor::MoveToNextLine(1)
-                )?;
+/// `gcz --message` and `gcz --answers`: commits `text`, prefixed with `commit_type` and
+/// `scope` (and `!` when `breaking`), without asking anything.
+/// The checks run as usual and any error stops the commit.
+fn gcz_without_prompts(
+    config: &Config,
+    commit_type: Option<&str>,
+    scope: Option<&str>,
+    breaking: bool,
+    text: &str,
+    note: Option<&str>,
+) -> Result<(), GczError> {
+    let in_jj = jj::workspace_root().is_some();
+    if !has_changes(in_jj)? {
+        return Ok(());
+    }
+    if !in_jj {
+        protect::check_name(config)?;
+        identity::check(config)?;
+    }
+
+    let text = match commit_type {
+        Some(commit_type) => {
+            let paths = changed_paths(in_jj)?;
+            let emoji = header_emoji(config, commit_type, scope, &paths);
+            let mut prefix = type_prefix(config, commit_type, scope, emoji.as_deref());
+            if breaking {
+                prefix = prefix.replacen(": ", "!: ", 1);
             }
+            format!("{}{}", prefix, text)
         }
-        stdout.flush()?;
+        None => text.to_string(),
+    };
+    let (message, mut findings) = finish_message(config, &text)?;
+    findings.splice(0..0, lint::check_header(&message, config));
+    findings.extend(change_findings(config, in_jj)?);
+    if !in_jj {
+        findings.extend(protect::problem(config)?.map(|message| lint::Finding {
+            severity: config::Severity::Warning,
+            message,
+        }));
+    }
+    report_findings(&findings)?;
+    commit_changes(config, &message, in_jj, note)
+}
 
-        if let Event::Key(key_event) = event::read()? {
-            match (key_event.code, key_event.modifiers) {
-                (KeyCode::Char('c'), KeyModifiers::CONTROL)
-                | (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
-                    return Err(GczError::UserInterrupt);
-                }
-                (KeyCode::Up, _) => {
-                    if selected_index > 0 {
-                        selected_index -= 1
-                    } else {
-                        selected_index = filtered_types.len() - 1
-                    }
-                }
-                (KeyCode::Down, _) => {
-                    if selected_index < filtered_types.len() - 1 {
-                        selected_index += 1
-                    } else {
-                        selected_index = 0
-                    }
-                }
+/// Fails outside a repository or with nothing staged. In a jj workspace without changes it
+/// says so and returns false.
+fn has_changes(in_jj: bool) -> Result<bool, GczError> {
+    if in_jj {
+        if jj::changed_files()?.is_empty() {
+            println!("No changes in the working copy");
+            return Ok(false);
+        }
+    } else if !git::is_inside_git_dir()?.stdout.starts_with(b"true") {
+        return Err(GczError::NotARepository);
+    } else if git::exist_stages_changes()?.success() {
+        let intended = git::intent_to_add_files()?;
+        if !intended.is_empty() {
+            return Err(GczError::OnlyIntentToAdd(intended));
+        }
+        return Err(GczError::NothingStaged);
+    }
+    Ok(true)
+}
 
-                (KeyCode::Enter, _) => {
-                    if !filtered_types.is_empty() {
-                        input = filtered_types[selected_index].1.to_string();
-                        is_selected = true;
-                    }
-                }
-                (KeyCode::Char(c), _) => {
-                    input.push(c);
-                    selected_index = 0
-                }
-                (KeyCode::Backspace, _) => {
-                    input.pop();
-                    selected_index = 0;
-                }
-                (KeyCode::Esc, _) => {
-                    input.clear();
-                    selected_index = 0;
-                }
-                _ => continue,
-            }
+/// Paths added with `git add -N` have no staged content and would be left out of the commit
+/// without a word; offers to stage them. Without confirmations they are only reported, by
+/// [`check_staged_changes`] or, when nothing else is staged, as the error.
+fn offer_intent_to_add(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    let intended = git::intent_to_add_files()?;
+    if intended.is_empty() {
+        return Ok(());
+    }
+    let nothing_else = git::exist_stages_changes()?.success();
+    if !config.confirm {
+        if nothing_else {
+            return Err(GczError::OnlyIntentToAdd(intended));
+        }
+        return Ok(());
+    }
+
+    let title = match intended.as_slice() {
+        [path] => format!(
+            "`{}` was added with `git add -N`; its content isn't staged",
+            path
+        ),
+        paths => format!(
+            "{} files were added with `git add -N`; their content isn't staged: {}",
+            paths.len(),
+            paths.join(", ")
+        ),
+    };
+    let mut options = vec!["Stage the content".to_string()];
+    if !nothing_else {
+        options.push(if intended.len() == 1 {
+            "Commit without it".to_string()
+        } else {
+            "Commit without them".to_string()
+        });
+    }
+    options.push("Abort".to_string());
+    match select_option(stdout, &title, &options, &config.keymap)? {
+        0 => {
+            git::stage_files(&intended)?;
+            Ok(())
         }
+        1 if !nothing_else => Ok(()),
+        _ => Err(GczError::UserInterrupt),
     }
 }
 
-fn filter_type_by_input(input: &str) -> Vec<(usize, &'static str)> {
-    COMMIT_TYPES
-        .iter()
-        .enumerate()
-        .filter(|(_, &t)| t.to_lowercase().contains(&input.to_lowercase()))
-        .map(|(i, &t)| (i, t))
-        .collect()
+/// The paths the commit will contain: the staged ones, or the working-copy change in jj.
+fn changed_paths(in_jj: bool) -> Result<Vec<String>, GczError> {
+    if in_jj {
+        jj::changed_files()
+    } else {
+        git::staged_files()
+    }
 }
 
-fn finalize(input: String, stdout: &mut io::Stdout) -> Result<String, GczError> {
-    disable_raw_mode()?;
-    execute!(stdout, cursor::Show, cursor::MoveToNextLine(1))?;
-    Ok(input)
+/// [`check_staged_changes`], or just the diff checks in jj.
+fn change_findings(config: &Config, in_jj: bool) -> Result<Vec<lint::Finding>, GczError> {
+    if !in_jj {
+        return check_staged_changes(config);
+    }
+    if !config.staged_checks {
+        return Ok(Vec::new());
+    }
+    Ok(lint::check_staged_diff(
+        &jj::diff()?,
+        &config.staged_patterns,
+    ))
 }
 
-fn input_commit_message(stdout: &mut io::Stdout, commit_type: &str) -> Result<String, GczError> {
-    let mut message = format!("{}: ", commit_type);
-    let mut cursor_pos = message.graphemes(true).count();
+/// Commits `message` without asking anything more, then attaches `note` and publishes.
+fn commit_changes(
+    config: &Config,
+    message: &str,
+    in_jj: bool,
+    note: Option<&str>,
+) -> Result<(), GczError> {
+    if !in_jj {
+        commit_staged(config, message, false)?;
+        usage::record_commit(config, message);
+        add_note(note);
+        publish(config, message)?;
+        stats::print(config);
+        return Ok(());
+    }
+    if note.is_some() {
+        eprintln!("Warning: notes are a git feature; --note is ignored in a jj workspace");
+    }
+    if !jj_commit(config, message)?.success() {
+        return Err(GczError::CommitFailed);
+    }
+    Ok(())
+}
 
-    enable_raw_mode()?;
-    loop {
-        let cursor_display_width =
-            UnicodeWidthStr::width(&message[..cursor_byte_index(&message, cursor_pos)]);
+/// The wizard in a jj workspace. jj has no index: the whole working-copy change is
+/// described and committed, and every file in it is already tracked.
+fn gcz_jj(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    let changed = jj::changed_files()?;
+    if changed.is_empty() {
+        println!("No changes in the working copy");
+        return Ok(());
+    }
 
-        execute!(
-            stdout,
-            Clear(ClearType::CurrentLine),
-            cursor::MoveToColumn(0),
-            Print(&message),
-            cursor::MoveToColumn(cursor_display_width as u16)
-        )?;
-        stdout.flush()?;
+    let mut untracked = UntrackedFiles::new(Vec::new());
+    let (text, cursor) = start_message(stdout, config, &mut untracked, &changed)?;
+    let diff = jj::diff()?;
+    let findings = if config.staged_checks {
+        lint::check_staged_diff(&diff, &config.staged_patterns)
+    } else {
+        Vec::new()
+    };
+    let message = compose_message(
+        stdout,
+        config,
+        text,
+        cursor,
+        &findings,
+        Some(&diff),
+        None,
+        false,
+    )?
+    .message;
 
-        if let Event::Key(key_event) = event::read()? {
-            match (key_event.code, key_event.modifiers) {
-                (KeyCode::Char('c'), KeyModifiers::CONTROL)
-                | (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
-                    disable_raw_mode()?;
-                    return Err(GczError::UserInterrupt);
-                }
-                (KeyCode::Enter, _) => {
-                    disable_raw_mode()?;
-                    execute!(stdout, cursor::MoveToNextLine(2))?;
-                    return Ok(message);
-                }
-                (KeyCode::Char(c), _) => {
-                    let mut graphemes: Vec<&str> = message.graphemes(true).collect();
-                    let character = c.to_string();
-                    graphemes.insert(cursor_pos, &character);
-                    message = graphemes.concat();
-                    cursor_pos += 1;
-                }
-                (KeyCode::Backspace, _) if cursor_pos > 0 => {
-                    let mut graphemes: Vec<&str> = message.graphemes(true).collect();
-                    cursor_pos -= 1;
-                    graphemes.remove(cursor_pos);
-                    message = graphemes.concat();
-                }
-                (KeyCode::Delete, _) => {
-                    let mut graphemes: Vec<&str> = message.graphemes(true).collect();
-                    if cursor_pos < graphemes.len() {
-                        graphemes.remove(cursor_pos);
-                        message = graphemes.concat();
-                    }
-                }
-                (KeyCode::Left, _) if cursor_pos > 0 => {
-                    cursor_pos -= 1;
-                }
-                (KeyCode::Right, _) => {
-                    let graphemes_count = message.graphemes(true).count();
-                    if cursor_pos < graphemes_count {
-                        cursor_pos += 1;
-                    }
-                }
-                (KeyCode::Home, _) => cursor_pos = 0,
-                (KeyCode::End, _) => cursor_pos = message.graphemes(true).count(),
-                _ => continue,
-            }
+    if !jj_commit(config, &message)?.success() {
+        println!("Commit failed");
+    }
+    Ok(())
+}
+
+/// `jj commit` has no `--signoff`, so the trailer is added here.
+fn jj_commit(config: &Config, message: &str) -> Result<ExitStatus, GczError> {
+    if !config.signoff {
+        return jj::commit(message);
+    }
+    match jj::user()? {
+        Some(user) => jj::commit(&add_trailers(
+            message,
+            &[format!("Signed-off-by: {}", user)],
+        )?),
+        None => {
+            eprintln!("Warning: not signed off; set user.name and user.email for jj");
+            jj::commit(message)
         }
     }
 }
 
-fn cursor_byte_index(s: &str, cursor_pos: usize) -> usize {
-    s.grapheme_indices(true)
-        .nth(cursor_pos)
-        .map(|(idx, _)| idx)
-        .unwrap_or_else(|| s.len())
+/// Asks for the type and returns the message to start editing from, with the cursor
+/// position the template asks for.
+fn start_message(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    untracked: &mut UntrackedFiles,
+    staged: &[String],
+) -> Result<(String, Option<usize>), GczError> {
+    let suggested = match config.suggested_type(staged) {
+        Some(commit_type) => Some(commit_type.to_string()),
+        None => plugin::suggested_type(config)?,
+    };
+    let (selected_type, prefix) =
+        choose_prefix(stdout, config, untracked, staged, suggested.as_deref())?;
+    let (template, template_cursor) = expand_template(
+        config.template_for(&selected_type).unwrap_or(""),
+        template_variable,
+    );
+    let cursor = template_cursor.map(|offset| prefix.graphemes(true).count() + offset);
+    let text = format!("{}{}", prefix, template);
+
+    Ok(match plugin::prefill(config, &selected_type, &text)? {
+        Some(prefilled) => (prefilled, None),
+        None => (text, cursor),
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Asks for the type, then for the scope and emoji as the config says, returning the type
+/// and the header prefix they make up.
+fn choose_prefix(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    untracked: &mut UntrackedFiles,
+    staged: &[String],
+    suggested: Option<&str>,
+) -> Result<(String, String), GczError> {
+    let (selected_type, scope) = if config.ask_scope && config.scope_pane {
+        let types = favorites_first(&types_by_use(config), &config.favorite_types);
+        scope::select_type_and_scope(stdout, config, &types, suggested)?
+    } else {
+        let selected_type = select_commit_type(stdout, config, untracked, suggested)?;
+        let scope = if config.ask_scope {
+            scope::select_scope(stdout, config, &selected_type)?
+        } else {
+            None
+        };
+        (selected_type, scope)
+    };
+    let config = &emoji::with_toggle(config);
+    let emoji = if config.emoji == Some(true) && config.emoji_picker {
+        let mapped = config.emoji_for_changes(scope.as_deref(), staged);
+        emoji::pick(stdout, config, &selected_type, mapped)?
+    } else {
+        header_emoji(config, &selected_type, scope.as_deref(), staged)
+    };
+    let prefix = type_prefix(config, &selected_type, scope.as_deref(), emoji.as_deref());
+    Ok((selected_type, prefix))
+}
 
-    #[test]
-    fn should_filter() {
-        let input = "f";
-        let result = filter_type_by_input(input);
-        assert_eq!(
-            result,
-            vec![(0, "feat"), (1, "fix"), (4, "refactor"), (5, "perf")]
+/// Offers the message an earlier run autosaved but never committed. Discarding it removes the
+/// draft, which stays in the history.
+fn recover_draft(
+    stdout: &mut io::Stdout,
+    draft: &Draft,
+    keymap: &Keymap,
+) -> Result<Option<String>, GczError> {
+    let saved = match draft.load() {
+        Some(saved) => saved,
+        None => return Ok(None),
+    };
+
+    let title = format!(
+        "Found an unfinished message from an earlier run: {}",
+        saved.lines().next().unwrap_or("")
+    );
+    let options = ["Restore it".to_string(), "Discard it".to_string()];
+    if select_option(stdout, &title, &options, keymap)? == 0 {
+        Ok(Some(saved))
+    } else {
+        draft.discard()?;
+        Ok(None)
+    }
+}
+
+/// `--resume`: picks one of the aborted or failed messages, newest first.
+fn resume_draft(
+    stdout: &mut io::Stdout,
+    draft: &Draft,
+    keymap: &Keymap,
+) -> Result<Option<String>, GczError> {
+    // the current draft is in the list too
+    draft.remember()?;
+    let history = draft.history();
+    if history.is_empty() {
+        return Err(GczError::NoSavedMessages);
+    }
+    let options: Vec<String> = history
+        .iter()
+        .map(|message| {
+            let mut lines = message.lines();
+            let subject = lines.next().unwrap_or("");
+            match lines.filter(|line| !line.trim().is_empty()).count() {
+                0 => subject.to_string(),
+                1 => format!("{} (+1 line)", subject),
+                more => format!("{} (+{} lines)", subject, more),
+            }
+        })
+        .collect();
+    let chosen = select_option(stdout, "Resume which message?", &options, keymap)?;
+    Ok(Some(draft.resume(chosen)?))
+}
+
+/// Findings about the staged content itself, which don't change while the message is edited.
+fn check_staged_changes(config: &Config) -> Result<Vec<lint::Finding>, GczError> {
+    let mut findings = if config.staged_checks {
+        lint::check_staged_diff(&git::staged_diff()?, &config.staged_patterns)
+    } else {
+        Vec::new()
+    };
+
+    findings.extend(
+        git::intent_to_add_files()?
+            .into_iter()
+            .map(|path| lint::Finding {
+                severity: config::Severity::Warning,
+                message: format!(
+                    "{} isn't in the commit: it was added with `git add -N` and has no staged content",
+                    path
+                ),
+            }),
+    );
+
+    let blob_sizes = git::staged_blob_sizes()?;
+    let paths: Vec<String> = blob_sizes.iter().map(|(path, _)| path.clone()).collect();
+    findings.extend(lint::check_large_files(
+        &blob_sizes,
+        &git::lfs_tracked(&paths)?,
+        config,
+    ));
+    Ok(findings)
+}
+
+/// The message the confirmation screen ended with.
+struct Composed {
+    message: String,
+    /// The recent commit with the same subject the staged changes go into instead, when the
+    /// user chose so.
+    fold_into: Option<duplicate::Duplicate>,
+}
+
+/// Edits the header starting from `text` and loops through the confirmation screen
+/// until the user commits, returning the full message. The header is autosaved to `draft`.
+/// With the `diff` being committed, an empty body can be started from a list of the changes.
+/// With `fold`, a recent commit with the same subject is pointed out, and the changes can
+/// go into it instead.
+#[allow(clippy::too_many_arguments)]
+fn compose_message(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    text: String,
+    mut cursor: Option<usize>,
+    staged_findings: &[lint::Finding],
+    diff: Option<&str>,
+    draft: Option<&Draft>,
+    fold: bool,
+) -> Result<Composed, GczError> {
+    let (header, body) = conventional::split_message(&text);
+    let mut header = header.to_string();
+    let mut body = body.to_string();
+    // offered once, for the first version of the message
+    let mut translate = config.translate_command.is_some();
+    let mut ask_trailers = steps::includes(Screen::Trailers);
+    let speller = spell::Speller::load(config)?;
+    let history = recall_history(draft);
+    let vocabulary = diff.map(complete::vocabulary).unwrap_or_default();
+    let options = MessageOptions {
+        speller: speller.as_ref(),
+        confirm_abort: config.confirm_abort,
+        history: &history,
+        vocabulary: &vocabulary,
+        preview: config.header_preview,
+    };
+
+    loop {
+        header = edit_message(stdout, config, &header, cursor, &mut body, draft, &options)?;
+        let mut translation_failure = None;
+        if translate {
+            translate = false;
+            match translate::offer(stdout, config, &mut header, &mut body)? {
+                translate::Offer::Edit => {
+                    cursor = None;
+                    continue;
+                }
+                translate::Offer::Failed(reason) => translation_failure = Some(reason),
+                translate::Offer::Translated | translate::Offer::Kept => {}
+            }
+        }
+        if ask_trailers {
+            ask_trailers = false;
+            // into the body, so editing the message again can change them
+            let trailers = trailers::choose(stdout, config)?;
+            let text = add_trailers(&join_message(&header, &body), &trailers)?;
+            let (with_header, with_body) = conventional::split_message(&text);
+            (header, body) = (with_header.to_string(), with_body.to_string());
+        }
+        let (message, mut findings) = finish_message(config, &join_message(&header, &body))?;
+        // the prefix can be edited away, in the inline editor or an external one
+        findings.splice(0..0, lint::check_grammar(&message, config));
+        if let Some(reason) = translation_failure {
+            findings.push(lint::Finding {
+                severity: config::Severity::Warning,
+                message: format!("The message wasn't translated: {}", reason),
+            });
+        }
+        if let Some(speller) = &speller {
+            findings.extend(speller.findings(&message));
+        }
+        let duplicate = if fold {
+            duplicate::find(conventional::split_message(&message).0)?
+        } else {
+            None
+        };
+        findings.extend(duplicate.as_ref().map(duplicate::Duplicate::finding));
+        findings.extend(staged_findings.iter().cloned());
+        findings.extend(hook::skipped_finding()?);
+
+        let blocked = findings
+            .iter()
+            .any(|f| f.severity == config::Severity::Error);
+        if !config.confirm && !blocked {
+            for finding in &findings {
+                eprintln!("{} {}", glyph::get().warning, finding.message);
+            }
+            return Ok(Composed {
+                message,
+                fold_into: None,
+            });
+        }
+
+        let skeleton = match diff {
+            Some(diff) if body.trim().is_empty() => skeleton::from_diff(diff),
+            _ => String::new(),
+        };
+        match confirm_commit(
+            stdout,
+            &message,
+            &findings,
+            !skeleton.is_empty(),
+            duplicate
+                .as_ref()
+                .map(duplicate::Duplicate::action)
+                .as_deref(),
+            speller.as_ref(),
+            &config.keymap,
+        )? {
+            Confirmation::Commit => {
+                return Ok(Composed {
+                    message,
+                    fold_into: None,
+                })
+            }
+            Confirmation::Fold => {
+                return Ok(Composed {
+                    message,
+                    fold_into: duplicate,
+                })
+            }
+            Confirmation::Edit => cursor = None,
+            Confirmation::AddBody => {
+                body = format!("\n{}", skeleton);
+                cursor = None;
+            }
+        }
+    }
+}
+
+/// Adds the co-author trailers, runs the script and tidies the whitespace, returning the
+/// message to commit with what the checks and plugins found in it.
+fn finish_message(config: &Config, text: &str) -> Result<(String, Vec<lint::Finding>), GczError> {
+    let trailers: Vec<String> = config
+        .co_authors
+        .iter()
+        .map(|author| format!("Co-authored-by: {}", author))
+        .chain(
+            config
+                .footers
+                .iter()
+                .filter_map(|footer| expand_footer(footer, template_variable)),
+        )
+        .collect();
+    let mut message = add_trailers(text, &trailers)?;
+    let mut findings = Vec::new();
+    match script::apply(config, &message) {
+        Ok(transformed) => message = transformed,
+        Err(reason) => findings.push(lint::Finding {
+            severity: config::Severity::Error,
+            message: reason,
+        }),
+    }
+    message = text::tidy_whitespace(&message, config.expand_tabs);
+    findings.extend(lint::check_message(&message, config));
+    findings.extend(plugin::lint(config, &message)?);
+    Ok((message, findings))
+}
+
+/// Subjects to recall into the message, newest first: those of the aborted and failed
+/// messages, then of the recent commits.
+fn recall_history(draft: Option<&Draft>) -> Vec<String> {
+    let saved = draft.map(Draft::history).unwrap_or_default();
+    // no commits yet
+    let log = git::recent_log(RECALLED_COMMITS).unwrap_or_default();
+    let mut subjects: Vec<String> = Vec::new();
+    for message in saved.iter().chain(log.iter().map(|entry| &entry.message)) {
+        let subject = message.lines().next().unwrap_or("").trim();
+        if !subject.is_empty() && !subjects.iter().any(|s| s == subject) {
+            subjects.push(subject.to_string());
+        }
+    }
+    subjects
+}
+
+/// Edits `header` and `body` inline, switching to the external editor and back on request.
+/// `body` is everything after the header line.
+fn edit_message(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    header: &str,
+    mut cursor: Option<usize>,
+    body: &mut String,
+    draft: Option<&Draft>,
+    options: &MessageOptions,
+) -> Result<String, GczError> {
+    let mut header = header.to_string();
+
+    if !config.inline {
+        let edited = editor::edit(config, &join_message(&header, body))?;
+        let (new_header, new_body) = conventional::split_message(&edited);
+        *body = separated_body(new_body);
+        return restore_prefix(stdout, config, &header, new_header);
+    }
+
+    loop {
+        let autosave = |text: &str| match draft {
+            // a message discarded on abort
+            Some(draft) if text.is_empty() => draft.discard(),
+            Some(draft) => draft.save(text),
+            None => Ok(()),
+        };
+        // the cursor starts at the end of the header rather than of the body
+        let start = cursor.unwrap_or_else(|| header.graphemes(true).count());
+        let text = join_message(&header, body);
+        match input_commit_message(
+            stdout,
+            &text,
+            Some(start),
+            &autosave,
+            &config.keymap,
+            options,
+        )? {
+            LineInput::Accepted(accepted) => {
+                let (new_header, new_body) = conventional::split_message(&accepted);
+                *body = separated_body(new_body);
+                return Ok(new_header.to_string());
+            }
+            LineInput::OpenEditor(current) => {
+                // back to the inline editor with the text as it was
+                let edited = match editor::edit(config, &current) {
+                    Err(err @ GczError::EditorFailed { .. }) => {
+                        eprintln!("{} {}", glyph::get().error, err);
+                        current
+                    }
+                    result => result?,
+                };
+                let (new_header, new_body) = conventional::split_message(&edited);
+                header = restore_prefix(stdout, config, &header, new_header)?;
+                *body = separated_body(new_body);
+                cursor = None;
+            }
+        }
+    }
+}
+
+/// Checks the header that came back from the editor. When it lost the `type(scope): ` that
+/// `before` started with, asks whether to add it back, keep the edit, or choose the type
+/// again, rather than going on with a message that isn't conventional.
+fn restore_prefix(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    before: &str,
+    edited: &str,
+) -> Result<String, GczError> {
+    let prefix = conventional::prefix(before);
+    if prefix.is_empty() || edited.starts_with(prefix.trim_end()) {
+        return Ok(edited.to_string());
+    }
+    let description = conventional::split_prefix(edited).1.trim();
+    let title = format!(
+        "The edited subject no longer starts with `{}`",
+        prefix.trim_end()
+    );
+    let options = [
+        format!("Add `{}` back", prefix.trim_end()),
+        "Keep the edited subject".to_string(),
+        "Choose the type again".to_string(),
+    ];
+    match select_option(stdout, &title, &options, &config.keymap)? {
+        0 => Ok(format!("{} {}", prefix.trim_end(), description)),
+        1 => Ok(edited.to_string()),
+        _ => {
+            let previous = conventional::parse_header(before).map(|h| h.commit_type.to_string());
+            let (_, prefix) = choose_prefix(
+                stdout,
+                config,
+                &mut UntrackedFiles::new(Vec::new()),
+                &git::staged_files().unwrap_or_default(),
+                previous.ok().as_deref(),
+            )?;
+            Ok(format!("{}{}", prefix, description))
+        }
+    }
+}
+
+/// `body` as it should follow the header: after exactly one blank line, so git doesn't read
+/// its first paragraph as part of the subject, with the blank lines between its own
+/// paragraphs kept. A body of only blank lines is dropped.
+fn separated_body(body: &str) -> String {
+    if body.trim().is_empty() {
+        return String::new();
+    }
+    format!("\n{}", body.trim_start_matches(['\n', '\r']))
+}
+
+/// The inverse of `conventional::split_message`.
+fn join_message(header: &str, body: &str) -> String {
+    if body.is_empty() {
+        header.to_string()
+    } else {
+        format!("{}\n{}", header, body)
+    }
+}
+
+/// Commits the index, with the unstaged changes and untracked files stashed meanwhile
+/// when `stash_unstaged` is on.
+fn commit_staged(config: &Config, message: &str, no_verify: bool) -> Result<(), GczError> {
+    if !config.stash_unstaged || !git::stash_unstaged()? {
+        return commit(config, message, None, no_verify);
+    }
+    let result = commit(config, message, None, no_verify);
+    if let Err(err) = git::restore_stash() {
+        eprintln!("Your unstaged changes are still in the stash (`git stash list`)");
+        return Err(err);
+    }
+    result
+}
+
+/// Attaches `note` to the new commit, unless it is missing or blank. The commit is made
+/// by then, so a failure is only a warning.
+fn add_note(note: Option<&str>) {
+    if let Some(note) = note.map(str::trim).filter(|note| !note.is_empty()) {
+        if let Err(err) = git::add_note(note) {
+            eprintln!(
+                "{} Could not attach the note: {}",
+                glyph::get().warning,
+                err
+            );
+        }
+    }
+}
+
+/// `push` and `pull_request`: pushes the new commit, then opens a pull request from it.
+fn publish(config: &Config, message: &str) -> Result<(), GczError> {
+    if config.push || config.pull_request {
+        git::push()?;
+    }
+    if config.pull_request {
+        gh::create_pull_request(message)?;
+    }
+    Ok(())
+}
+
+/// Commits the index, or the alternate `index` file when given. `no_verify` skips the
+/// pre-commit and commit-msg hooks.
+fn commit(
+    config: &Config,
+    message: &str,
+    index: Option<&Path>,
+    no_verify: bool,
+) -> Result<(), GczError> {
+    git::commit_with_spinner(
+        &mut commit_command(config, message, index, no_verify),
+        "Committing",
+    )
+}
+
+/// The `git commit` that [`commit`] runs.
+fn commit_command(
+    config: &Config,
+    message: &str,
+    index: Option<&Path>,
+    no_verify: bool,
+) -> Command {
+    let mut command = Command::new("git");
+    command.args(["commit", "-m", message]);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+    if config.signoff {
+        command.arg("--signoff");
+    }
+    if no_verify || hook::no_verify() {
+        command.arg("--no-verify");
+    }
+    command
+}
+
+/// Returns true when the user chose to stage the remaining changes as well.
+fn warn_partially_staged(
+    stdout: &mut io::Stdout,
+    paths: &[String],
+    keymap: &Keymap,
+) -> Result<bool, GczError> {
+    enable_raw_mode()?;
+    loop {
+        execute!(
+            stdout,
+            cursor::Hide,
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+
+        execute!(
+            stdout,
+            SetForegroundColor(Color::Yellow),
+            Print("Warning: these files also have unstaged changes that will not be committed:"),
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+        for path in paths {
+            execute!(
+                stdout,
+                Print(format!("  {}", path)),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+        execute!(
+            stdout,
+            cursor::MoveToNextLine(1),
+            Print(format!(
+                "{}: stage the remaining changes  {}: continue  {}: abort",
+                keymap.describe(Screen::PartialStaging, Action::StageRest),
+                keymap.describe(Screen::PartialStaging, Action::Confirm),
+                keymap.describe(Screen::PartialStaging, Action::Abort)
+            )),
+            cursor::MoveToNextLine(1)
+        )?;
+        stdout.flush()?;
+
+        if let Event::Key(key_event) = event::read()? {
+            match keymap.action(Screen::PartialStaging, &key_event) {
+                Some(Action::Abort) => return Err(GczError::UserInterrupt),
+                Some(Action::StageRest) => break finalize(true, stdout),
+                Some(Action::Confirm) => break finalize(false, stdout),
+                Some(Action::Help) => show_help(stdout, keymap, &[Screen::PartialStaging])?,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Untracked files shown below the type list, so new files aren't silently left out of the commit.
+struct UntrackedFiles {
+    paths: Vec<String>,
+    checked: Vec<bool>,
+    cursor: usize,
+    expanded: bool,
+}
+
+impl UntrackedFiles {
+    fn new(paths: Vec<String>) -> Self {
+        let checked = vec![false; paths.len()];
+        UntrackedFiles {
+            paths,
+            checked,
+            cursor: 0,
+            expanded: false,
+        }
+    }
+
+    fn toggle(&mut self) {
+        if let Some(checked) = self.checked.get_mut(self.cursor) {
+            *checked = !*checked;
+        }
+    }
+
+    /// Removes the checked paths from the list and returns them.
+    fn take_checked(&mut self) -> Vec<String> {
+        let (taken, kept): (Vec<_>, Vec<_>) = self
+            .paths
+            .drain(..)
+            .zip(self.checked.drain(..))
+            .partition(|(_, checked)| *checked);
+
+        self.paths = kept.into_iter().map(|(path, _)| path).collect();
+        self.checked = vec![false; self.paths.len()];
+        self.cursor = 0;
+        self.expanded = self.expanded && !self.paths.is_empty();
+
+        taken.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+/// A plain vertical menu; returns the index of the chosen option.
+fn select_option(
+    stdout: &mut io::Stdout,
+    title: &str,
+    options: &[String],
+    keymap: &Keymap,
+) -> Result<usize, GczError> {
+    let mut selected_index = 0;
+
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    loop {
+        execute!(
+            stdout,
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print(title),
+            cursor::MoveToNextLine(1)
+        )?;
+        for (i, option) in options.iter().enumerate() {
+            if i == selected_index {
+                execute!(
+                    stdout,
+                    SetForegroundColor(Color::Green),
+                    Print(format!("{} {}", glyph::get().pointer, option)),
+                    SetForegroundColor(Color::Reset),
+                    cursor::MoveToNextLine(1)
+                )?;
+            } else {
+                execute!(
+                    stdout,
+                    Print(format!("  {}", option)),
+                    cursor::MoveToNextLine(1)
+                )?;
+            }
+        }
+        draw_status_line(stdout, keymap, Screen::Menu)?;
+        stdout.flush()?;
+
+        if let Event::Key(key_event) = event::read()? {
+            match keymap.action(Screen::Menu, &key_event) {
+                Some(Action::Abort) => return Err(GczError::UserInterrupt),
+                Some(Action::Up) | Some(Action::Left) => {
+                    selected_index = (selected_index + options.len() - 1) % options.len()
+                }
+                Some(Action::Down) => selected_index = (selected_index + 1) % options.len(),
+                Some(Action::Confirm) => break finalize(selected_index, stdout),
+                Some(Action::Help) => show_help(stdout, keymap, &[Screen::Menu])?,
+                _ => continue,
+            }
+        }
+    }
+}
+
+fn select_commit_type(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    untracked: &mut UntrackedFiles,
+    preselected: Option<&str>,
+) -> Result<String, GczError> {
+    enable_raw_mode()
+        .map_err(GczError::from)
+        .and_then(|_| execute!(stdout, cursor::Hide, Clear(ClearType::All)).map_err(GczError::from))
+        .and_then(|_| handle_commit_type(stdout, config, untracked, preselected))
+        .and_then(|input| finalize(input, stdout))
+}
+
+fn render_untracked_files(
+    stdout: &mut io::Stdout,
+    untracked: &UntrackedFiles,
+    keymap: &Keymap,
+) -> Result<(), GczError> {
+    if untracked.paths.is_empty() {
+        return Ok(());
+    }
+
+    execute!(stdout, cursor::MoveToNextLine(1))?;
+
+    if !untracked.expanded {
+        execute!(
+            stdout,
+            SetForegroundColor(Color::Yellow),
+            Print(format!(
+                "{} untracked file(s) will not be committed ({}: show)",
+                untracked.paths.len(),
+                keymap.describe(Screen::TypeSelect, Action::ToggleUntracked)
+            )),
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+        return Ok(());
+    }
+
+    execute!(
+        stdout,
+        SetForegroundColor(Color::Yellow),
+        Print(format!(
+            "Untracked files ({}: hide, {}: select, {}: add selected)",
+            keymap.describe(Screen::Untracked, Action::ToggleUntracked),
+            keymap.describe(Screen::Untracked, Action::ToggleFile),
+            keymap.describe(Screen::Untracked, Action::AddFiles)
+        )),
+        SetForegroundColor(Color::Reset),
+        cursor::MoveToNextLine(1)
+    )?;
+
+    for (i, path) in untracked.paths.iter().enumerate() {
+        let mark = if untracked.checked[i] { "x" } else { " " };
+        if i == untracked.cursor {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print(format!("{} [{}] {}", glyph::get().pointer, mark, path)),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+        } else {
+            execute!(
+                stdout,
+                Print(format!("  [{}] {}", mark, path)),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_untracked_files(untracked: &mut UntrackedFiles, action: Action) -> Result<(), GczError> {
+    match action {
+        Action::Up if untracked.cursor > 0 => untracked.cursor -= 1,
+        Action::Down if untracked.cursor + 1 < untracked.paths.len() => untracked.cursor += 1,
+        Action::ToggleFile => untracked.toggle(),
+        Action::AddFiles => {
+            let paths = untracked.take_checked();
+            if !paths.is_empty() {
+                git::stage_files(&paths)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_commit_type(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    untracked: &mut UntrackedFiles,
+    preselected: Option<&str>,
+) -> Result<String, GczError> {
+    let (aliases, keymap) = (&config.type_aliases, &config.keymap);
+    let mut favorites = config.favorite_types.clone();
+    let ordered = types_by_use(config);
+    let mut types = favorites_first(&ordered, &favorites);
+    let mut selected = preselected
+        .and_then(|t| types.iter().position(|c| c == t))
+        .or((!types.is_empty()).then_some(0));
+    let mut input = String::new();
+    let mut is_selected = false;
+    let mut folded: Vec<String> = types.iter().map(|t| text::fold(t)).collect();
+
+    loop {
+        if is_selected {
+            execute!(
+                stdout,
+                Clear(ClearType::All),
+                cursor::MoveTo(0, 0),
+                Print("Selected commit type: "),
+                SetForegroundColor(Color::Cyan),
+                Print(&input),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+            break Ok(input);
+        }
+
+        let filtered_types: Vec<(usize, &str)> =
+            filter_type_by_input(&input, &types, &folded, aliases);
+        let pinned = types.iter().filter(|t| favorites.contains(t)).count();
+        // Enter takes what was typed when nothing matches it
+        let custom_type =
+            filtered_types.is_empty() && config.custom_types && conventional::is_type_name(&input);
+
+        if term::should_redraw()? {
+            execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+            draw_progress(stdout, Screen::TypeSelect)?;
+            execute!(
+                stdout,
+                Print(format!("Select a commit type: {}", &input)),
+                cursor::MoveToNextLine(1)
+            )?;
+
+            let filter = text::fold(&input);
+            if config.compact {
+                render_compact_types(stdout, &filtered_types, selected, &filter)?;
+            } else {
+                // the emojis line up in a column after the longest name
+                let name_width = filtered_types
+                    .iter()
+                    .map(|(_, commit_type)| text::width(commit_type))
+                    .max()
+                    .unwrap_or(0);
+                for (i, &(index, commit_type)) in filtered_types.iter().enumerate() {
+                    let heading = match filtered_types.get(i.wrapping_sub(1)) {
+                        None if index < pinned => Some("Favorites"),
+                        Some(&(previous, _)) if previous < pinned && index >= pinned => {
+                            Some("Other types")
+                        }
+                        _ => None,
+                    };
+                    if let Some(heading) = heading {
+                        execute!(
+                            stdout,
+                            SetForegroundColor(Color::DarkGrey),
+                            Print(heading),
+                            SetForegroundColor(Color::Reset),
+                            cursor::MoveToNextLine(1)
+                        )?;
+                    }
+                    let color = if selected == Some(i) {
+                        execute!(
+                            stdout,
+                            SetForegroundColor(Color::Green),
+                            Print(format!("{} ", glyph::get().pointer)),
+                        )?;
+                        Color::Green
+                    } else {
+                        execute!(stdout, Print("  "))?;
+                        Color::Reset
+                    };
+                    print_match(stdout, commit_type, &filter, color)?;
+                    match emoji_for(config, commit_type).filter(|_| emoji::enabled(config)) {
+                        Some(emoji) if config.emoji_column => execute!(
+                            stdout,
+                            cursor::MoveToColumn((name_width + 3) as u16),
+                            Print(emoji),
+                            cursor::MoveToNextLine(1)
+                        )?,
+                        Some(emoji) => execute!(
+                            stdout,
+                            Print(" ".repeat(name_width - text::width(commit_type) + 1)),
+                            Print(emoji),
+                            cursor::MoveToNextLine(1)
+                        )?,
+                        None => execute!(stdout, cursor::MoveToNextLine(1))?,
+                    }
+                }
+            }
+            if filtered_types.is_empty() {
+                let hint = if custom_type {
+                    format!(
+                        "No matches; {}: use `{}` as a custom type",
+                        keymap.describe(Screen::TypeSelect, Action::Confirm),
+                        input
+                    )
+                } else {
+                    format!(
+                        "No type matches `{}` ({}: clear the filter)",
+                        input,
+                        keymap.describe(Screen::TypeSelect, Action::Clear)
+                    )
+                };
+                execute!(
+                    stdout,
+                    SetForegroundColor(Color::Yellow),
+                    Print(hint),
+                    SetForegroundColor(Color::Reset),
+                    cursor::MoveToNextLine(1)
+                )?;
+            }
+            render_untracked_files(stdout, untracked, keymap)?;
+            let screen = if untracked.expanded {
+                Screen::Untracked
+            } else {
+                Screen::TypeSelect
+            };
+            draw_status_line(stdout, keymap, screen)?;
+            stdout.flush()?;
+        }
+
+        if let Event::Key(key_event) = event::read()? {
+            let highlighted = selected
+                .and_then(|i| filtered_types.get(i))
+                .map(|&(_, t)| t.to_string());
+            if untracked.expanded {
+                match keymap.action(Screen::Untracked, &key_event) {
+                    Some(Action::ToggleUntracked) => untracked.expanded = false,
+                    Some(action) => handle_untracked_files(untracked, action)?,
+                    None => match keymap.action(Screen::TypeSelect, &key_event) {
+                        Some(Action::Abort) => return Err(GczError::UserInterrupt),
+                        Some(Action::Help) => {
+                            show_help(stdout, keymap, &[Screen::Untracked, Screen::TypeSelect])?
+                        }
+                        Some(Action::Confirm) => {
+                            if let Some(highlighted) = highlighted {
+                                input = highlighted;
+                                is_selected = true;
+                            } else if custom_type {
+                                is_selected = true;
+                            }
+                        }
+                        _ => {}
+                    },
+                }
+                continue;
+            }
+
+            match keymap.action(Screen::TypeSelect, &key_event) {
+                Some(Action::Abort) => return Err(GczError::UserInterrupt),
+                Some(Action::Help) => {
+                    let mut screens = vec![Screen::TypeSelect];
+                    if !untracked.paths.is_empty() {
+                        screens.push(Screen::Untracked);
+                    }
+                    show_help(stdout, keymap, &screens)?;
+                }
+                Some(Action::ToggleUntracked) if !untracked.paths.is_empty() => {
+                    untracked.expanded = true;
+                }
+                Some(Action::ToggleEmoji) => emoji::toggle(config)?,
+                Some(Action::Pin) => {
+                    let Some(highlighted) = highlighted else {
+                        continue;
+                    };
+                    match favorites.iter().position(|f| *f == highlighted) {
+                        Some(i) => {
+                            favorites.remove(i);
+                        }
+                        None => favorites.push(highlighted.clone()),
+                    }
+                    config::remember("favorite_types", favorites.iter().collect())?;
+                    types = favorites_first(&ordered, &favorites);
+                    folded = types.iter().map(|t| text::fold(t)).collect();
+                    selected = position_in_filter(
+                        &input,
+                        &types,
+                        &folded,
+                        aliases,
+                        Some(highlighted.as_str()),
+                    );
+                }
+                Some(Action::Up) => selected = step(selected, filtered_types.len(), false),
+                Some(Action::Down) | Some(Action::Right) => {
+                    selected = step(selected, filtered_types.len(), true)
+                }
+                Some(Action::Confirm) => {
+                    if let Some(highlighted) = highlighted {
+                        input = highlighted;
+                        is_selected = true;
+                    } else if custom_type {
+                        is_selected = true;
+                    }
+                }
+                Some(Action::DeleteBack) => {
+                    input.pop();
+                    selected = position_in_filter(
+                        &input,
+                        &types,
+                        &folded,
+                        aliases,
+                        highlighted.as_deref(),
+                    );
+                }
+                Some(Action::Clear) => {
+                    input.clear();
+                    selected = position_in_filter(
+                        &input,
+                        &types,
+                        &folded,
+                        aliases,
+                        highlighted.as_deref(),
+                    );
+                }
+                _ => match key_event.code {
+                    KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        input.push(c);
+                        selected = position_in_filter(
+                            &input,
+                            &types,
+                            &folded,
+                            aliases,
+                            highlighted.as_deref(),
+                        );
+                        if let Some(commit_type) = complete_alias(&input, &types, aliases) {
+                            input = commit_type.to_string();
+                            is_selected = true;
+                        }
+                    }
+                    _ => continue,
+                },
+            }
+        }
+    }
+}
+
+/// The types, the most used first with `sort_types_by_use`.
+fn types_by_use(config: &Config) -> Vec<String> {
+    if config.sort_types_by_use {
+        usage::Usage::load().sort(&config.types)
+    } else {
+        config.types.clone()
+    }
+}
+
+/// `types` with the favorites among them moved to the front, in the order they were pinned.
+fn favorites_first(types: &[String], favorites: &[String]) -> Vec<String> {
+    let (mut first, rest): (Vec<&String>, Vec<&String>) =
+        types.iter().partition(|t| favorites.contains(t));
+    first.sort_by_key(|t| favorites.iter().position(|f| f == *t));
+    first.into_iter().chain(rest).cloned().collect()
+}
+
+fn alias_target<'a>(
+    input: &str,
+    types: &'a [String],
+    aliases: &[(String, String)],
+) -> Option<(usize, &'a str)> {
+    let (_, commit_type) = aliases.iter().find(|(alias, _)| alias == input)?;
+    types
+        .iter()
+        .position(|t| t == commit_type)
+        .map(|i| (i, types[i].as_str()))
+}
+
+/// The type to pick right away: `input` is an alias and no longer alias starts with it.
+fn complete_alias<'a>(
+    input: &str,
+    types: &'a [String],
+    aliases: &[(String, String)],
+) -> Option<&'a str> {
+    let ambiguous = aliases
+        .iter()
+        .any(|(alias, _)| alias.len() > input.len() && alias.starts_with(input));
+    if ambiguous {
+        return None;
+    }
+    alias_target(input, types, aliases).map(|(_, commit_type)| commit_type)
+}
+
+/// Prints `name` in `color`, with the part matching the folded `filter` highlighted.
+fn print_match(
+    stdout: &mut io::Stdout,
+    name: &str,
+    filter: &str,
+    color: Color,
+) -> Result<(), GczError> {
+    let matched = text::find_folded(name, filter).unwrap_or(0..0);
+    execute!(
+        stdout,
+        SetForegroundColor(color),
+        Print(&name[..matched.start]),
+        SetForegroundColor(Color::Yellow),
+        Print(&name[matched.clone()]),
+        SetForegroundColor(color),
+        Print(&name[matched.end..]),
+        SetForegroundColor(Color::Reset)
+    )?;
+    Ok(())
+}
+
+/// Draws the types on a single line, scrolled so the selected one stays visible.
+fn render_compact_types(
+    stdout: &mut io::Stdout,
+    types: &[(usize, &str)],
+    selected: Option<usize>,
+    filter: &str,
+) -> Result<(), GczError> {
+    // some embedded terminals report a zero size
+    let width = match terminal::size()? {
+        (0, _) => 80,
+        (width, _) => width,
+    };
+    let widths: Vec<usize> = types.iter().map(|(_, t)| text::width(t)).collect();
+    let (start, end) = compact_window(&widths, selected.unwrap_or(0), width as usize);
+
+    if start > 0 {
+        execute!(stdout, Print(format!("{} ", glyph::get().scroll_left)))?;
+    }
+    for (i, &(_, commit_type)) in types.iter().enumerate().take(end).skip(start) {
+        if i > start {
+            execute!(stdout, Print(" "))?;
+        }
+        if selected == Some(i) {
+            execute!(stdout, SetForegroundColor(Color::Green), Print("["))?;
+            print_match(stdout, commit_type, filter, Color::Green)?;
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print("]"),
+                SetForegroundColor(Color::Reset)
+            )?;
+        } else {
+            print_match(stdout, commit_type, filter, Color::Reset)?;
+        }
+    }
+    if end < types.len() {
+        execute!(stdout, Print(format!(" {}", glyph::get().scroll_right)))?;
+    }
+    execute!(stdout, cursor::MoveToNextLine(1))?;
+    Ok(())
+}
+
+/// The range of items that fit in `width` columns around `selected`, leaving room for
+/// the brackets around it and the `…` markers.
+fn compact_window(widths: &[usize], selected: usize, width: usize) -> (usize, usize) {
+    if widths.is_empty() {
+        return (0, 0);
+    }
+    let available = width.saturating_sub(6);
+    let span = |start: usize, end: usize| -> usize {
+        widths[start..end].iter().sum::<usize>() + (end - start).saturating_sub(1)
+    };
+
+    let mut start = 0;
+    while start < selected && span(start, selected + 1) > available {
+        start += 1;
+    }
+    let mut end = selected + 1;
+    while end < widths.len() && span(start, end + 1) <= available {
+        end += 1;
+    }
+    (start, end)
+}
+
+/// The types whose name contains `input`, ignoring case and diacritics, or only the aliased
+/// type when `input` is an alias. `folded` holds the names of `types` through `text::fold`.
+fn filter_type_by_input<'a>(
+    input: &str,
+    types: &'a [String],
+    folded: &[String],
+    aliases: &[(String, String)],
+) -> Vec<(usize, &'a str)> {
+    if let Some(aliased) = alias_target(input, types, aliases) {
+        return vec![aliased];
+    }
+    let input = text::fold(input);
+    folded
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| name.contains(&input))
+        .map(|(i, _)| (i, types[i].as_str()))
+        .collect()
+}
+
+/// Where `highlighted` is among the types matching `input`, the top when it no longer
+/// matches, or `None` when nothing does.
+fn position_in_filter(
+    input: &str,
+    types: &[String],
+    folded: &[String],
+    aliases: &[(String, String)],
+    highlighted: Option<&str>,
+) -> Option<usize> {
+    let matches = filter_type_by_input(input, types, folded, aliases);
+    matches
+        .iter()
+        .position(|&(_, t)| Some(t) == highlighted)
+        .or((!matches.is_empty()).then_some(0))
+}
+
+/// The selection one item further or back in a list of `len`, wrapping around at the ends.
+fn step(selected: Option<usize>, len: usize, forward: bool) -> Option<usize> {
+    let i = selected.filter(|&i| i < len)?;
+    Some(if forward {
+        (i + 1) % len
+    } else {
+        (i + len - 1) % len
+    })
+}
+
+fn finalize<T>(input: T, stdout: &mut io::Stdout) -> Result<T, GczError> {
+    disable_raw_mode()?;
+    execute!(stdout, cursor::Show, cursor::MoveToNextLine(1))?;
+    Ok(input)
+}
+
+/// Prints `question` and reads a line of free text.
+fn ask_line(stdout: &mut io::Stdout, question: &str, keymap: &Keymap) -> Result<String, GczError> {
+    execute!(stdout, Print(question), cursor::MoveToNextLine(1))?;
+    match input_commit_message(
+        stdout,
+        "",
+        None,
+        &|_| Ok(()),
+        keymap,
+        &MessageOptions::default(),
+    )? {
+        LineInput::Accepted(text) | LineInput::OpenEditor(text) => Ok(text),
+    }
+}
+
+enum LineInput {
+    Accepted(String),
+    /// The user asked to continue in the external editor with this text.
+    OpenEditor(String),
+}
+
+/// Waits for a key in raw mode: `y` answers yes, anything else no.
+fn read_yes() -> io::Result<bool> {
+    loop {
+        if let Event::Key(key_event) = event::read()? {
+            return Ok(matches!(key_event.code, KeyCode::Char('y' | 'Y')));
+        }
+    }
+}
+
+/// What the inline editor does with the message besides editing it.
+#[derive(Default)]
+struct MessageOptions<'a> {
+    /// Underlines the words it doesn't know.
+    speller: Option<&'a spell::Speller>,
+    /// Ask before aborting throws away what was typed, and discard the draft when it does.
+    confirm_abort: bool,
+    /// Subjects that Up and Down recall into an untouched message, newest first.
+    history: &'a [String],
+    /// Words the complete key offers for the word before the cursor.
+    vocabulary: &'a [String],
+    /// Show the header as it will be committed, with its length, below the text.
+    preview: bool,
+}
+
+/// Edits the header line starting from `text`. `cursor` is a grapheme offset into
+/// `text`; the cursor starts at the end when it is `None`. Aborting after `text` was changed
+/// saves it.
+fn input_commit_message(
+    stdout: &mut io::Stdout,
+    text: &str,
+    cursor: Option<usize>,
+    autosave: &dyn Fn(&str) -> io::Result<()>,
+    keymap: &Keymap,
+    options: &MessageOptions,
+) -> Result<LineInput, GczError> {
+    let MessageOptions {
+        speller,
+        confirm_abort,
+        history,
+        vocabulary,
+        preview,
+    } = *options;
+    let mut message = TextBuffer::new(text, cursor);
+    // the history entry shown, while the message is as it was recalled
+    let mut recalled: Option<usize> = None;
+    // the words offered for the word before the cursor, and which of them was put in,
+    // while the complete key is pressed again and again
+    let mut completion: Option<(Vec<&str>, usize)> = None;
+    let mut view = EditorView::default();
+    let mut unsaved_edits = 0;
+    let status = if term::status_line() {
+        keymap.status(Screen::Message)
+    } else {
+        String::new()
+    };
+
+    enable_raw_mode()?;
+    draw_progress(stdout, Screen::Message)?;
+    loop {
+        if term::should_redraw()? {
+            let marks = match speller {
+                // not the word being typed, which is usually unfinished
+                Some(speller) => speller
+                    .misspelled(message.as_str())
+                    .into_iter()
+                    .filter(|mark| mark.end != message.cursor_byte())
+                    .collect(),
+                None => Vec::new(),
+            };
+            let status = match &completion {
+                Some((words, _)) => words.join(glyph::get().separator),
+                None => status.clone(),
+            };
+            let preview = if preview {
+                header_preview(message.as_str())
+            } else {
+                String::new()
+            };
+            view.draw(
+                stdout,
+                &message,
+                &marks,
+                &[(&preview, Color::DarkGrey), (&status, Color::DarkGrey)],
+            )?;
+        }
+
+        if let Event::Key(key_event) = event::read()? {
+            let action = keymap.action(Screen::Message, &key_event);
+            let completed = completion.take();
+            match (action, key_event.code) {
+                (Some(Action::Abort), _) => {
+                    let changed = message.as_str() != text;
+                    if confirm_abort && changed {
+                        let question = "Discard the commit message? (y/n)";
+                        view.draw(stdout, &message, &[], &[(question, Color::Yellow)])?;
+                        if !read_yes()? {
+                            continue;
+                        }
+                    }
+                    if changed {
+                        autosave(message.as_str())?;
+                    }
+                    if confirm_abort && changed {
+                        autosave("")?;
+                    }
+                    disable_raw_mode()?;
+                    view.leave(stdout, 1)?;
+                    return Err(GczError::UserInterrupt);
+                }
+                (Some(Action::Help), _) => {
+                    show_help(stdout, keymap, &[Screen::Message])?;
+                    execute!(stdout, cursor::MoveTo(0, 0))?;
+                    draw_progress(stdout, Screen::Message)?;
+                    view = EditorView::default();
+                    continue;
+                }
+                (Some(Action::Confirm), _) => {
+                    autosave(message.as_str())?;
+                    disable_raw_mode()?;
+                    view.leave(stdout, 2)?;
+                    return Ok(LineInput::Accepted(message.into_string()));
+                }
+                (Some(Action::OpenEditor), _) => {
+                    disable_raw_mode()?;
+                    view.leave(stdout, 1)?;
+                    return Ok(LineInput::OpenEditor(message.into_string()));
+                }
+                (Some(Action::Complete), _) => {
+                    let (words, index) = match completed {
+                        Some((words, index)) => {
+                            let next = (index + 1) % words.len();
+                            (words, next)
+                        }
+                        None => {
+                            let end = message.cursor_byte();
+                            let start = complete::word_start(message.as_str(), end);
+                            let words =
+                                complete::matches(vocabulary, &message.as_str()[start..end]);
+                            if words.is_empty() {
+                                continue;
+                            }
+                            (words, 0)
+                        }
+                    };
+                    let end = message.cursor_byte();
+                    let start = complete::word_start(message.as_str(), end);
+                    let typed = message.as_str()[start..end].graphemes(true).count();
+                    for _ in 0..typed {
+                        message.delete_back();
+                    }
+                    message.insert(words[index]);
+                    completion = Some((words, index));
+                }
+                (Some(Action::NewLine), _) => message.insert("\n"),
+                (Some(Action::DeleteBack), _) => message.delete_back(),
+                (Some(Action::DeleteForward), _) => message.delete_forward(),
+                (Some(Action::DeleteWord), _) => message.delete_word(),
+                (Some(Action::Up), _)
+                    if recalled.map_or(0, |i| i + 1) < history.len()
+                        && message.as_str() == recalled.map_or(text, |i| &history[i]) =>
+                {
+                    let i = recalled.map_or(0, |i| i + 1);
+                    message = TextBuffer::new(&history[i], None);
+                    recalled = Some(i);
+                }
+                (Some(Action::Down), _)
+                    if recalled.is_some_and(|i| message.as_str() == history[i]) =>
+                {
+                    recalled = recalled.and_then(|i| i.checked_sub(1));
+                    message = TextBuffer::new(recalled.map_or(text, |i| &history[i]), None);
+                }
+                (Some(Action::Up), _) => message.move_rows(-1, terminal_size()?.0),
+                (Some(Action::Down), _) => message.move_rows(1, terminal_size()?.0),
+                (Some(Action::Left), _) => message.move_left(),
+                (Some(Action::Right), _) => message.move_right(),
+                (Some(Action::Home), _) => message.move_home(),
+                (Some(Action::End), _) => message.move_end(),
+                (None, KeyCode::Char(c))
+                    if !key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    message.insert(c.encode_utf8(&mut [0; 4]));
+                }
+                _ => continue,
+            }
+
+            if matches!(
+                action,
+                None | Some(Action::DeleteBack)
+                    | Some(Action::DeleteForward)
+                    | Some(Action::Complete)
+                    | Some(Action::DeleteWord)
+                    | Some(Action::NewLine)
+            ) {
+                unsaved_edits += 1;
+            }
+            if unsaved_edits >= AUTOSAVE_EVERY {
+                autosave(message.as_str())?;
+                unsaved_edits = 0;
+            }
+        }
+    }
+}
+
+fn terminal_size() -> io::Result<(usize, usize)> {
+    // some embedded terminals report a zero size
+    Ok(match terminal::size()? {
+        (0, _) | (_, 0) => (80, 24),
+        (width, height) => (width as usize, height as usize),
+    })
+}
+
+/// The first line of `message` as it will be committed, e.g. `feat(api): add login · 20
+/// characters`; empty until there is a description.
+fn header_preview(message: &str) -> String {
+    let header = text::tidy_whitespace(message.lines().next().unwrap_or(""), false);
+    if header.is_empty() || conventional::prefix(&header) == header {
+        return String::new();
+    }
+    let length = header.chars().count();
+    let unit = if length == 1 {
+        "character"
+    } else {
+        "characters"
+    };
+    format!("{}{}{} {}", header, glyph::get().separator, length, unit)
+}
+
+/// Where the inline editor drew the text last time, so the next draw can start over
+/// from its first row.
+#[derive(Default)]
+struct EditorView {
+    /// The first row of the text on screen; long messages scroll to keep the cursor shown.
+    top: usize,
+    /// How many rows are on screen, and which of them the terminal cursor is on.
+    rows: usize,
+    cursor_row: usize,
+}
+
+impl EditorView {
+    /// Draws `message` wrapped to the terminal width, replacing what was drawn before, with
+    /// the byte ranges in `marks` underlined and the non-empty `lines` below it in their colors.
+    fn draw(
+        &mut self,
+        stdout: &mut io::Stdout,
+        message: &TextBuffer,
+        marks: &[Range<usize>],
+        lines: &[(&str, Color)],
+    ) -> io::Result<()> {
+        let (width, height) = terminal_size()?;
+        let (rows, (row, column)) = message.wrap(width);
+
+        let lines: Vec<_> = lines.iter().filter(|(line, _)| !line.is_empty()).collect();
+        let below = lines.len();
+        let visible = height.saturating_sub(1 + below).max(1);
+        self.top = self.top.min(row).max((row + 1).saturating_sub(visible));
+        let shown = &rows[self.top..rows.len().min(self.top + visible)];
+
+        if self.cursor_row > 0 {
+            execute!(stdout, cursor::MoveUp(self.cursor_row as u16))?;
+        }
+        execute!(
+            stdout,
+            cursor::MoveToColumn(0),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        for (i, row) in shown.iter().enumerate() {
+            // `\r\n` rather than cursor moves, so a message at the bottom scrolls the screen
+            if i > 0 {
+                execute!(stdout, Print("\r\n"))?;
+            }
+            spell::print_marked(stdout, row, message.offset_of(row), marks, Color::Reset)?;
+        }
+        for (line, color) in lines {
+            execute!(
+                stdout,
+                Print("\r\n"),
+                SetForegroundColor(*color),
+                Print(text::clip(line, width)),
+                SetForegroundColor(Color::Reset)
+            )?;
+        }
+        self.rows = shown.len();
+        self.cursor_row = row - self.top;
+        if self.rows - 1 + below > self.cursor_row {
+            execute!(
+                stdout,
+                cursor::MoveUp((self.rows - 1 + below - self.cursor_row) as u16)
+            )?;
+        }
+        execute!(stdout, cursor::MoveToColumn(column as u16))?;
+        stdout.flush()
+    }
+
+    /// Moves `lines` lines below the text, clearing the lines drawn under it.
+    fn leave(&self, stdout: &mut io::Stdout, lines: u16) -> io::Result<()> {
+        if self.rows > self.cursor_row + 1 {
+            execute!(
+                stdout,
+                cursor::MoveDown((self.rows - 1 - self.cursor_row) as u16)
+            )?;
+        }
+        execute!(
+            stdout,
+            cursor::MoveToNextLine(1),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        if lines > 1 {
+            execute!(stdout, cursor::MoveToNextLine(lines - 1))?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists the keys of `screens` over the current screen until a key is pressed.
+/// The caller redraws its screen afterwards.
+fn show_help(stdout: &mut io::Stdout, keymap: &Keymap, screens: &[Screen]) -> Result<(), GczError> {
+    execute!(
+        stdout,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        Print("Keys"),
+        cursor::MoveToNextLine(1)
+    )?;
+    for screen in screens {
+        execute!(
+            stdout,
+            cursor::MoveToNextLine(1),
+            SetForegroundColor(Color::Yellow),
+            Print(screen.title()),
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+        for (keys, description) in keymap.help(*screen) {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Cyan),
+                Print(format!("  {:<20}", keys)),
+                SetForegroundColor(Color::Reset),
+                Print(description),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+    }
+    execute!(
+        stdout,
+        cursor::MoveToNextLine(1),
+        Print("Press any key to go back"),
+        cursor::MoveToNextLine(1)
+    )?;
+    stdout.flush()?;
+
+    loop {
+        if let Event::Key(_) = event::read()? {
+            break;
+        }
+    }
+    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    Ok(())
+}
+
+/// Starts a step of the wizard with where it is in the flow, e.g. `Step 2/4 — Scope`;
+/// nothing outside the wizard.
+fn draw_progress(stdout: &mut io::Stdout, screen: Screen) -> io::Result<()> {
+    match steps::breadcrumb(screen) {
+        Some(breadcrumb) => execute!(
+            stdout,
+            SetForegroundColor(Color::DarkGrey),
+            Print(breadcrumb),
+            SetForegroundColor(Color::Reset),
+            Print("\r\n")
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Draws the main keys of `screen` on the bottom row of a full-screen step, leaving the
+/// cursor where it was.
+fn draw_status_line(stdout: &mut io::Stdout, keymap: &Keymap, screen: Screen) -> io::Result<()> {
+    let status = keymap.status(screen);
+    if !term::status_line() || status.is_empty() {
+        return Ok(());
+    }
+    let (width, height) = terminal_size()?;
+    execute!(
+        stdout,
+        cursor::SavePosition,
+        cursor::MoveTo(0, height.saturating_sub(1) as u16),
+        Clear(ClearType::CurrentLine),
+        SetForegroundColor(Color::DarkGrey),
+        Print(text::clip(&status, width)),
+        SetForegroundColor(Color::Reset),
+        cursor::RestorePosition
+    )
+}
+
+enum Confirmation {
+    Commit,
+    Edit,
+    AddBody,
+    Fold,
+}
+
+/// Shows the final message with lint findings. Committing is refused while any finding is an error.
+fn confirm_commit(
+    stdout: &mut io::Stdout,
+    message: &str,
+    findings: &[lint::Finding],
+    can_add_body: bool,
+    fold: Option<&str>,
+    speller: Option<&spell::Speller>,
+    keymap: &Keymap,
+) -> Result<Confirmation, GczError> {
+    let marks = speller.map_or_else(Vec::new, |speller| speller.misspelled(message));
+    let blocked = findings
+        .iter()
+        .any(|f| f.severity == config::Severity::Error);
+
+    enable_raw_mode()?;
+    loop {
+        execute!(
+            stdout,
+            cursor::Hide,
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        draw_progress(stdout, Screen::Confirm)?;
+        execute!(stdout, Print("Commit message:"), cursor::MoveToNextLine(1))?;
+        let mut line_start = 0;
+        for line in message.split_inclusive('\n') {
+            execute!(stdout, SetForegroundColor(Color::Cyan), Print("  "))?;
+            let text = line.trim_end_matches(['\n', '\r']);
+            spell::print_marked(stdout, text, line_start, &marks, Color::Cyan)?;
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+            line_start += line.len();
+        }
+
+        if !findings.is_empty() {
+            execute!(stdout, cursor::MoveToNextLine(1))?;
+        }
+        for finding in findings {
+            let (color, mark) = match finding.severity {
+                config::Severity::Error => (Color::Red, glyph::get().error),
+                config::Severity::Warning => (Color::Yellow, glyph::get().warning),
+            };
+            execute!(
+                stdout,
+                SetForegroundColor(color),
+                Print(format!("{} {}", mark, finding.message)),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+
+        let mut keys = Vec::new();
+        if !blocked {
+            keys.push(format!(
+                "{}: commit",
+                keymap.describe(Screen::Confirm, Action::Confirm)
+            ));
+        }
+        keys.push(format!(
+            "{}: edit",
+            keymap.describe(Screen::Confirm, Action::Edit)
+        ));
+        if can_add_body {
+            keys.push(format!(
+                "{}: list the changes in the body",
+                keymap.describe(Screen::Confirm, Action::AddBody)
+            ));
+        }
+        if let Some(fold) = fold {
+            keys.push(format!(
+                "{}: {}",
+                keymap.describe(Screen::Confirm, Action::Amend),
+                fold
+            ));
+        }
+        keys.push(format!(
+            "{}: abort",
+            keymap.describe(Screen::Confirm, Action::Abort)
+        ));
+        keys.push(format!(
+            "{}: help",
+            keymap.describe(Screen::Confirm, Action::Help)
+        ));
+        execute!(
+            stdout,
+            cursor::MoveToNextLine(1),
+            Print(keys.join("  ")),
+            cursor::MoveToNextLine(1)
+        )?;
+        stdout.flush()?;
+
+        if let Event::Key(key_event) = event::read()? {
+            match keymap.action(Screen::Confirm, &key_event) {
+                Some(Action::Abort) => return Err(GczError::UserInterrupt),
+                Some(Action::Confirm) if !blocked => break finalize(Confirmation::Commit, stdout),
+                Some(Action::Edit) => {
+                    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+                    break finalize(Confirmation::Edit, stdout);
+                }
+                Some(Action::AddBody) if can_add_body => {
+                    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+                    break finalize(Confirmation::AddBody, stdout);
+                }
+                Some(Action::Amend) if fold.is_some() => {
+                    break finalize(Confirmation::Fold, stdout)
+                }
+                Some(Action::Help) => show_help(stdout, keymap, &[Screen::Confirm])?,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_filter() {
+        let types = Config::default().types;
+        let folded: Vec<String> = types.iter().map(|t| text::fold(t)).collect();
+        let result = filter_type_by_input("f", &types, &folded, &[]);
+        assert_eq!(
+            result,
+            vec![(0, "feat"), (1, "fix"), (4, "refactor"), (5, "perf")]
+        );
+        assert_eq!(
+            filter_type_by_input("FÉAT", &types, &folded, &[]),
+            vec![(0, "feat")]
+        );
+    }
+
+    #[test]
+    fn should_list_favorite_types_first() {
+        let types: Vec<String> = ["feat", "fix", "docs", "test"].map(String::from).into();
+        let favorites: Vec<String> = ["test", "wip", "fix"].map(String::from).into();
+        assert_eq!(
+            favorites_first(&types, &favorites),
+            ["test", "fix", "feat", "docs"]
+        );
+    }
+
+    #[test]
+    fn should_keep_the_highlighted_type_while_filtering() {
+        let types = Config::default().types;
+        let folded: Vec<String> = types.iter().map(|t| text::fold(t)).collect();
+        let position = |input| position_in_filter(input, &types, &folded, &[], Some("perf"));
+        assert_eq!(position("f"), Some(3));
+        assert_eq!(position("fe"), Some(0));
+        assert_eq!(position(""), Some(5));
+        assert_eq!(position("xyz"), None);
+    }
+
+    #[test]
+    fn should_wrap_the_selection_and_stay_empty_without_items() {
+        assert_eq!(step(Some(0), 3, false), Some(2));
+        assert_eq!(step(Some(2), 3, true), Some(0));
+        assert_eq!(step(None, 0, false), None);
+        assert_eq!(step(Some(0), 0, true), None);
+    }
+
+    #[test]
+    fn should_preview_the_header_once_it_has_a_description() {
+        let separator = glyph::get().separator;
+        assert_eq!(header_preview("feat(api): "), "");
+        assert_eq!(
+            header_preview("feat(api): add login  \nbody"),
+            format!("feat(api): add login{}20 characters", separator)
+        );
+        assert_eq!(header_preview("x"), format!("x{}1 character", separator));
+    }
+
+    #[test]
+    fn should_pick_aliased_types() {
+        let types = Config::default().types;
+        let aliases = vec![
+            ("f".to_string(), "feat".to_string()),
+            ("b".to_string(), "fix".to_string()),
+            ("bu".to_string(), "build".to_string()),
+        ];
+
+        assert_eq!(
+            filter_type_by_input("f", &types, &types, &aliases),
+            vec![(0, "feat")]
+        );
+        assert_eq!(complete_alias("f", &types, &aliases), Some("feat"));
+        // "bu" could still follow
+        assert_eq!(complete_alias("b", &types, &aliases), None);
+        assert_eq!(
+            filter_type_by_input("b", &types, &types, &aliases),
+            vec![(1, "fix")]
+        );
+        // "build" isn't one of the types
+        assert_eq!(complete_alias("bu", &types, &aliases), None);
+    }
+
+    #[test]
+    fn should_take_checked_untracked_files() {
+        let mut untracked = UntrackedFiles::new(vec!["a.rs".to_string(), "b.rs".to_string()]);
+        untracked.expanded = true;
+        untracked.cursor = 1;
+        untracked.toggle();
+
+        assert_eq!(untracked.take_checked(), vec!["b.rs".to_string()]);
+        assert_eq!(untracked.paths, vec!["a.rs".to_string()]);
+        assert!(untracked.expanded);
+    }
+
+    #[test]
+    fn should_keep_selected_type_in_compact_window() {
+        let widths = [4, 3, 4, 5, 8, 4, 4, 2, 5];
+
+        assert_eq!(compact_window(&widths, 0, 80), (0, 9));
+        assert_eq!(compact_window(&widths, 0, 20), (0, 3));
+        assert_eq!(compact_window(&widths, 8, 20), (6, 9));
+    }
+
+    #[test]
+    fn should_place_the_emoji() {
+        let mut config = Config {
+            emoji: Some(true),
+            ..Config::default()
+        };
+        let prefix = |config: &Config, commit_type: &str, scope: Option<&str>| {
+            let emoji = header_emoji(config, commit_type, scope, &[]);
+            type_prefix(config, commit_type, scope, emoji.as_deref())
+        };
+        assert_eq!(prefix(&config, "feat", Some("ui")), "feat(ui): ✨ ");
+        config.emoji_position = "before_type".to_string();
+        assert_eq!(prefix(&config, "feat", Some("ui")), "✨ feat(ui): ");
+        config.emoji_position = "instead_of_type".to_string();
+        assert_eq!(prefix(&config, "feat", None), "✨ ");
+        assert_eq!(prefix(&config, "feat", Some("ui")), "✨ (ui): ");
+        assert_eq!(prefix(&config, "wip", None), "wip: ");
+        // a picked emoji replaces the one of the type
+        assert_eq!(type_prefix(&config, "feat", None, Some("🎉")), "🎉 ");
+
+        config.scope_emojis = vec![("api".to_string(), "🌐".to_string())];
+        assert_eq!(prefix(&config, "feat", Some("api")), "✨ 🌐 (api): ");
+        config.mapped_emoji = "replace".to_string();
+        assert_eq!(prefix(&config, "feat", Some("api")), "🌐 (api): ");
+        config.path_emojis = vec![("ui/**".to_string(), "🎨".to_string())];
+        let paths = ["ui/button.rs".to_string()];
+        assert_eq!(
+            header_emoji(&config, "feat", None, &paths).as_deref(),
+            Some("🎨")
+        );
+    }
+
+    #[test]
+    fn should_expand_template() {
+        let variable = |name: &str| (name == "env.TICKET").then(|| "ABC-1".to_string());
+        assert_eq!(
+            expand_template("bump ", variable),
+            ("bump ".to_string(), None)
+        );
+        assert_eq!(
+            expand_template("bump {dep} to {version}", variable),
+            ("bump  to ".to_string(), Some(5))
+        );
+        assert_eq!(
+            expand_template("[{env.TICKET}] {what}", variable),
+            ("[ABC-1] ".to_string(), Some(8))
+        );
+        assert_eq!(
+            expand_footer("Refs: {env.TICKET}", variable).as_deref(),
+            Some("Refs: ABC-1")
+        );
+        assert_eq!(expand_footer("Reviewed-by: {user.name}", variable), None);
+    }
+
+    #[test]
+    fn should_separate_the_body_by_one_blank_line() {
+        let (header, body) = conventional::split_message("fix: a\nFirst.\n\nSecond.");
+        assert_eq!(
+            join_message(header, &separated_body(body)),
+            "fix: a\n\nFirst.\n\nSecond."
+        );
+        assert_eq!(
+            separated_body("\n\n\nFirst.\n\n  Second."),
+            "\nFirst.\n\n  Second."
+        );
+        assert_eq!(separated_body("\n  \n"), "");
+    }
+
+    #[test]
+    fn should_append_trailers() {
+        assert_eq!(add_trailers("feat: a", &[]).unwrap(), "feat: a");
+        assert_eq!(
+            append_trailer("feat: a", "Co-authored-by: Jane <jane@example.com>"),
+            "feat: a\n\nCo-authored-by: Jane <jane@example.com>"
+        );
+        assert_eq!(
+            append_trailer(
+                "feat: a\n\nCo-authored-by: Jane <jane@example.com>",
+                "Signed-off-by: Joe <joe@example.com>"
+            ),
+            "feat: a\n\nCo-authored-by: Jane <jane@example.com>\nSigned-off-by: Joe <joe@example.com>"
+        );
+        assert_eq!(
+            append_trailer("feat: a\n\nSee the issue: it explains why", "Refs: #1"),
+            "feat: a\n\nSee the issue: it explains why\n\nRefs: #1"
         );
     }
 
@@ -0,0 +1,121 @@
+use crate::config::{self, Config};
+use crate::hook;
+use crate::keymap::Keymap;
+use crate::{
+    emoji_for, git, input_commit_message, select_option, GczError, LineInput, MessageOptions,
+    COMMIT_TYPES,
+};
+use crossterm::{cursor, execute, style::Print};
+use std::io::{self, IsTerminal};
+use toml_edit::{value, Array, Item};
+
+/// True on the very first run: no config file anywhere and someone at the terminal to ask.
+pub fn needed() -> Result<bool, GczError> {
+    Ok(io::stdin().is_terminal() && config::config_paths()?.is_empty())
+}
+
+/// Asks a few optional questions and saves the answers to the global config. Skipping
+/// still writes the file, so the questions aren't asked again.
+pub fn run(stdout: &mut io::Stdout) -> Result<(), GczError> {
+    let path = match config::global_config_path() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let defaults = Config::default();
+    let keymap = &defaults.keymap;
+    let no_yes = ["No".to_string(), "Yes".to_string()];
+
+    let start = select_option(
+        stdout,
+        "Welcome to gcz! Set up a config now? (you can change it later)",
+        &[
+            "Set up".to_string(),
+            "Skip and use the defaults".to_string(),
+        ],
+        keymap,
+    )?;
+    if start == 1 {
+        config::set_values(&path, &[])?;
+        println!("Using the defaults; edit {} to change them", path.display());
+        return Ok(());
+    }
+
+    let emoji = select_option(
+        stdout,
+        &format!(
+            "Add an emoji for the type ({} feat: ...)?",
+            emoji_for(&defaults, "feat").unwrap_or_default()
+        ),
+        &no_yes,
+        keymap,
+    )? == 1;
+    let inline = select_option(
+        stdout,
+        "Where do you want to write the message?",
+        &[
+            "Inline, in the terminal".to_string(),
+            "In my editor".to_string(),
+        ],
+        keymap,
+    )? == 0;
+    let types = match select_option(
+        stdout,
+        "Which commit types?",
+        &[
+            format!("The defaults ({})", COMMIT_TYPES.join(", ")),
+            "My own list".to_string(),
+        ],
+        keymap,
+    )? {
+        0 => None,
+        _ => Some(ask_types(stdout, keymap)?),
+    };
+    let install_hook = git::toplevel()?.is_some()
+        && select_option(
+            stdout,
+            "Install a commit-msg hook that checks every commit in this repository?",
+            &no_yes,
+            keymap,
+        )? == 1;
+
+    let mut values = vec![("emoji", value(emoji)), ("inline", value(inline))];
+    if let Some(types) = types {
+        values.push(("types", Item::Value(types.iter().collect::<Array>().into())));
+    }
+    config::set_values(&path, &values)?;
+    println!("Saved to {}", path.display());
+
+    if install_hook {
+        println!("{}", hook::install()?);
+    }
+    Ok(())
+}
+
+fn ask_types(stdout: &mut io::Stdout, keymap: &Keymap) -> Result<Vec<String>, GczError> {
+    execute!(
+        stdout,
+        Print("Commit types, separated by commas:"),
+        cursor::MoveToNextLine(1)
+    )?;
+    let raw = match input_commit_message(
+        stdout,
+        &COMMIT_TYPES.join(", "),
+        None,
+        &|_| Ok(()),
+        keymap,
+        &MessageOptions::default(),
+    )? {
+        LineInput::Accepted(raw) | LineInput::OpenEditor(raw) => raw,
+    };
+
+    let types: Vec<String> = raw
+        .split([',', '\n'])
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    Ok(if types.is_empty() {
+        COMMIT_TYPES.iter().map(|t| t.to_string()).collect()
+    } else {
+        types
+    })
+}
@@ -0,0 +1,215 @@
+use crate::config::{Config, Severity};
+use crate::{
+    change_findings, changed_paths, commit_changes, conventional, emoji_for, filter_type_by_input,
+    finish_message, glyph, has_changes, header_emoji, identity, jj, lint, plugin, protect, spell,
+    text, type_prefix, GczError,
+};
+use std::io::{self, Write};
+
+/// Entry point of `gcz --plain`: the wizard with a numbered type menu and ordinary line
+/// input, for screen readers, restricted shells and terminals where raw mode misbehaves.
+pub fn plain(config: &Config, note: Option<&str>) -> Result<(), GczError> {
+    let in_jj = jj::workspace_root().is_some();
+    if !has_changes(in_jj)? {
+        return Ok(());
+    }
+    if !in_jj {
+        protect::check_name(config)?;
+        identity::check(config)?;
+    }
+    if let Some(problem) = protect::problem(config)?.filter(|_| !in_jj) {
+        if !config.confirm {
+            println!("{} {}", glyph::get().warning, problem);
+        } else if !ask(&format!("{}. Commit here anyway? [y/N] ", problem))?
+            .to_lowercase()
+            .starts_with('y')
+        {
+            return Err(GczError::UserInterrupt);
+        }
+    }
+    let paths = changed_paths(in_jj)?;
+    let staged_findings = change_findings(config, in_jj)?;
+
+    let suggested = match config.suggested_type(&paths) {
+        Some(commit_type) => Some(commit_type.to_string()),
+        None => plugin::suggested_type(config)?,
+    };
+    let commit_type = ask_type(config, suggested.as_deref())?;
+    let scope = if config.ask_scope {
+        Some(ask("Scope (leave empty for none): ")?).filter(|scope| !scope.is_empty())
+    } else {
+        None
+    };
+    let emoji = header_emoji(config, &commit_type, scope.as_deref(), &paths);
+    let prefix = type_prefix(config, &commit_type, scope.as_deref(), emoji.as_deref());
+    let speller = spell::Speller::load(config)?;
+
+    let mut description = String::new();
+    let mut body = String::new();
+    loop {
+        description = ask_again("Description", &description)?;
+        body = ask_body(&body)?;
+        let mut text = format!("{}{}", prefix, description);
+        if !body.is_empty() {
+            text = format!("{}\n\n{}", text, body);
+        }
+        let (message, mut findings) = finish_message(config, &text)?;
+        findings.splice(0..0, lint::check_header(&message, config));
+        if let Some(speller) = &speller {
+            findings.extend(speller.findings(&message));
+        }
+        findings.extend(staged_findings.iter().cloned());
+
+        let blocked = findings.iter().any(|f| f.severity == Severity::Error);
+        if !config.confirm && !blocked {
+            print_findings(&findings);
+            return commit_changes(config, &message, in_jj, note);
+        }
+
+        println!("\n{}\n", message);
+        print_findings(&findings);
+        let question = if blocked {
+            "Fix the errors: e to edit, q to quit: "
+        } else {
+            "c to commit, e to edit, q to quit: "
+        };
+        loop {
+            match ask(question)?.to_lowercase().as_str() {
+                "c" | "commit" if !blocked => {
+                    return commit_changes(config, &message, in_jj, note);
+                }
+                "e" | "edit" => break,
+                "q" | "quit" => return Err(GczError::UserInterrupt),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Lists the types with numbers and reads one by number, name or alias.
+fn ask_type(config: &Config, suggested: Option<&str>) -> Result<String, GczError> {
+    println!("Commit types:");
+    for (i, commit_type) in config.types.iter().enumerate() {
+        match emoji_for(config, commit_type).filter(|_| config.emoji == Some(true)) {
+            Some(emoji) => println!("{:>3}. {} {}", i + 1, commit_type, emoji),
+            None => println!("{:>3}. {}", i + 1, commit_type),
+        }
+    }
+    let question = match suggested {
+        Some(suggested) => format!("Type (number or name, Enter for {}): ", suggested),
+        None => "Type (number or name): ".to_string(),
+    };
+    loop {
+        let answer = ask(&question)?;
+        if answer.is_empty() {
+            if let Some(suggested) = suggested {
+                return Ok(suggested.to_string());
+            }
+            continue;
+        }
+        match pick_type(&answer, config) {
+            Some(commit_type) => return Ok(commit_type),
+            None => println!("No type matches `{}`", answer),
+        }
+    }
+}
+
+/// The type `answer` stands for: its number in the list, a name or alias, or the only type
+/// containing it. Unknown names are used as they are when `custom_types` is on.
+fn pick_type(answer: &str, config: &Config) -> Option<String> {
+    let types = &config.types;
+    if let Ok(number) = answer.parse::<usize>() {
+        return number.checked_sub(1).and_then(|i| types.get(i)).cloned();
+    }
+    let folded: Vec<String> = types.iter().map(|t| text::fold(t)).collect();
+    let matches = filter_type_by_input(answer, types, &folded, &config.type_aliases);
+    let exact = matches
+        .iter()
+        .find(|(i, _)| folded[*i] == text::fold(answer));
+    match (exact, matches.as_slice()) {
+        (Some((_, name)), _) | (None, [(_, name)]) => Some(name.to_string()),
+        _ if config.custom_types && conventional::is_type_name(answer) => Some(answer.to_string()),
+        _ => None,
+    }
+}
+
+/// Asks for `label`, offering `previous` again when there is one.
+fn ask_again(label: &str, previous: &str) -> Result<String, GczError> {
+    if previous.is_empty() {
+        return ask(&format!("{}: ", label));
+    }
+    let answer = ask(&format!("{} (Enter keeps `{}`): ", label, previous))?;
+    Ok(if answer.is_empty() {
+        previous.to_string()
+    } else {
+        answer
+    })
+}
+
+/// Reads body lines up to the first empty one; `previous` is kept when none are typed.
+fn ask_body(previous: &str) -> Result<String, GczError> {
+    if previous.is_empty() {
+        println!("Body, ending with an empty line (leave empty for none):");
+    } else {
+        println!("Body, ending with an empty line (leave empty to keep it):");
+    }
+    let mut lines = Vec::new();
+    loop {
+        let line = ask("")?;
+        if line.is_empty() {
+            break;
+        }
+        lines.push(line);
+    }
+    Ok(if lines.is_empty() {
+        previous.to_string()
+    } else {
+        lines.join("\n")
+    })
+}
+
+/// Prints `question` and reads one line; end of input aborts like Ctrl+C.
+fn ask(question: &str) -> Result<String, GczError> {
+    print!("{}", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Err(GczError::UserInterrupt);
+    }
+    Ok(answer.trim().to_string())
+}
+
+fn print_findings(findings: &[lint::Finding]) {
+    for finding in findings {
+        let mark = match finding.severity {
+            Severity::Error => glyph::get().error,
+            Severity::Warning => glyph::get().warning,
+        };
+        println!("{} {}", mark, finding.message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_pick_types_by_number_name_or_alias() {
+        let mut config = Config {
+            types: vec!["feat".to_string(), "fix".to_string(), "docs".to_string()],
+            type_aliases: vec![("f".to_string(), "fix".to_string())],
+            custom_types: false,
+            ..Config::default()
+        };
+
+        assert_eq!(pick_type("2", &config).as_deref(), Some("fix"));
+        assert_eq!(pick_type("4", &config), None);
+        assert_eq!(pick_type("FEAT", &config).as_deref(), Some("feat"));
+        assert_eq!(pick_type("f", &config).as_deref(), Some("fix"));
+        assert_eq!(pick_type("do", &config).as_deref(), Some("docs"));
+        assert_eq!(pick_type("deploy", &config), None);
+
+        config.custom_types = true;
+        assert_eq!(pick_type("deploy", &config).as_deref(), Some("deploy"));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::config::{Config, Severity};
+use crate::lint::Finding;
+use crate::{git, glyph, GczError};
+use serde_json::{json, Value};
+use std::ffi::OsString;
+use std::io;
+use std::process::{Command, ExitStatus};
+
+/// Bumped when a request or response field changes meaning.
+const PROTOCOL_VERSION: u64 = 1;
+
+/// Wizard steps a plugin can take part in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Hook {
+    /// Before the type selector; may answer `{"type": "fix"}` to preselect a type.
+    Type,
+    /// Before the message editor; may answer `{"message": "..."}` to prefill it.
+    Message,
+    /// Before the confirmation screen; may answer `{"findings": [...]}`.
+    Lint,
+}
+
+impl Hook {
+    fn name(self) -> &'static str {
+        match self {
+            Hook::Type => "type",
+            Hook::Message => "message",
+            Hook::Lint => "lint",
+        }
+    }
+}
+
+/// What the plugins said about one step, later plugins winning.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Response {
+    commit_type: Option<String>,
+    message: Option<String>,
+    findings: Vec<Finding>,
+}
+
+/// Whether `name` can only name a `gcz-<name>` executable on PATH, never a path such as
+/// `../x` or `a/b` into the repository.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Runs `gcz-<name>` for a subcommand gcz doesn't know, like git and cargo do.
+pub fn run_subcommand(name: &str, args: &[OsString]) -> Result<ExitStatus, GczError> {
+    if !is_valid_name(name) {
+        return Err(GczError::UnknownCommand(name.to_string()));
+    }
+    match Command::new(format!("gcz-{}", name)).args(args).status() {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            Err(GczError::UnknownCommand(name.to_string()))
+        }
+        result => Ok(result?),
+    }
+}
+
+/// The type the plugins suggest preselecting, if any.
+pub fn suggested_type(config: &Config) -> Result<Option<String>, GczError> {
+    let response = call(config, Hook::Type, json!({}))?;
+    print_findings(&response.findings);
+    Ok(response
+        .commit_type
+        .filter(|commit_type| config.types.contains(commit_type)))
+}
+
+/// The message the plugins want to start from instead of `message`, if any.
+pub fn prefill(
+    config: &Config,
+    commit_type: &str,
+    message: &str,
+) -> Result<Option<String>, GczError> {
+    let response = call(
+        config,
+        Hook::Message,
+        json!({ "type": commit_type, "message": message }),
+    )?;
+    print_findings(&response.findings);
+    Ok(response.message)
+}
+
+/// Findings from the plugins about the finished message.
+pub fn lint(config: &Config, message: &str) -> Result<Vec<Finding>, GczError> {
+    Ok(call(config, Hook::Lint, json!({ "message": message }))?.findings)
+}
+
+/// Steps without a confirmation screen can only print what went wrong.
+fn print_findings(findings: &[Finding]) {
+    for finding in findings {
+        eprintln!("{} {}", glyph::get().warning, finding.message);
+    }
+}
+
+/// Asks every plugin in `plugins` about `hook`, adding `fields` to the request. A plugin
+/// that fails is reported as a warning and otherwise ignored.
+fn call(config: &Config, hook: Hook, fields: Value) -> Result<Response, GczError> {
+    let mut response = Response::default();
+    if config.plugins.is_empty() {
+        return Ok(response);
+    }
+
+    let mut request = json!({
+        "protocol": PROTOCOL_VERSION,
+        "hook": hook.name(),
+        "types": config.types,
+        "staged": git::staged_files()?,
+    });
+    if let (Some(request), Value::Object(fields)) = (request.as_object_mut(), fields) {
+        request.extend(fields);
+    }
+
+    for name in &config.plugins {
+        match ask(name, hook, &request) {
+            Ok(answer) => {
+                response.commit_type = answer.commit_type.or(response.commit_type);
+                response.message = answer.message.or(response.message);
+                response.findings.extend(answer.findings);
+            }
+            Err(problem) => response.findings.push(Finding {
+                severity: Severity::Warning,
+                message: format!("plugin `{}`: {}", name, problem),
+            }),
+        }
+    }
+    Ok(response)
+}
+
+/// Runs `gcz-<name> --gcz-hook <hook>` with the request on stdin and parses its answer.
+fn ask(name: &str, hook: Hook, request: &Value) -> Result<Response, String> {
+    if !is_valid_name(name) {
+        return Err("not a valid plugin name".to_string());
+    }
+    let output = git::run_with_input(
+        Command::new(format!("gcz-{}", name)).args(["--gcz-hook", hook.name()]),
+        request.to_string().into_bytes(),
+        &format!("Asking {}", name),
+    )
+    .map_err(|err| match err {
+        GczError::Io(err) if err.kind() == io::ErrorKind::NotFound => {
+            format!("gcz-{} not found on PATH", name)
+        }
+        err => err.to_string(),
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("exited with {}", output.status),
+            stderr => stderr.to_string(),
+        });
+    }
+    parse_response(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Empty output means the plugin has nothing to say about this step.
+fn parse_response(raw: &str) -> Result<Response, String> {
+    if raw.trim().is_empty() {
+        return Ok(Response::default());
+    }
+    let value: Value =
+        serde_json::from_str(raw).map_err(|err| format!("invalid response: {}", err))?;
+
+    let findings = value
+        .get("findings")
+        .and_then(Value::as_array)
+        .map(|findings| findings.iter().map(parse_finding).collect())
+        .transpose()?
+        .unwrap_or_default();
+    Ok(Response {
+        commit_type: value.get("type").and_then(Value::as_str).map(String::from),
+        message: value
+            .get("message")
+            .and_then(Value::as_str)
+            .map(String::from),
+        findings,
+    })
+}
+
+fn parse_finding(value: &Value) -> Result<Finding, String> {
+    let message = value
+        .get("message")
+        .and_then(Value::as_str)
+        .ok_or("each finding needs a \"message\"")?;
+    let severity = match value.get("severity").and_then(Value::as_str) {
+        Some("error") => Severity::Error,
+        Some("warning") | None => Severity::Warning,
+        Some(other) => return Err(format!("unknown severity {:?}", other)),
+    };
+    Ok(Finding {
+        severity,
+        message: message.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_responses() {
+        let raw = r#"{"type": "fix", "findings": [
+            {"severity": "error", "message": "missing ticket"},
+            {"message": "long subject"}
+        ]}"#;
+
+        assert_eq!(
+            parse_response(raw),
+            Ok(Response {
+                commit_type: Some("fix".to_string()),
+                message: None,
+                findings: vec![
+                    Finding {
+                        severity: Severity::Error,
+                        message: "missing ticket".to_string(),
+                    },
+                    Finding {
+                        severity: Severity::Warning,
+                        message: "long subject".to_string(),
+                    },
+                ],
+            })
+        );
+        assert_eq!(parse_response("\n"), Ok(Response::default()));
+        assert!(parse_response("not json").is_err());
+        assert!(
+            parse_response(r#"{"findings": [{"severity": "fatal", "message": "x"}]}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn should_only_accept_plain_plugin_names() {
+        assert!(is_valid_name("jira"));
+        assert!(is_valid_name("ticket_check-2"));
+        assert!(!is_valid_name("../x"));
+        assert!(!is_valid_name("a/b"));
+        assert!(!is_valid_name(""));
+    }
+}
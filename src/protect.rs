@@ -0,0 +1,116 @@
+use crate::config::Config;
+use crate::{branch, git, glob, glyph, select_option, GczError};
+use regex::Regex;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether `--force-intent` was given. Set once from the command line.
+static FORCE_INTENT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_force_intent(force: bool) {
+    FORCE_INTENT.store(force, Ordering::Relaxed);
+}
+
+/// Fails when `commit` is already on the upstream of the current branch, where amending or
+/// rewording it would rewrite published history, unless `--force-intent` was given.
+pub fn check_rewrite(commit: &str) -> Result<(), GczError> {
+    if FORCE_INTENT.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let Some(upstream) = git::upstream()? else {
+        return Ok(());
+    };
+    if !git::is_ancestor(commit, &upstream)? {
+        return Ok(());
+    }
+    Err(GczError::PublishedCommit {
+        commit: commit[..commit.len().min(7)].to_string(),
+        upstream,
+    })
+}
+
+/// Fails when `branch_patterns` is set and the current branch matches none of them. A
+/// detached `HEAD` has no name to check.
+pub fn check_name(config: &Config) -> Result<(), GczError> {
+    if config.branch_patterns.is_empty() {
+        return Ok(());
+    }
+    let Some(branch) = git::current_branch()? else {
+        return Ok(());
+    };
+    if name_allowed(&config.branch_patterns, &branch) {
+        return Ok(());
+    }
+    Err(GczError::BranchRejected {
+        branch,
+        patterns: config.branch_patterns.clone(),
+    })
+}
+
+fn name_allowed(patterns: &[String], branch: &str) -> bool {
+    patterns
+        .iter()
+        .filter_map(|pattern| Regex::new(pattern).ok())
+        .any(|re| re.is_match(branch))
+}
+
+/// Why committing here is likely a mistake: a detached `HEAD`, or a branch matching
+/// `protected_branches`.
+pub fn problem(config: &Config) -> Result<Option<String>, GczError> {
+    Ok(match git::current_branch()? {
+        None => Some("HEAD is detached, so the commit won't be on any branch".to_string()),
+        Some(branch) if is_protected(config, &branch) => {
+            Some(format!("`{}` is a protected branch", branch))
+        }
+        Some(_) => None,
+    })
+}
+
+/// Asks before committing on a detached `HEAD` or a protected branch, offering to create a
+/// branch first. Without confirmations the problem is only printed.
+pub fn confirm(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    while let Some(problem) = problem(config)? {
+        if !config.confirm {
+            eprintln!("{} {}", glyph::get().warning, problem);
+            return Ok(());
+        }
+        let options = [
+            "Commit here anyway".to_string(),
+            "Create a branch first".to_string(),
+            "Abort".to_string(),
+        ];
+        let title = format!("{}. Commit here?", problem);
+        match select_option(stdout, &title, &options, &config.keymap)? {
+            0 => return Ok(()),
+            1 => branch::branch(stdout, config)?,
+            _ => return Err(GczError::UserInterrupt),
+        }
+    }
+    Ok(())
+}
+
+/// Patterns without `/` only match branches without one, so `main` doesn't cover
+/// `feat/main`.
+fn is_protected(config: &Config, branch: &str) -> bool {
+    config.protected_branches.iter().any(|pattern| {
+        (pattern.contains('/') || !branch.contains('/')) && glob::matches(pattern, branch)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_check_branch_names() {
+        let config = Config::default();
+        assert!(is_protected(&config, "main"));
+        assert!(is_protected(&config, "release/1.2"));
+        assert!(!is_protected(&config, "feat/main"));
+        assert!(!is_protected(&config, "release"));
+
+        let patterns = ["^(feat|fix)/".to_string(), "^release/".to_string()];
+        assert!(name_allowed(&patterns, "fix/crash"));
+        assert!(!name_allowed(&patterns, "main"));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::config::Config;
+use crate::{gcz, git, hook, identity, lock, protect, publish, select_option, stats, GczError};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Entry point of `gcz --recurse <dir>`: runs the wizard in every repository under `dir`
+/// with staged changes, one after the other. After the first commit the same message can be
+/// reused. Each repository is committed with its own config, which `load` reads from the
+/// current directory; a failure in one is reported and the next one goes on.
+pub fn recurse(
+    stdout: &mut io::Stdout,
+    dir: &Path,
+    force: bool,
+    note: Option<&str>,
+    load: impl Fn() -> Result<Config, GczError>,
+) -> Result<(), GczError> {
+    let start = env::current_dir()?;
+    let mut repositories = Vec::new();
+    for path in find_repositories(dir)? {
+        if git::change_counts(&path)?.0 > 0 {
+            repositories.push(path);
+        }
+    }
+    if repositories.is_empty() {
+        println!("No repository under {} has staged changes", dir.display());
+        return Ok(());
+    }
+
+    let mut message: Option<String> = None;
+    let mut committed = 0;
+    for (i, path) in repositories.iter().enumerate() {
+        println!(
+            "[{}/{}] {}",
+            i + 1,
+            repositories.len(),
+            path.strip_prefix(&start).unwrap_or(path).display()
+        );
+        env::set_current_dir(start.join(path))?;
+        match commit_in(stdout, &load, force, note, &mut message) {
+            Ok(true) => committed += 1,
+            Ok(false) => {}
+            Err(GczError::UserInterrupt) => return Err(GczError::UserInterrupt),
+            Err(err) => eprintln!("Error: {}", err),
+        }
+    }
+    env::set_current_dir(start)?;
+    println!(
+        "Committed in {} of {} repositories",
+        committed,
+        repositories.len()
+    );
+    Ok(())
+}
+
+/// Commits in the current repository, offering `message` from the previous one first.
+/// Returns whether a commit was made.
+fn commit_in(
+    stdout: &mut io::Stdout,
+    load: &impl Fn() -> Result<Config, GczError>,
+    force: bool,
+    note: Option<&str>,
+    message: &mut Option<String>,
+) -> Result<bool, GczError> {
+    let config = load()?;
+    let _lock = lock::acquire(force)?;
+    if let Some(previous) = message.as_deref() {
+        let subject = previous.lines().next().unwrap_or_default();
+        let options = [
+            format!("Commit with `{}` again", subject),
+            "Write a new message".to_string(),
+            "Skip this repository".to_string(),
+        ];
+        match select_option(stdout, "Reuse the last message?", &options, &config.keymap)? {
+            0 => {
+                protect::check_name(&config)?;
+                protect::confirm(stdout, &config)?;
+                identity::confirm(stdout, &config)?;
+                hook::commit_with_triage(stdout, &config, previous)?;
+                publish(&config, previous)?;
+                stats::print(&config);
+                return Ok(true);
+            }
+            1 => {}
+            _ => return Ok(false),
+        }
+    }
+    gcz(stdout, &config, note, false)?;
+    *message = Some(git::commit_message("HEAD")?);
+    Ok(true)
+}
+
+/// `dir` and the directories below it that hold a repository, skipping hidden ones.
+fn find_repositories(dir: &Path) -> Result<Vec<PathBuf>, GczError> {
+    let mut found = Vec::new();
+    if dir.join(".git").exists() {
+        found.push(dir.to_path_buf());
+    }
+    let mut entries: Vec<_> = fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !hidden && entry.file_type()?.is_dir() {
+            found.extend(find_repositories(&entry.path())?);
+        }
+    }
+    Ok(found)
+}
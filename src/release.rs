@@ -0,0 +1,312 @@
+use crate::config::Config;
+use crate::{check_staged_changes, commit, compose_message, select_option, GczError};
+use crate::{git, glyph};
+use regex::Regex;
+use std::path::PathBuf;
+use std::{fs, io};
+use toml_edit::{DocumentMut, Item};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Manifest {
+    Cargo,
+    Npm,
+    Pyproject,
+}
+
+impl Manifest {
+    const ALL: [Manifest; 3] = [Manifest::Cargo, Manifest::Npm, Manifest::Pyproject];
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Manifest::Cargo => "Cargo.toml",
+            Manifest::Npm => "package.json",
+            Manifest::Pyproject => "pyproject.toml",
+        }
+    }
+
+    /// Tables that may hold the version, in lookup order.
+    fn version_paths(self) -> &'static [&'static [&'static str]] {
+        match self {
+            Manifest::Cargo => &[
+                &["package", "version"],
+                &["workspace", "package", "version"],
+            ],
+            Manifest::Pyproject => &[&["project", "version"], &["tool", "poetry", "version"]],
+            Manifest::Npm => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Bump {
+    const ALL: [Bump; 3] = [Bump::Patch, Bump::Minor, Bump::Major];
+
+    fn name(self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+/// Entry point of `gcz release`: bumps the manifest version and commits it as `chore(release): vX.Y.Z`.
+pub fn release(stdout: &mut io::Stdout, config: &Config, tag: bool) -> Result<(), GczError> {
+    let root = match git::toplevel()? {
+        Some(root) => root,
+        None => return Err(GczError::NotARepository),
+    };
+
+    let (manifest, path) = match Manifest::ALL
+        .iter()
+        .map(|&m| (m, root.join(m.file_name())))
+        .find(|(_, path)| path.is_file())
+    {
+        Some(found) => found,
+        None => {
+            println!("No Cargo.toml, package.json or pyproject.toml found");
+            return Ok(());
+        }
+    };
+
+    let raw = fs::read_to_string(&path)?;
+    let current = match read_version(manifest, &raw) {
+        Some(version) => version,
+        None => {
+            println!("No version found in {}", manifest.file_name());
+            return Ok(());
+        }
+    };
+
+    let candidates: Vec<(String, String)> = Bump::ALL
+        .iter()
+        .filter_map(|&b| bump(&current, b).map(|next| (b.name().to_string(), next)))
+        .collect();
+    if candidates.is_empty() {
+        println!("Unsupported version format: {}", current);
+        return Ok(());
+    }
+
+    let options: Vec<String> = candidates
+        .iter()
+        .map(|(name, next)| format!("{:<5}  {} {} {}", name, current, glyph::get().arrow, next))
+        .collect();
+    let title = format!("Current version in {}: {}", manifest.file_name(), current);
+    let next = candidates[select_option(stdout, &title, &options, &config.keymap)?]
+        .1
+        .clone();
+
+    let text = format!("chore(release): v{}", next);
+    let message = compose_message(
+        stdout,
+        config,
+        text,
+        None,
+        &check_staged_changes(config)?,
+        None,
+        None,
+        false,
+    )?
+    .message;
+
+    let lock_path = root.join("Cargo.lock");
+    let mut originals = vec![(path.clone(), raw.clone())];
+    fs::write(&path, write_version(manifest, &raw, &next))?;
+    if manifest == Manifest::Cargo {
+        let lock_raw = fs::read_to_string(&lock_path).ok();
+        if let (Some(lock_raw), Some(_)) = (
+            lock_raw,
+            update_cargo_lock(&lock_path, &raw, &current, &next)?,
+        ) {
+            originals.push((lock_path, lock_raw));
+        }
+    }
+    let changed: Vec<String> = originals
+        .iter()
+        .map(|(p, _)| p.to_string_lossy().into_owned())
+        .collect();
+
+    // committed from an index of its own, so nothing else staged goes into the release
+    let index = git::git_path("GCZ_RELEASE_INDEX")?;
+    let result = git::stage_only(&index, &changed)
+        .and_then(|()| commit(config, &message, Some(&index), false));
+    let _ = fs::remove_file(&index);
+    if let Err(err) = result {
+        for (path, raw) in &originals {
+            fs::write(path, raw)?;
+        }
+        println!("The version is back to {}; nothing was committed", current);
+        return Err(err);
+    }
+    // the real index still has the old version of the files just committed
+    git::reset_in_index(&git::git_path("index")?, &changed)?;
+
+    if tag {
+        git::create_tag(&format!("v{}", next))?;
+    }
+    Ok(())
+}
+
+fn read_version(manifest: Manifest, raw: &str) -> Option<String> {
+    if manifest == Manifest::Npm {
+        return npm_version_regex()
+            .captures(raw)
+            .map(|captures| captures[2].to_string());
+    }
+
+    let doc: DocumentMut = raw.parse().ok()?;
+    manifest
+        .version_paths()
+        .iter()
+        .find_map(|path| lookup(doc.as_item(), path)?.as_str().map(String::from))
+}
+
+fn write_version(manifest: Manifest, raw: &str, version: &str) -> String {
+    if manifest == Manifest::Npm {
+        return npm_version_regex()
+            .replace(raw, |captures: &regex::Captures| {
+                format!("{}{}\"", &captures[1], version)
+            })
+            .into_owned();
+    }
+
+    let mut doc: DocumentMut = match raw.parse() {
+        Ok(doc) => doc,
+        Err(_) => return raw.to_string(),
+    };
+    for path in manifest.version_paths() {
+        let item = match lookup_mut(doc.as_item_mut(), path) {
+            Some(item) if item.is_str() => item,
+            _ => continue,
+        };
+        let decor = item.as_value().map(|v| v.decor().clone());
+        *item = toml_edit::value(version);
+        if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+            *value.decor_mut() = decor;
+        }
+        break;
+    }
+    doc.to_string()
+}
+
+/// Bumps the root package in `Cargo.lock` so the release commit doesn't leave it stale.
+fn update_cargo_lock(
+    lock_path: &PathBuf,
+    manifest_raw: &str,
+    current: &str,
+    next: &str,
+) -> Result<Option<PathBuf>, GczError> {
+    let name = manifest_raw.parse::<DocumentMut>().ok().and_then(|doc| {
+        lookup(doc.as_item(), &["package", "name"])?
+            .as_str()
+            .map(String::from)
+    });
+    let (name, raw) = match (name, fs::read_to_string(lock_path)) {
+        (Some(name), Ok(raw)) => (name, raw),
+        _ => return Ok(None),
+    };
+
+    let mut doc: DocumentMut = match raw.parse() {
+        Ok(doc) => doc,
+        Err(_) => return Ok(None),
+    };
+    let packages = match doc
+        .get_mut("package")
+        .and_then(|p| p.as_array_of_tables_mut())
+    {
+        Some(packages) => packages,
+        None => return Ok(None),
+    };
+    let package = packages.iter_mut().find(|p| {
+        p.get("name").and_then(|n| n.as_str()) == Some(name.as_str())
+            && p.get("version").and_then(|v| v.as_str()) == Some(current)
+    });
+    match package {
+        Some(package) => package["version"] = toml_edit::value(next),
+        None => return Ok(None),
+    }
+
+    fs::write(lock_path, doc.to_string())?;
+    Ok(Some(lock_path.clone()))
+}
+
+fn lookup<'a>(item: &'a Item, path: &[&str]) -> Option<&'a Item> {
+    path.iter().try_fold(item, |item, key| item.get(key))
+}
+
+fn lookup_mut<'a>(item: &'a mut Item, path: &[&str]) -> Option<&'a mut Item> {
+    path.iter().try_fold(item, |item, key| item.get_mut(key))
+}
+
+/// The first `"version": "..."` in package.json, which is the top-level one by convention.
+fn npm_version_regex() -> Regex {
+    Regex::new(r#"("version"\s*:\s*")([^"]*)""#).expect("version pattern should compile")
+}
+
+/// Bumps a `MAJOR.MINOR.PATCH` version. A pre-release is released by a patch bump
+/// instead of incrementing.
+fn bump(version: &str, bump: Bump) -> Option<String> {
+    let core_end = version.find(['-', '+']).unwrap_or(version.len());
+    let is_prerelease = version[core_end..].starts_with('-');
+
+    let parts: Vec<u64> = version[..core_end]
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let [major, minor, patch] = parts[..] else {
+        return None;
+    };
+
+    Some(match bump {
+        Bump::Major => format!("{}.0.0", major + 1),
+        Bump::Minor => format!("{}.{}.0", major, minor + 1),
+        Bump::Patch if is_prerelease => format!("{}.{}.{}", major, minor, patch),
+        Bump::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_bump_versions() {
+        assert_eq!(bump("0.2.0", Bump::Patch), Some("0.2.1".to_string()));
+        assert_eq!(bump("0.2.9", Bump::Minor), Some("0.3.0".to_string()));
+        assert_eq!(bump("1.4.2", Bump::Major), Some("2.0.0".to_string()));
+        assert_eq!(bump("1.0.0-rc.1", Bump::Patch), Some("1.0.0".to_string()));
+        assert_eq!(bump("1.0", Bump::Patch), None);
+    }
+
+    #[test]
+    fn should_read_and_write_versions() {
+        let cargo = "[package]\nname = \"gcz\"\nversion = \"0.2.0\" # keep\n";
+        assert_eq!(
+            read_version(Manifest::Cargo, cargo),
+            Some("0.2.0".to_string())
+        );
+        assert_eq!(
+            write_version(Manifest::Cargo, cargo, "0.3.0"),
+            "[package]\nname = \"gcz\"\nversion = \"0.3.0\" # keep\n"
+        );
+
+        let npm = "{\n  \"name\": \"app\",\n  \"version\": \"1.2.3\"\n}\n";
+        assert_eq!(read_version(Manifest::Npm, npm), Some("1.2.3".to_string()));
+        assert_eq!(
+            write_version(Manifest::Npm, npm, "1.3.0"),
+            "{\n  \"name\": \"app\",\n  \"version\": \"1.3.0\"\n}\n"
+        );
+
+        let poetry = "[tool.poetry]\nversion = \"0.1.0\"\n";
+        assert_eq!(
+            read_version(Manifest::Pyproject, poetry),
+            Some("0.1.0".to_string())
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use crate::config::Config;
+use crate::{git, select_option, GczError};
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A repository next to the current one that may be the one to commit in.
+struct Candidate {
+    path: PathBuf,
+    kind: &'static str,
+    staged: usize,
+    changed: usize,
+}
+
+impl Candidate {
+    fn label(&self) -> String {
+        let changes = match (self.staged, self.changed) {
+            (0, 0) => "no changes".to_string(),
+            (0, changed) => format!("{} changed, nothing staged", changed),
+            (staged, changed) => format!("{} staged of {} changed", staged, changed),
+        };
+        format!("{} ({}, {})", self.path.display(), self.kind, changes)
+    }
+}
+
+/// When submodules or other worktrees have changes, asks which repository to commit in and
+/// switches to it. Returns whether the current directory changed.
+pub fn pick(stdout: &mut io::Stdout, config: &Config) -> Result<bool, GczError> {
+    let Some(toplevel) = git::toplevel()? else {
+        return Ok(false);
+    };
+    let mut others = Vec::new();
+    for path in git::submodules()? {
+        let (staged, changed) = git::change_counts(Path::new(&path))?;
+        if changed > 0 {
+            others.push(Candidate {
+                path: PathBuf::from(path),
+                kind: "submodule",
+                staged,
+                changed,
+            });
+        }
+    }
+    for path in git::worktrees()? {
+        if same_path(&path, &toplevel) {
+            continue;
+        }
+        let (staged, changed) = git::change_counts(&path)?;
+        if changed > 0 {
+            others.push(Candidate {
+                path,
+                kind: "worktree",
+                staged,
+                changed,
+            });
+        }
+    }
+    if others.is_empty() {
+        return Ok(false);
+    }
+
+    let (staged, changed) = git::change_counts(&toplevel)?;
+    let current = Candidate {
+        path: PathBuf::from("."),
+        kind: "this repository",
+        staged,
+        changed,
+    };
+    let options: Vec<String> = std::iter::once(&current)
+        .chain(&others)
+        .map(Candidate::label)
+        .collect();
+    let choice = select_option(
+        stdout,
+        "Which repository do you want to commit in?",
+        &options,
+        &config.keymap,
+    )?;
+    if choice == 0 {
+        return Ok(false);
+    }
+    env::set_current_dir(&others[choice - 1].path)?;
+    Ok(true)
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
@@ -0,0 +1,235 @@
+use crate::config::Config;
+use crate::conventional;
+use crate::editor;
+use crate::git;
+use crate::jj;
+use crate::lint;
+use crate::protect;
+use crate::spell;
+use crate::text;
+use crate::{
+    edit_message, join_message, report_findings, select_commit_type, GczError, MessageOptions,
+    UntrackedFiles,
+};
+use std::process::Command;
+use std::{env, fs, io};
+
+/// Rewrites the message of `reference` through the wizard, pre-filled with its current message.
+pub fn reword(stdout: &mut io::Stdout, config: &Config, reference: &str) -> Result<(), GczError> {
+    if jj::workspace_root().is_some() {
+        return reword_jj(stdout, config, reference);
+    }
+    if !git::is_inside_git_dir()?.stdout.starts_with(b"true") {
+        return Err(GczError::NotARepository);
+    }
+
+    let commit = git::resolve_commit(reference)?
+        .ok_or_else(|| GczError::UnknownRevision(reference.to_string()))?;
+    protect::check_rewrite(&commit)?;
+
+    let message = edit_existing(stdout, config, &git::commit_message(&commit)?)?;
+    if git::resolve_commit("HEAD")?.as_deref() == Some(commit.as_str()) {
+        git::commit_with_spinner(
+            Command::new("git").args(["commit", "--amend", "--only", "-m", &message]),
+            "Amending",
+        )?;
+    } else if !rebase_with_message(&commit, &message)?.success() {
+        return Err(GczError::RebaseFailed);
+    }
+    Ok(())
+}
+
+/// Entry point of `gcz amend-body`: opens only the body of the last commit in the editor and
+/// amends it in place. The header stays, and nothing staged goes into the commit.
+pub fn amend_body(config: &Config) -> Result<(), GczError> {
+    let in_jj = jj::workspace_root().is_some();
+    let original = if in_jj {
+        jj::description("@-")?
+    } else {
+        if !git::is_inside_git_dir()?.stdout.starts_with(b"true") {
+            return Err(GczError::NotARepository);
+        }
+        match git::resolve_commit("HEAD")? {
+            Some(head) => {
+                protect::check_rewrite(&head)?;
+                Some(git::commit_message(&head)?)
+            }
+            None => None,
+        }
+    };
+    let Some(original) = original else {
+        println!("No commit to amend yet");
+        return Ok(());
+    };
+
+    let (header, rest) = conventional::split_message(&original);
+    let body = rest.trim_matches('\n');
+    let edited = text::tidy_whitespace(&editor::edit(config, body)?, config.expand_tabs);
+    if edited.trim() == body.trim() {
+        println!("Body unchanged");
+        return Ok(());
+    }
+    let message = format!("{}\n\n{}", header, edited.trim_matches('\n'));
+    report_findings(&lint::check_message(&message, config))?;
+
+    if in_jj {
+        if !jj::describe("@-", &message)?.success() {
+            return Err(GczError::CommitFailed);
+        }
+        return Ok(());
+    }
+    git::commit_with_spinner(
+        Command::new("git").args(["commit", "--amend", "--only", "-m", &message]),
+        "Amending",
+    )
+}
+
+/// In jj, rewording any change is a `jj describe`.
+fn reword_jj(stdout: &mut io::Stdout, config: &Config, revision: &str) -> Result<(), GczError> {
+    let original = jj::description(revision)?
+        .ok_or_else(|| GczError::UnknownRevision(revision.to_string()))?;
+
+    let message = edit_existing(stdout, config, &original)?;
+    if !jj::describe(revision, &message)?.success() {
+        return Err(GczError::CommitFailed);
+    }
+    Ok(())
+}
+
+/// Runs the type and message steps starting from `original`, keeping its scope and body.
+fn edit_existing(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    original: &str,
+) -> Result<String, GczError> {
+    let (header, rest) = conventional::split_message(original);
+    let parsed = conventional::parse_header(header).ok();
+
+    let mut untracked = UntrackedFiles::new(Vec::new());
+    let selected_type = select_commit_type(
+        stdout,
+        config,
+        &mut untracked,
+        parsed.as_ref().map(|h| h.commit_type),
+    )?;
+
+    let (prefix, description) = match &parsed {
+        Some(h) => (
+            format!(
+                "{}{}{}",
+                selected_type,
+                h.scope.map(|s| format!("({})", s)).unwrap_or_default(),
+                if h.breaking { "!" } else { "" }
+            ),
+            h.description,
+        ),
+        None => (selected_type, header),
+    };
+    let mut body = rest.to_string();
+    let speller = spell::Speller::load(config)?;
+    let options = MessageOptions {
+        speller: speller.as_ref(),
+        confirm_abort: config.confirm_abort,
+        preview: config.header_preview,
+        ..MessageOptions::default()
+    };
+    let new_header = edit_message(
+        stdout,
+        config,
+        &format!("{}: {}", prefix, description),
+        None,
+        &mut body,
+        None,
+        &options,
+    )?;
+    Ok(join_message(&new_header, &body))
+}
+
+/// Runs `git rebase -i` with gcz itself as the sequence editor, amending `commit` right after it is picked.
+/// `--rebase-merges` keeps the merges above `commit` instead of flattening them.
+fn rebase_with_message(commit: &str, message: &str) -> Result<std::process::ExitStatus, GczError> {
+    let message_file = git::git_path("GCZ_REWORD_MSG")?;
+    fs::write(&message_file, message)?;
+
+    let sequence_editor = format!(
+        "{} __reword-todo {} {}",
+        shell_quote(&env::current_exe()?.to_string_lossy()),
+        commit,
+        shell_quote(&message_file.to_string_lossy())
+    );
+    let base = if git::has_parent(commit)? {
+        format!("{}^", commit)
+    } else {
+        "--root".to_string()
+    };
+
+    let status = Command::new("git")
+        .args(["rebase", "-i", "--autostash", "--rebase-merges", &base])
+        .env("GIT_SEQUENCE_EDITOR", sequence_editor)
+        .status()?;
+
+    if status.success() {
+        fs::remove_file(&message_file)?;
+    }
+    Ok(status)
+}
+
+/// Entry point of the hidden `__reword-todo` subcommand git invokes as its sequence editor.
+pub fn edit_todo(commit: &str, message_file: &str, todo_file: &str) -> Result<(), GczError> {
+    let todo = fs::read_to_string(todo_file)?;
+    fs::write(todo_file, insert_amend(&todo, commit, message_file))?;
+    Ok(())
+}
+
+fn insert_amend(todo: &str, commit: &str, message_file: &str) -> String {
+    let mut lines = Vec::new();
+
+    for line in todo.lines() {
+        lines.push(line.to_string());
+
+        // with `--rebase-merges`, a merge is recreated by `merge -C <id> <label>`
+        let mut words = line.split_whitespace();
+        let is_pick = match words.next() {
+            Some("pick") | Some("p") => true,
+            Some("merge") | Some("m") => matches!(words.next(), Some("-C") | Some("-c")),
+            _ => false,
+        };
+        if is_pick && words.next().is_some_and(|id| commit.starts_with(id)) {
+            lines.push(format!(
+                "exec git commit --amend --only -F {}",
+                shell_quote(message_file)
+            ));
+        }
+    }
+
+    lines.join("\n") + "\n"
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_insert_amend_after_target_pick() {
+        let todo = "pick 1a2b3c4 feat: first\npick 5d6e7f8 fix: second\n\n# Rebase";
+        let result = insert_amend(todo, "1a2b3c4d5e6f", "/repo/.git/GCZ_REWORD_MSG");
+
+        assert_eq!(
+            result,
+            "pick 1a2b3c4 feat: first\n\
+             exec git commit --amend --only -F '/repo/.git/GCZ_REWORD_MSG'\n\
+             pick 5d6e7f8 fix: second\n\n# Rebase\n"
+        );
+
+        let todo = "label onto\nreset onto\nmerge -C 9a8b7c6 topic # Merge branch 'topic'\n";
+        assert_eq!(
+            insert_amend(todo, "9a8b7c6d5e4f", "/repo/.git/GCZ_REWORD_MSG"),
+            "label onto\nreset onto\nmerge -C 9a8b7c6 topic # Merge branch 'topic'\n\
+             exec git commit --amend --only -F '/repo/.git/GCZ_REWORD_MSG'\n"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use crate::config::{self, Config};
+use crate::keymap::{Action, Keymap, Screen};
+use crate::term::{self, enable_raw_mode};
+use crate::{
+    conventional, draw_progress, draw_status_line, filter_type_by_input, finalize, git, glyph,
+    position_in_filter, print_match, select_option, show_help, step, text, GczError,
+};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+
+/// Asks for the scope of the commit, suggesting the ones in `scopes`; `None` when skipped.
+/// A scope the registry doesn't know yet is offered for the repository's `.gcz.toml`.
+pub fn select_scope(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    commit_type: &str,
+) -> Result<Option<String>, GczError> {
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    let scope = input_scope(stdout, &config.scopes, &config.keymap, commit_type)
+        .and_then(|scope| finalize(scope, stdout))?;
+    offer_to_remember(stdout, config, scope.as_deref())?;
+    Ok(scope)
+}
+
+/// The type and scope picked on one screen, the types on the left and the scopes on the
+/// right, for `scope_pane`. `types` are in the order to list them.
+pub fn select_type_and_scope(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    types: &[String],
+    suggested: Option<&str>,
+) -> Result<(String, Option<String>), GczError> {
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    let (commit_type, scope) = input_type_and_scope(stdout, config, types, suggested)
+        .and_then(|picked| finalize(picked, stdout))?;
+    offer_to_remember(stdout, config, scope.as_deref())?;
+    Ok((commit_type, scope))
+}
+
+/// Offers to add a scope the registry doesn't know yet to the repository's `.gcz.toml`.
+fn offer_to_remember(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    scope: Option<&str>,
+) -> Result<(), GczError> {
+    if let Some(scope) = scope {
+        if !config.scopes.iter().any(|known| known == scope) && git::toplevel()?.is_some() {
+            let title = format!(
+                "`{}` is a new scope. Remember it in .gcz.toml for future suggestions?",
+                scope
+            );
+            let options = ["Remember it".to_string(), "Just this once".to_string()];
+            if select_option(stdout, &title, &options, &config.keymap)? == 0 {
+                config::remember_scope(scope)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn input_scope(
+    stdout: &mut io::Stdout,
+    scopes: &[String],
+    keymap: &Keymap,
+    commit_type: &str,
+) -> Result<Option<String>, GczError> {
+    let mut input = String::new();
+    // `None` while the typed text itself is chosen
+    let mut selected: Option<usize> = None;
+    let folded: Vec<String> = scopes.iter().map(|scope| text::fold(scope)).collect();
+
+    loop {
+        let suggestions = matching_scopes(&input, scopes, &folded);
+        if term::should_redraw()? {
+            render_scopes(stdout, commit_type, &input, &suggestions, selected, keymap)?;
+        }
+
+        let key_event = match event::read()? {
+            Event::Key(key_event) => key_event,
+            _ => continue,
+        };
+        match keymap.action(Screen::Scope, &key_event) {
+            Some(Action::Abort) => return Err(GczError::UserInterrupt),
+            Some(Action::Help) => show_help(stdout, keymap, &[Screen::Scope])?,
+            Some(Action::Up) => {
+                selected = match selected {
+                    Some(0) | None => None,
+                    Some(i) => Some(i - 1),
+                }
+            }
+            Some(Action::Down) if !suggestions.is_empty() => {
+                selected = Some(selected.map_or(0, |i| (i + 1).min(suggestions.len() - 1)))
+            }
+            Some(Action::Confirm) => {
+                let scope = match selected {
+                    Some(i) => suggestions[i].to_string(),
+                    None => input.trim().to_string(),
+                };
+                return Ok(Some(scope).filter(|scope| !scope.is_empty()));
+            }
+            Some(Action::DeleteBack) => {
+                input.pop();
+                selected = None;
+            }
+            Some(Action::Clear) => {
+                input.clear();
+                selected = None;
+            }
+            _ => match key_event.code {
+                // a scope is one word inside the parentheses
+                KeyCode::Char(c)
+                    if !key_event.modifiers.contains(KeyModifiers::CONTROL)
+                        && !c.is_whitespace()
+                        && !"():".contains(c) =>
+                {
+                    input.push(c);
+                    selected = None;
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Which side of the type and scope screen the keys go to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Types,
+    Scopes,
+}
+
+fn input_type_and_scope(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    types: &[String],
+    suggested: Option<&str>,
+) -> Result<(String, Option<String>), GczError> {
+    let (aliases, keymap) = (&config.type_aliases, &config.keymap);
+    let folded_types: Vec<String> = types.iter().map(|t| text::fold(t)).collect();
+    let folded_scopes: Vec<String> = config.scopes.iter().map(|s| text::fold(s)).collect();
+    let mut pane = Pane::Types;
+    let (mut type_input, mut scope_input) = (String::new(), String::new());
+    let mut type_selected = position_in_filter("", types, &folded_types, aliases, suggested);
+    // `None` while the typed scope itself is chosen
+    let mut scope_selected: Option<usize> = None;
+
+    loop {
+        let matching_types = filter_type_by_input(&type_input, types, &folded_types, aliases);
+        let suggestions = matching_scopes(&scope_input, &config.scopes, &folded_scopes);
+        if term::should_redraw()? {
+            let left = Column {
+                title: "Type",
+                input: &type_input,
+                items: matching_types.iter().map(|&(_, t)| t).collect(),
+                selected: type_selected,
+                focused: pane == Pane::Types,
+            };
+            let right = Column {
+                title: "Scope",
+                input: &scope_input,
+                items: suggestions.clone(),
+                selected: scope_selected,
+                focused: pane == Pane::Scopes,
+            };
+            render_panes(stdout, keymap, &left, &right)?;
+        }
+
+        let key_event = match event::read()? {
+            Event::Key(key_event) => key_event,
+            _ => continue,
+        };
+        let highlighted = type_selected
+            .and_then(|i| matching_types.get(i))
+            .map(|&(_, t)| t);
+        let input = match pane {
+            Pane::Types => &mut type_input,
+            Pane::Scopes => &mut scope_input,
+        };
+        let edited = match keymap.action(Screen::TypeAndScope, &key_event) {
+            Some(Action::Abort) => return Err(GczError::UserInterrupt),
+            Some(Action::Help) => {
+                show_help(stdout, keymap, &[Screen::TypeAndScope])?;
+                false
+            }
+            Some(Action::SwitchPane) => {
+                pane = match pane {
+                    Pane::Types => Pane::Scopes,
+                    Pane::Scopes => Pane::Types,
+                };
+                false
+            }
+            Some(action @ (Action::Up | Action::Down)) => {
+                let forward = action == Action::Down;
+                match pane {
+                    Pane::Types => {
+                        type_selected = step(type_selected, matching_types.len(), forward)
+                    }
+                    Pane::Scopes if forward && !suggestions.is_empty() => {
+                        scope_selected =
+                            Some(scope_selected.map_or(0, |i| (i + 1).min(suggestions.len() - 1)))
+                    }
+                    Pane::Scopes => scope_selected = scope_selected.and_then(|i| i.checked_sub(1)),
+                }
+                false
+            }
+            Some(Action::Confirm) => {
+                let commit_type = match highlighted {
+                    Some(commit_type) => commit_type.to_string(),
+                    None if config.custom_types && conventional::is_type_name(&type_input) => {
+                        type_input.clone()
+                    }
+                    // nothing to commit with yet
+                    None => {
+                        pane = Pane::Types;
+                        continue;
+                    }
+                };
+                let scope = match scope_selected {
+                    Some(i) => suggestions[i].to_string(),
+                    None => scope_input.trim().to_string(),
+                };
+                return Ok((commit_type, Some(scope).filter(|scope| !scope.is_empty())));
+            }
+            Some(Action::DeleteBack) => input.pop().is_some(),
+            Some(Action::Clear) => {
+                input.clear();
+                true
+            }
+            _ => match key_event.code {
+                // a scope is one word inside the parentheses
+                KeyCode::Char(c)
+                    if !key_event.modifiers.contains(KeyModifiers::CONTROL)
+                        && !c.is_whitespace()
+                        && (pane == Pane::Types || !"():".contains(c)) =>
+                {
+                    input.push(c);
+                    true
+                }
+                _ => false,
+            },
+        };
+        if edited {
+            match pane {
+                Pane::Types => {
+                    type_selected =
+                        position_in_filter(&type_input, types, &folded_types, aliases, highlighted)
+                }
+                Pane::Scopes => scope_selected = None,
+            }
+        }
+    }
+}
+
+/// One side of the type and scope screen.
+struct Column<'a> {
+    title: &'static str,
+    input: &'a str,
+    items: Vec<&'a str>,
+    selected: Option<usize>,
+    focused: bool,
+}
+
+impl Column<'_> {
+    fn width(&self) -> usize {
+        let title = text::width(self.title) + 2 + text::width(self.input);
+        let items = self.items.iter().map(|item| text::width(item)).max();
+        title.max(items.unwrap_or(0) + 2)
+    }
+
+    /// Prints row `row` of the column: the title with what was typed, then the items.
+    fn print_row(&self, stdout: &mut io::Stdout, row: usize) -> Result<(), GczError> {
+        if row == 0 {
+            let color = if self.focused {
+                Color::Cyan
+            } else {
+                Color::DarkGrey
+            };
+            execute!(
+                stdout,
+                SetForegroundColor(color),
+                Print(format!("{}: ", self.title)),
+                SetForegroundColor(Color::Reset),
+                Print(self.input)
+            )?;
+            return Ok(());
+        }
+        let Some(item) = self.items.get(row - 1) else {
+            return Ok(());
+        };
+        let color = match (self.selected == Some(row - 1), self.focused) {
+            (true, true) => Color::Green,
+            (true, false) => Color::Cyan,
+            (false, _) => Color::Reset,
+        };
+        if self.selected == Some(row - 1) {
+            execute!(
+                stdout,
+                SetForegroundColor(color),
+                Print(format!("{} ", glyph::get().pointer))
+            )?;
+        } else {
+            execute!(stdout, Print("  "))?;
+        }
+        print_match(stdout, item, &text::fold(self.input), color)
+    }
+}
+
+fn render_panes(
+    stdout: &mut io::Stdout,
+    keymap: &Keymap,
+    left: &Column,
+    right: &Column,
+) -> Result<(), GczError> {
+    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    draw_progress(stdout, Screen::TypeAndScope)?;
+    execute!(
+        stdout,
+        Print(format!(
+            "Select a type and a scope ({}: switch, {}: help)",
+            keymap.describe(Screen::TypeAndScope, Action::SwitchPane),
+            keymap.describe(Screen::TypeAndScope, Action::Help)
+        )),
+        cursor::MoveToNextLine(2)
+    )?;
+    let gap = (left.width() + 4).max(16) as u16;
+    for row in 0..=left.items.len().max(right.items.len()) {
+        left.print_row(stdout, row)?;
+        execute!(stdout, cursor::MoveToColumn(gap))?;
+        right.print_row(stdout, row)?;
+        execute!(
+            stdout,
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+    }
+    draw_status_line(stdout, keymap, Screen::TypeAndScope)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// The registered scopes containing `input`, those starting with it first. Case and
+/// diacritics are ignored; `folded` holds `scopes` through `text::fold`.
+fn matching_scopes<'a>(input: &str, scopes: &'a [String], folded: &[String]) -> Vec<&'a str> {
+    let input = text::fold(input);
+    let (mut prefixed, contained): (Vec<_>, Vec<_>) = folded
+        .iter()
+        .zip(scopes)
+        .filter(|(name, _)| name.contains(&input))
+        .partition(|(name, _)| name.starts_with(&input));
+    prefixed.extend(contained);
+    prefixed
+        .into_iter()
+        .map(|(_, scope)| scope.as_str())
+        .collect()
+}
+
+fn render_scopes(
+    stdout: &mut io::Stdout,
+    commit_type: &str,
+    input: &str,
+    suggestions: &[&str],
+    selected: Option<usize>,
+    keymap: &Keymap,
+) -> Result<(), GczError> {
+    execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    draw_progress(stdout, Screen::Scope)?;
+    execute!(
+        stdout,
+        Print(format!(
+            "Scope for {} ({}: skip when empty): {}",
+            commit_type,
+            keymap.describe(Screen::Scope, Action::Confirm),
+            input
+        )),
+        cursor::MoveToNextLine(1)
+    )?;
+    for (i, scope) in suggestions.iter().enumerate() {
+        if Some(i) == selected {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print(format!("{} {}", glyph::get().pointer, scope)),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+        } else {
+            execute!(
+                stdout,
+                Print(format!("  {}", scope)),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+    }
+    draw_status_line(stdout, keymap, Screen::Scope)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_suggest_scopes_starting_with_the_input_first() {
+        let scopes: Vec<String> = ["ui", "api", "apps", "build"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let folded: Vec<String> = scopes.iter().map(|s| text::fold(s)).collect();
+        let matching = |input: &str| matching_scopes(input, &scopes, &folded);
+
+        assert_eq!(matching(""), ["ui", "api", "apps", "build"]);
+        assert_eq!(matching("Áp"), ["api", "apps"]);
+        assert_eq!(matching("i"), ["ui", "api", "build"]);
+    }
+}
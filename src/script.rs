@@ -0,0 +1,129 @@
+use crate::config::{self, Config};
+use crate::git;
+
+/// Runs the `script` on `message`, returning the message to commit or why it was refused.
+///
+/// The script defines `gcz_message(message, ctx)`, which returns the new message, `nil`
+/// to keep it, or `false, "reason"` to veto the commit.
+pub fn apply(config: &Config, message: &str) -> Result<String, String> {
+    let path = match &config.script {
+        Some(path) => config::repo_relative(path).map_err(|err| format!("script: {}", err))?,
+        None => return Ok(message.to_string()),
+    };
+    let source = std::fs::read_to_string(&path)
+        .map_err(|err| format!("script {}: {}", path.display(), err))?;
+
+    run(
+        &source,
+        message,
+        &git::staged_files().unwrap_or_default(),
+        config.git_timeout,
+    )
+    .map_err(|reason| format!("script {}: {}", path.display(), reason))
+}
+
+#[cfg(feature = "lua")]
+fn run(source: &str, message: &str, staged: &[String], timeout: u64) -> Result<String, String> {
+    use crate::conventional;
+    use mlua::{HookTriggers, Lua, LuaOptions, StdLib, Value};
+    use std::time::{Duration, Instant};
+
+    // no io or os: a script only sees the message and its context
+    let lua = Lua::new_with(
+        StdLib::STRING | StdLib::TABLE | StdLib::MATH | StdLib::UTF8,
+        LuaOptions::default(),
+    )
+    .map_err(|err| err.to_string())?;
+
+    if timeout > 0 {
+        let deadline = Instant::now() + Duration::from_secs(timeout);
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(10_000),
+            move |_, _| {
+                if Instant::now() > deadline {
+                    return Err(mlua::Error::runtime(format!(
+                        "did not finish within {}s (see `git_timeout`)",
+                        timeout
+                    )));
+                }
+                Ok(())
+            },
+        );
+    }
+
+    let result = (|| {
+        lua.load(source).set_name("script").exec()?;
+        let function: mlua::Function = lua.globals().get("gcz_message")?;
+
+        let ctx = lua.create_table()?;
+        let (header, body) = conventional::split_message(message);
+        if let Ok(parsed) = conventional::parse_header(header) {
+            ctx.set("type", parsed.commit_type)?;
+            ctx.set("scope", parsed.scope)?;
+            ctx.set("breaking", parsed.breaking)?;
+            ctx.set("description", parsed.description)?;
+        }
+        ctx.set("header", header)?;
+        ctx.set("body", body)?;
+        ctx.set("staged", staged)?;
+
+        function.call::<_, (Value, Option<String>)>((message, ctx))
+    })();
+
+    let (value, reason) = result.map_err(|err| first_line(&err.to_string()))?;
+    let outcome = match value {
+        Value::Nil => Ok(message.to_string()),
+        Value::String(text) => Ok(text.to_str().map_err(|err| err.to_string())?.to_string()),
+        Value::Boolean(false) => Err(reason.unwrap_or_else(|| "rejected the commit".to_string())),
+        other => Err(format!(
+            "gcz_message must return a string, nil or false, got {}",
+            other.type_name()
+        )),
+    };
+    outcome
+}
+
+#[cfg(not(feature = "lua"))]
+fn run(_source: &str, _message: &str, _staged: &[String], _timeout: u64) -> Result<String, String> {
+    Err("this gcz was built without Lua support (the `lua` feature)".to_string())
+}
+
+/// Lua errors carry a traceback after the first line.
+#[cfg(feature = "lua")]
+fn first_line(error: &str) -> String {
+    error.lines().next().unwrap_or(error).to_string()
+}
+
+#[cfg(all(test, feature = "lua"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_transform_or_veto_messages() {
+        let script = r#"
+            function gcz_message(message, ctx)
+                if ctx.type == "wip" then
+                    return false, "no wip commits"
+                end
+                if ctx.scope == nil then
+                    return nil
+                end
+                return message:gsub("^(%w+)%((%w+)%)", "%1(" .. ctx.scope:upper() .. ")")
+            end
+        "#;
+
+        assert_eq!(
+            run(script, "feat(api): add login", &[], 0),
+            Ok("feat(API): add login".to_string())
+        );
+        assert_eq!(
+            run(script, "fix: typo", &[], 0),
+            Ok("fix: typo".to_string())
+        );
+        assert_eq!(
+            run(script, "wip: stuff", &[], 0),
+            Err("no wip commits".to_string())
+        );
+        assert!(run("x = ", "fix: typo", &[], 0).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use crate::config::{Config, Severity};
+use crate::lint::{self, Finding};
+use crate::{
+    commit_command, completion, emoji_for, finish_message, git, header_emoji, lock, type_prefix,
+    GczError,
+};
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+// JSON-RPC 2.0 error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Any gcz error, e.g. nothing staged or a rejected message.
+const FAILED: i64 = -32000;
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> RpcError {
+        RpcError {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<GczError> for RpcError {
+    fn from(err: GczError) -> RpcError {
+        RpcError::new(FAILED, err.to_string())
+    }
+}
+
+/// Entry point of `gcz serve --stdio`: answers JSON-RPC 2.0 requests, one JSON object per
+/// line on stdin, with one line per response on stdout, until stdin is closed.
+pub fn serve(config: &Config) -> Result<(), GczError> {
+    let mut stdout = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => respond(config, &request),
+            Err(err) => Some(response(
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, err.to_string())),
+            )),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to `request`, or `None` for a notification, which has no `id`.
+fn respond(config: &Config, request: &Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Some(response(
+            id.unwrap_or(Value::Null),
+            Err(RpcError::new(INVALID_REQUEST, "the request has no method")),
+        ));
+    };
+    let params = request.get("params").unwrap_or(&Value::Null);
+    let result = call(config, method, params);
+    id.map(|id| response(id, result))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => {
+            let mut error = json!({ "code": err.code, "message": err.message });
+            if let Some(data) = err.data {
+                error["data"] = data;
+            }
+            json!({ "jsonrpc": "2.0", "id": id, "error": error })
+        }
+    }
+}
+
+fn call(config: &Config, method: &str, params: &Value) -> Result<Value, RpcError> {
+    match method {
+        "types" => Ok(config
+            .types
+            .iter()
+            .map(|t| json!({ "name": t, "emoji": emoji_for(config, t) }))
+            .collect()),
+        "scopes" => Ok(json!(completion::known_scopes(config)?)),
+        "validate" => {
+            let findings = lint::check_commit_message(string_param(params, "message")?, config);
+            Ok(json!({ "valid": !has_errors(&findings), "findings": to_json(&findings) }))
+        }
+        "compose" => compose(config, params),
+        "commit" => commit(config, string_param(params, "message")?),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method `{}`", method),
+        )),
+    }
+}
+
+/// The message the wizard would commit for `type`, `scope`, `description` and `body`, with
+/// the emoji, co-authors and script applied, and what the checks found in it.
+fn compose(config: &Config, params: &Value) -> Result<Value, RpcError> {
+    let commit_type = string_param(params, "type")?;
+    let scope = optional_param(params, "scope")?.filter(|scope| !scope.is_empty());
+    let description = string_param(params, "description")?;
+    let body = optional_param(params, "body")?.filter(|body| !body.trim().is_empty());
+
+    let paths = git::staged_files().unwrap_or_default();
+    let emoji = header_emoji(config, commit_type, scope, &paths);
+    let mut text = format!(
+        "{}{}",
+        type_prefix(config, commit_type, scope, emoji.as_deref()),
+        description
+    );
+    if let Some(body) = body {
+        text = format!("{}\n\n{}", text, body);
+    }
+    let (message, mut findings) = finish_message(config, &text)?;
+    findings.splice(0..0, lint::check_header(&message, config));
+    Ok(json!({ "message": message, "findings": to_json(&findings) }))
+}
+
+/// Commits the index with `message` when no check reports an error.
+fn commit(config: &Config, message: &str) -> Result<Value, RpcError> {
+    if git::exist_stages_changes()?.success() {
+        return Err(GczError::NothingStaged.into());
+    }
+    let findings = lint::check_commit_message(message, config);
+    if has_errors(&findings) {
+        let mut err = RpcError::from(GczError::MessageRejected);
+        err.data = Some(json!({ "findings": to_json(&findings) }));
+        return Err(err);
+    }
+
+    let _lock = lock::acquire(false)?;
+    let output = git::try_commit(&mut commit_command(config, message, None, false), None)?;
+    Ok(json!({
+        "commit": git::resolve_commit("HEAD")?,
+        "output": String::from_utf8_lossy(&output.stdout).trim_end(),
+    }))
+}
+
+fn string_param<'a>(params: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    optional_param(params, name)?
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("`{}` is required", name)))
+}
+
+fn optional_param<'a>(params: &'a Value, name: &str) -> Result<Option<&'a str>, RpcError> {
+    match params.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value)),
+        Some(_) => Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("`{}` must be a string", name),
+        )),
+    }
+}
+
+fn has_errors(findings: &[Finding]) -> bool {
+    findings.iter().any(|f| f.severity == Severity::Error)
+}
+
+/// Findings in the shape plugins report them in.
+fn to_json(findings: &[Finding]) -> Value {
+    findings
+        .iter()
+        .map(|finding| {
+            let severity = match finding.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            json!({ "severity": severity, "message": finding.message })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_answer_requests_and_skip_notifications() {
+        let config = Config::default();
+        let answer = |request: Value| respond(&config, &request);
+
+        let types = answer(json!({ "jsonrpc": "2.0", "id": 1, "method": "types" })).unwrap();
+        assert_eq!(types["id"], 1);
+        assert_eq!(types["result"][0]["name"], "feat");
+
+        let invalid = answer(json!({ "jsonrpc": "2.0", "id": 2, "method": "validate" })).unwrap();
+        assert_eq!(invalid["error"]["code"], INVALID_PARAMS);
+
+        let unknown = answer(json!({ "jsonrpc": "2.0", "id": 3, "method": "nope" })).unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        assert!(answer(json!({ "jsonrpc": "2.0", "method": "types" })).is_none());
+    }
+}
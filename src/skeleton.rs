@@ -0,0 +1,113 @@
+use regex::Regex;
+
+/// At most this many changed functions are named per file.
+const MAX_NAMES: usize = 3;
+
+/// One bullet per file of a git-style `diff`, naming the functions its hunks touch:
+/// `- update parse_header, split_message (src/conventional.rs)`.
+pub fn from_diff(diff: &str) -> String {
+    let name_re = Regex::new(
+        r"\b(?:fn|def|func|function|class|struct|enum|impl|trait|interface|module|mod|type)\s+([A-Za-z_][A-Za-z0-9_]*)",
+    )
+    .expect("name pattern should compile");
+    let mut bullets = Vec::new();
+    let mut file: Option<FileSummary> = None;
+
+    for line in diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git a/") {
+            bullets.extend(file.take().map(FileSummary::bullet));
+            let path = paths.rsplit_once(" b/").map_or(paths, |(_, path)| path);
+            file = Some(FileSummary {
+                verb: "update",
+                path: path.to_string(),
+                renamed_from: None,
+                names: Vec::new(),
+            });
+            continue;
+        }
+        let file = match file.as_mut() {
+            Some(file) => file,
+            None => continue,
+        };
+
+        if line.starts_with("new file mode") {
+            file.verb = "add";
+        } else if line.starts_with("deleted file mode") {
+            file.verb = "remove";
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.verb = "rename";
+            file.renamed_from = Some(from.to_string());
+        } else if let Some(context) = line.strip_prefix("@@").and_then(|l| l.split("@@").nth(1)) {
+            if let Some(name) = name_re.captures(context).map(|c| c[1].to_string()) {
+                if !file.names.contains(&name) {
+                    file.names.push(name);
+                }
+            }
+        }
+    }
+    bullets.extend(file.map(FileSummary::bullet));
+    bullets.join("\n")
+}
+
+struct FileSummary {
+    verb: &'static str,
+    path: String,
+    renamed_from: Option<String>,
+    names: Vec<String>,
+}
+
+impl FileSummary {
+    fn bullet(self) -> String {
+        if let Some(from) = self.renamed_from {
+            return format!("- rename {} to {}", from, self.path);
+        }
+        if self.verb != "update" || self.names.is_empty() {
+            return format!("- {} {}", self.verb, self.path);
+        }
+
+        let mut names = self.names[..self.names.len().min(MAX_NAMES)].join(", ");
+        if self.names.len() > MAX_NAMES {
+            names.push_str(&format!(" and {} more", self.names.len() - MAX_NAMES));
+        }
+        format!("- update {} ({})", names, self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_list_one_bullet_per_file() {
+        let diff = "diff --git a/src/parser.rs b/src/parser.rs\n\
+                    index 1111111..2222222 100644\n\
+                    --- a/src/parser.rs\n\
+                    +++ b/src/parser.rs\n\
+                    @@ -10,0 +11,2 @@ pub fn parse_header(header: &str) {\n\
+                    +    let x = 1;\n\
+                    @@ -40 +42 @@ pub fn parse_header(header: &str) {\n\
+                    +    x\n\
+                    @@ -80 +82 @@ impl Parser {\n\
+                    +    y\n\
+                    diff --git a/README.md b/README.md\n\
+                    --- a/README.md\n\
+                    +++ b/README.md\n\
+                    @@ -1 +1 @@\n\
+                    -old\n\
+                    +new\n\
+                    diff --git a/src/new.rs b/src/new.rs\n\
+                    new file mode 100644\n\
+                    --- /dev/null\n\
+                    +++ b/src/new.rs\n\
+                    @@ -0,0 +1 @@\n\
+                    +fn main() {}\n";
+
+        assert_eq!(
+            from_diff(diff),
+            "- update parse_header, Parser (src/parser.rs)\n\
+             - update README.md\n\
+             - add src/new.rs"
+        );
+        assert_eq!(from_diff(""), "");
+    }
+}
@@ -0,0 +1,334 @@
+use crate::config::{self, Config, Severity};
+use crate::lint::Finding;
+use crate::{conventional, git, GczError};
+use crossterm::execute;
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetForegroundColor};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// The bundled English words, one per line from the most common, so suggestions favour
+/// everyday words.
+const WORDS: &str = include_str!("words.txt");
+
+/// Shorter words aren't checked.
+const MIN_LENGTH: usize = 3;
+
+/// Endings taken off a word, and what goes back in their place, to find it in the list.
+const SUFFIXES: &[(&str, &str)] = &[
+    ("s", ""),
+    ("es", ""),
+    ("ies", "y"),
+    ("ed", ""),
+    ("ed", "e"),
+    ("ied", "y"),
+    ("ing", ""),
+    ("ing", "e"),
+    ("er", ""),
+    ("er", "e"),
+    ("ers", ""),
+    ("ers", "e"),
+    ("est", ""),
+    ("ly", ""),
+    ("ily", "y"),
+    ("ness", ""),
+    ("ment", ""),
+    ("able", ""),
+    ("able", "e"),
+    ("less", ""),
+];
+
+const PREFIXES: &[&str] = &[
+    "re", "un", "pre", "non", "de", "dis", "mis", "sub", "over", "under", "auto", "multi", "co",
+];
+
+fn bundled() -> &'static HashSet<&'static str> {
+    static BUNDLED: OnceLock<HashSet<&str>> = OnceLock::new();
+    BUNDLED.get_or_init(|| WORDS.lines().collect())
+}
+
+/// Checks messages against the bundled words and the ones this repository adds.
+pub struct Speller {
+    extra: HashSet<String>,
+}
+
+impl Speller {
+    /// The speller for `spell_check`, or `None` when it is off. Besides the bundled words it
+    /// knows the types and scopes, the words in `dictionary` and every word of the staged
+    /// diff, so the names of the code being committed pass.
+    pub fn load(config: &Config) -> Result<Option<Speller>, GczError> {
+        if !config.spell_check {
+            return Ok(None);
+        }
+        let mut extra: HashSet<String> = config
+            .types
+            .iter()
+            .chain(&config.scopes)
+            .map(|word| word.to_lowercase())
+            .collect();
+        if let Some(path) = &config.dictionary {
+            match fs::read_to_string(config::repo_relative(path)?) {
+                Ok(words) => extra.extend(
+                    words
+                        .lines()
+                        .map(str::trim)
+                        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+                        .map(str::to_lowercase),
+                ),
+                // a dictionary nobody has added to yet
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        // nothing staged, as in `gcz reword`, or not a git repository
+        if let Ok(diff) = git::staged_diff() {
+            let word = Regex::new("[A-Za-z]{3,}").expect("word pattern should compile");
+            extra.extend(word.find_iter(&diff).map(|m| m.as_str().to_lowercase()));
+        }
+        Ok(Some(Speller { extra }))
+    }
+
+    fn knows(&self, word: &str) -> bool {
+        stems(&word.to_lowercase())
+            .iter()
+            .any(|stem| bundled().contains(stem.as_str()) || self.extra.contains(stem))
+    }
+
+    /// Byte ranges of the words in `message` that aren't known.
+    pub fn misspelled(&self, message: &str) -> Vec<Range<usize>> {
+        words(message)
+            .into_iter()
+            .filter(|range| !self.knows(&message[range.clone()]))
+            .collect()
+    }
+
+    /// A warning for each misspelled word, with the closest known word when there is one.
+    pub fn findings(&self, message: &str) -> Vec<Finding> {
+        let mut seen = HashSet::new();
+        self.misspelled(message)
+            .into_iter()
+            .map(|range| &message[range])
+            .filter(|word| seen.insert(word.to_lowercase()))
+            .map(|word| Finding {
+                severity: Severity::Warning,
+                message: match suggestion(word) {
+                    Some(suggestion) => {
+                        format!("possible misspelling \"{}\" ({}?)", word, suggestion)
+                    }
+                    None => format!("possible misspelling \"{}\"", word),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Byte ranges of the prose words of `message`. Code is left out: anything in backticks,
+/// fenced or indented blocks, names with digits, underscores, dots, slashes or inner capitals,
+/// URLs, and the type, scope and trailers.
+fn words(message: &str) -> Vec<Range<usize>> {
+    static CHUNK: OnceLock<Regex> = OnceLock::new();
+    let chunk =
+        CHUNK.get_or_init(|| Regex::new(r"`[^`]*`|\S+").expect("chunk pattern should compile"));
+
+    let end = trailers_start(message);
+    let mut ranges = Vec::new();
+    let mut line_start = 0;
+    let mut fenced = false;
+    for (i, line) in message[..end].split('\n').enumerate() {
+        let start = line_start;
+        line_start += line.len() + 1;
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced || line.starts_with('#') || line.starts_with("    ") || line.starts_with('\t') {
+            continue;
+        }
+        let skipped = if i == 0 {
+            conventional::split_prefix(line).0.len()
+        } else {
+            0
+        };
+
+        for m in chunk.find_iter(&line[skipped..]) {
+            let text = m.as_str();
+            if text.contains(['`', '@']) || text.contains("://") {
+                continue;
+            }
+            let trimmed = text.trim_start_matches(|c: char| !c.is_alphanumeric());
+            let offset = start + skipped + m.start() + text.len() - trimmed.len();
+            let trimmed = trimmed.trim_end_matches(|c: char| !c.is_alphanumeric());
+            // numbers, identifiers, paths and other scripts
+            if !trimmed
+                .chars()
+                .all(|c| c.is_ascii_alphabetic() || c == '-' || c == '\'' || c == '’')
+            {
+                continue;
+            }
+
+            let mut part_start = offset;
+            for part in trimmed.split('-') {
+                // `user's` and `doesn't` are checked as `user` and `doesn`
+                let word = part.split(['\'', '’']).next().unwrap_or_default();
+                let inner_capital = word.chars().skip(1).any(|c| c.is_ascii_uppercase());
+                if word.len() >= MIN_LENGTH && !inner_capital {
+                    ranges.push(part_start..part_start + word.len());
+                }
+                part_start += part.len() + 1;
+            }
+        }
+    }
+    ranges
+}
+
+/// Where the trailing paragraph of `Key: value` trailers starts, or the end of `message`.
+fn trailers_start(message: &str) -> usize {
+    static TRAILER: OnceLock<Regex> = OnceLock::new();
+    let trailer = TRAILER.get_or_init(|| {
+        Regex::new(r"^([A-Za-z][A-Za-z0-9-]*|BREAKING CHANGE): ")
+            .expect("trailer pattern should compile")
+    });
+
+    let trimmed = message.trim_end();
+    match trimmed.rfind("\n\n") {
+        Some(blank)
+            if trimmed[blank + 2..]
+                .lines()
+                .all(|line| trailer.is_match(line)) =>
+        {
+            blank
+        }
+        _ => message.len(),
+    }
+}
+
+/// `word` and what it may be an inflection of, e.g. `stopped`, `stopp`, `stop` and `stope`.
+fn stems(word: &str) -> Vec<String> {
+    let mut stems = suffix_stems(word);
+    for prefix in PREFIXES {
+        // long enough that `untill` isn't taken for `un` and `till`
+        if let Some(rest) = word
+            .strip_prefix(prefix)
+            .filter(|rest| rest.len() > MIN_LENGTH + 1)
+        {
+            stems.extend(suffix_stems(rest));
+        }
+    }
+    stems
+}
+
+fn suffix_stems(word: &str) -> Vec<String> {
+    let mut stems = vec![word.to_string()];
+    for (suffix, replacement) in SUFFIXES {
+        let Some(stem) = word.strip_suffix(suffix).filter(|stem| stem.len() >= 2) else {
+            continue;
+        };
+        stems.push(format!("{}{}", stem, replacement));
+        // stopped, running, bigger
+        let bytes = stem.as_bytes();
+        if bytes.len() > 2 && bytes[bytes.len() - 1] == bytes[bytes.len() - 2] {
+            stems.push(stem[..stem.len() - 1].to_string());
+        }
+    }
+    stems
+}
+
+/// The most common bundled word at most two edits away from `word`.
+fn suggestion(word: &str) -> Option<&'static str> {
+    let word = word.to_lowercase();
+    let mut best = None;
+    for candidate in WORDS.lines() {
+        if candidate.len().abs_diff(word.len()) > 2 {
+            continue;
+        }
+        let distance = edit_distance(&word, candidate);
+        if distance <= 2 && best.is_none_or(|(best, _)| distance < best) {
+            best = Some((distance, candidate));
+        }
+    }
+    best.map(|(_, candidate)| candidate)
+}
+
+/// Edits that turn `a` into `b`, counting swapping two neighbours as one.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Prints `line`, which starts at byte `start` of the text `marks` point into, with the
+/// marked words underlined; `color` is the color of the rest of the line.
+pub fn print_marked(
+    stdout: &mut io::Stdout,
+    line: &str,
+    start: usize,
+    marks: &[Range<usize>],
+    color: Color,
+) -> io::Result<()> {
+    let end = start + line.len();
+    let mut printed = 0;
+    for mark in marks
+        .iter()
+        .filter(|mark| mark.start < end && mark.end > start)
+    {
+        let from = mark.start.max(start) - start;
+        let to = mark.end.min(end) - start;
+        execute!(
+            stdout,
+            Print(&line[printed..from]),
+            SetAttribute(Attribute::Underlined),
+            SetForegroundColor(Color::Yellow),
+            Print(&line[from..to]),
+            SetForegroundColor(color),
+            SetAttribute(Attribute::NoUnderline)
+        )?;
+        printed = to;
+    }
+    execute!(stdout, Print(&line[printed..]))?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_flag_prose_but_not_code() {
+        let speller = Speller {
+            extra: HashSet::from(["gcz".to_string()]),
+        };
+        let message = "fix(api): recieve the `hdr_lenght` in parse_hedaer\n\n\
+                       Teh stopped job's retries weren't re-queued by gcz, see \
+                       https://exmaple.com and fooBar.\n\n\
+                       Co-authored-by: Jhon <jhon@example.com>";
+        let misspelled: Vec<&str> = speller
+            .misspelled(message)
+            .into_iter()
+            .map(|range| &message[range])
+            .collect();
+        assert_eq!(misspelled, ["recieve", "Teh"]);
+
+        let findings = speller.findings(message);
+        assert_eq!(
+            findings[0].message,
+            "possible misspelling \"recieve\" (receive?)"
+        );
+        assert_eq!(findings[1].message, "possible misspelling \"Teh\" (the?)");
+    }
+}
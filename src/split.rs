@@ -0,0 +1,399 @@
+use crate::config::Config;
+use crate::keymap::{Action, Keymap, Screen};
+use crate::term::{self, enable_raw_mode};
+use crate::{
+    commit, compose_message, draw_status_line, finalize, git, glyph, jj, show_help, start_message,
+    GczError, UntrackedFiles,
+};
+use crossterm::{
+    cursor,
+    event::{self, Event},
+    execute,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{self, Clear, ClearType},
+};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A staged file offered for the next commit of the split.
+#[derive(Debug)]
+struct FileChanges {
+    path: String,
+    /// The diff lines before the first hunk (`diff --git`, `index`, `---`, `+++`).
+    header: String,
+    hunks: Vec<Hunk>,
+    /// Whether a file without hunks (binary, mode change, empty) is included.
+    included: bool,
+    expanded: bool,
+}
+
+#[derive(Debug)]
+struct Hunk {
+    text: String,
+    included: bool,
+}
+
+impl FileChanges {
+    fn parse(path: String, diff: &str) -> FileChanges {
+        let mut header = String::new();
+        let mut hunks: Vec<Hunk> = Vec::new();
+
+        for line in diff.split_inclusive('\n') {
+            if line.starts_with("@@") {
+                hunks.push(Hunk {
+                    text: line.to_string(),
+                    included: false,
+                });
+            } else {
+                match hunks.last_mut() {
+                    Some(hunk) => hunk.text.push_str(line),
+                    None => header.push_str(line),
+                }
+            }
+        }
+        FileChanges {
+            path,
+            header,
+            hunks,
+            included: false,
+            expanded: false,
+        }
+    }
+
+    fn included_hunks(&self) -> usize {
+        self.hunks.iter().filter(|hunk| hunk.included).count()
+    }
+
+    fn is_fully_included(&self) -> bool {
+        if self.hunks.is_empty() {
+            self.included
+        } else {
+            self.included_hunks() == self.hunks.len()
+        }
+    }
+
+    fn is_left_out(&self) -> bool {
+        if self.hunks.is_empty() {
+            !self.included
+        } else {
+            self.included_hunks() == 0
+        }
+    }
+
+    /// Includes the whole file unless it already is, in which case it is left out.
+    fn toggle(&mut self) {
+        let include = !self.is_fully_included();
+        self.included = include;
+        for hunk in &mut self.hunks {
+            hunk.included = include;
+        }
+    }
+
+    /// The file's header followed by the included hunks, for `git apply`.
+    fn patch(&self) -> String {
+        self.hunks
+            .iter()
+            .filter(|hunk| hunk.included)
+            .fold(self.header.clone(), |patch, hunk| patch + &hunk.text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    File(usize),
+    Hunk(usize, usize),
+}
+
+fn rows(files: &[FileChanges]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        rows.push(Row::File(i));
+        if file.expanded {
+            rows.extend((0..file.hunks.len()).map(|j| Row::Hunk(i, j)));
+        }
+    }
+    rows
+}
+
+/// Entry point of `gcz split`: commits the staged changes in several rounds of
+/// "choose files or hunks, write the message", until nothing staged is left.
+pub fn split(stdout: &mut io::Stdout, config: &Config) -> Result<(), GczError> {
+    if jj::workspace_root().is_some() {
+        println!("jj has no staged changes to split; use `jj split` instead");
+        return Ok(());
+    }
+    if !git::is_inside_git_dir()?.stdout.starts_with(b"true") {
+        return Err(GczError::NotARepository);
+    }
+
+    let index = git::git_path("GCZ_SPLIT_INDEX")?;
+    let mut commits = 0;
+    loop {
+        let mut files: Vec<FileChanges> = git::staged_file_diffs()?
+            .into_iter()
+            .map(|(path, diff)| FileChanges::parse(path, &diff))
+            .collect();
+        if files.is_empty() {
+            if commits == 0 {
+                return Err(GczError::NothingStaged);
+            }
+            println!("Committed the staged changes in {} commits", commits);
+            return Ok(());
+        }
+
+        select_changes(stdout, &mut files, &config.keymap, commits + 1)?;
+        let paths: Vec<String> = files
+            .iter()
+            .filter(|file| !file.is_left_out())
+            .map(|file| file.path.clone())
+            .collect();
+
+        let mut untracked = UntrackedFiles::new(Vec::new());
+        let (text, cursor) = start_message(stdout, config, &mut untracked, &paths)?;
+        let diff: String = files
+            .iter()
+            .filter(|file| !file.is_left_out())
+            .map(FileChanges::patch)
+            .collect();
+        let message =
+            compose_message(stdout, config, text, cursor, &[], Some(&diff), None, false)?.message;
+
+        let result = if files.iter().all(FileChanges::is_fully_included) {
+            commit(config, &message, None, false)
+        } else {
+            let index = TempIndex(index.clone());
+            if !prepare_index(&index.0, &files)? {
+                println!(
+                    "Could not build the commit from the selected hunks; nothing was committed"
+                );
+                return Ok(());
+            }
+            commit(config, &message, Some(&index.0), false)
+        };
+        if let Err(err) = result {
+            println!("The remaining changes are still staged");
+            return Err(err);
+        }
+        commits += 1;
+    }
+}
+
+/// The index copy of one round, removed however the round ends.
+struct TempIndex(PathBuf);
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Writes a copy of the index holding only the included changes. The real index is left
+/// alone: once the copy is committed, what it still has on top of `HEAD` is the rest.
+fn prepare_index(index: &Path, files: &[FileChanges]) -> Result<bool, GczError> {
+    fs::copy(git::git_path("index")?, index)?;
+
+    let reset: Vec<String> = files
+        .iter()
+        .filter(|file| !file.is_fully_included())
+        .map(|file| file.path.clone())
+        .collect();
+    if !git::reset_in_index(index, &reset)?.success() {
+        return Ok(false);
+    }
+
+    for file in files
+        .iter()
+        .filter(|file| !file.is_fully_included() && !file.is_left_out())
+    {
+        let output = git::apply_to_index(index, &file.patch())?;
+        if !output.status.success() {
+            io::stderr().write_all(&output.stderr)?;
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Lets the user pick the files and hunks of commit number `round`.
+fn select_changes(
+    stdout: &mut io::Stdout,
+    files: &mut [FileChanges],
+    keymap: &Keymap,
+    round: usize,
+) -> Result<(), GczError> {
+    let mut selected = 0;
+
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    loop {
+        let rows = rows(files);
+        if term::should_redraw()? {
+            render_changes(stdout, files, &rows, selected, keymap, round)?;
+        }
+
+        let key_event = match event::read()? {
+            Event::Key(key_event) => key_event,
+            _ => continue,
+        };
+        match (keymap.action(Screen::Split, &key_event), rows[selected]) {
+            (Some(Action::Abort), _) => return Err(GczError::UserInterrupt),
+            (Some(Action::Help), _) => show_help(stdout, keymap, &[Screen::Split])?,
+            (Some(Action::Up), _) if selected > 0 => selected -= 1,
+            (Some(Action::Down), _) if selected + 1 < rows.len() => selected += 1,
+            (Some(Action::Right), Row::File(i)) if files[i].hunks.len() > 1 => {
+                files[i].expanded = true
+            }
+            (Some(Action::Left), Row::File(i)) => files[i].expanded = false,
+            (Some(Action::Left), Row::Hunk(i, _)) => {
+                files[i].expanded = false;
+                selected = rows
+                    .iter()
+                    .position(|row| *row == Row::File(i))
+                    .unwrap_or(0);
+            }
+            (Some(Action::ToggleFile), Row::File(i)) => files[i].toggle(),
+            (Some(Action::ToggleFile), Row::Hunk(i, j)) => {
+                files[i].hunks[j].included = !files[i].hunks[j].included
+            }
+            (Some(Action::Confirm), _) if !files.iter().all(FileChanges::is_left_out) => {
+                return finalize((), stdout)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_changes(
+    stdout: &mut io::Stdout,
+    files: &[FileChanges],
+    rows: &[Row],
+    selected: usize,
+    keymap: &Keymap,
+    round: usize,
+) -> Result<(), GczError> {
+    // some embedded terminals report a zero size
+    let width = match terminal::size()? {
+        (0, _) => 80,
+        (width, _) => width as usize,
+    };
+
+    execute!(
+        stdout,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        Print(format!(
+            "Commit {}: choose the changes to include ({}: include, {}: hunks, {}: continue)",
+            round,
+            keymap.describe(Screen::Split, Action::ToggleFile),
+            keymap.describe(Screen::Split, Action::Right),
+            keymap.describe(Screen::Split, Action::Confirm)
+        )),
+        cursor::MoveToNextLine(1)
+    )?;
+
+    for (row_index, row) in rows.iter().enumerate() {
+        let line = match *row {
+            Row::File(i) => {
+                let file = &files[i];
+                let mark = if file.is_fully_included() {
+                    "x"
+                } else if file.is_left_out() {
+                    " "
+                } else {
+                    "~"
+                };
+                let hunks = match file.hunks.len() {
+                    0 | 1 => String::new(),
+                    n => format!(" ({}/{} hunks)", file.included_hunks(), n),
+                };
+                format!("[{}] {}{}", mark, file.path, hunks)
+            }
+            Row::Hunk(i, j) => {
+                let hunk = &files[i].hunks[j];
+                let mark = if hunk.included { "x" } else { " " };
+                format!("    [{}] {}", mark, hunk_summary(&hunk.text))
+            }
+        };
+        let line: String = line.graphemes(true).take(width.saturating_sub(3)).collect();
+
+        if row_index == selected {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print(format!("{} {}", glyph::get().pointer, line)),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+        } else {
+            execute!(
+                stdout,
+                Print(format!("  {}", line)),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+    }
+    draw_status_line(stdout, keymap, Screen::Split)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// The hunk's line range followed by its first changed line.
+fn hunk_summary(hunk: &str) -> String {
+    let mut lines = hunk.lines();
+    let range = lines
+        .next()
+        .and_then(|header| header.split("@@").nth(1))
+        .unwrap_or("")
+        .trim();
+    let change = lines
+        .find(|line| line.starts_with('+') || line.starts_with('-'))
+        .unwrap_or("");
+    format!("{}  {}", range, change.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_a_patch_from_the_included_hunks() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n\
+                    index 1111111..2222222 100644\n\
+                    --- a/src/lib.rs\n\
+                    +++ b/src/lib.rs\n\
+                    @@ -1,3 +1,3 @@\n \
+                    a\n\
+                    -b\n\
+                    +B\n \
+                    c\n\
+                    @@ -10,3 +10,3 @@ fn main() {\n \
+                    x\n\
+                    -y\n\
+                    +Y\n \
+                    z\n";
+        let mut file = FileChanges::parse("src/lib.rs".to_string(), diff);
+        assert_eq!(file.hunks.len(), 2);
+        assert!(file.is_left_out());
+
+        file.hunks[1].included = true;
+        assert!(!file.is_left_out() && !file.is_fully_included());
+        assert_eq!(
+            file.patch(),
+            "diff --git a/src/lib.rs b/src/lib.rs\n\
+             index 1111111..2222222 100644\n\
+             --- a/src/lib.rs\n\
+             +++ b/src/lib.rs\n\
+             @@ -10,3 +10,3 @@ fn main() {\n \
+             x\n\
+             -y\n\
+             +Y\n \
+             z\n"
+        );
+        assert_eq!(hunk_summary(&file.hunks[1].text), "-10,3 +10,3  -y");
+
+        file.toggle();
+        assert!(file.is_fully_included());
+    }
+}
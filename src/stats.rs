@@ -0,0 +1,125 @@
+use crate::config::Config;
+use crate::{git, GczError};
+
+/// Prints, after a commit, how many commits the user made today and how many days in a row,
+/// the commits on this branch, and where it stands against its upstream. Nothing is printed
+/// when `commit_stats` is off, and a git failure only leaves lines out: the commit is made.
+pub fn print(config: &Config) {
+    if !config.commit_stats {
+        return;
+    }
+    if let Ok(Some(line)) = today() {
+        println!("{}", line);
+    }
+    if let Ok(Some(line)) = branch() {
+        println!("{}", line);
+    }
+    if let Ok(Some(line)) = upstream() {
+        println!("{}", line);
+    }
+}
+
+fn today() -> Result<Option<String>, GczError> {
+    let Some(email) = git::config_value("user.email")? else {
+        return Ok(None);
+    };
+    let dates = git::commit_dates(&email)?;
+    // the commit just made is today's
+    let Some(today) = dates.first() else {
+        return Ok(None);
+    };
+    let count = dates.iter().filter(|date| *date == today).count();
+    let mut line = match count {
+        1 => "1 commit today".to_string(),
+        count => format!("{} commits today", count),
+    };
+    let days = streak(&dates);
+    if days > 1 {
+        line.push_str(&format!(", {} days in a row", days));
+    }
+    Ok(Some(line))
+}
+
+/// Commits since the branch left the default branch.
+fn branch() -> Result<Option<String>, GczError> {
+    let (Some(branch), Some(base)) = (git::current_branch()?, git::default_branch()?) else {
+        return Ok(None);
+    };
+    if base.rsplit('/').next() == Some(branch.as_str()) {
+        return Ok(None);
+    }
+    Ok(match git::ahead_behind(&base)?.0 {
+        0 => None,
+        1 => Some(format!("1 commit on {} since {}", branch, base)),
+        count => Some(format!("{} commits on {} since {}", count, branch, base)),
+    })
+}
+
+fn upstream() -> Result<Option<String>, GczError> {
+    let Some(upstream) = git::upstream()? else {
+        return Ok(Some(
+            "No upstream yet; the branch hasn't been pushed".to_string(),
+        ));
+    };
+    let line = match git::ahead_behind(&upstream)? {
+        (0, 0) => format!("Up to date with {}", upstream),
+        (ahead, 0) => format!("{} ahead of {}; push when ready", ahead, upstream),
+        (0, behind) => format!("{} behind {}", behind, upstream),
+        (ahead, behind) => format!(
+            "{} ahead and {} behind {}; pull before pushing",
+            ahead, behind, upstream
+        ),
+    };
+    Ok(Some(line))
+}
+
+/// The days in a row with commits, ending with the newest of `dates` (`YYYY-MM-DD`,
+/// newest first).
+fn streak(dates: &[String]) -> usize {
+    let mut days: Vec<i64> = dates.iter().filter_map(|date| day_number(date)).collect();
+    days.dedup();
+    let Some(&first) = days.first() else {
+        return 0;
+    };
+    days.iter()
+        .enumerate()
+        .take_while(|(i, day)| **day == first - *i as i64)
+        .count()
+}
+
+/// Days since 1970-01-01 of a `YYYY-MM-DD` date, in the proleptic Gregorian calendar.
+fn day_number(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146097 + day_of_era - 719468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_days_in_a_row() {
+        let dates = |dates: &[&str]| dates.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(day_number("1970-01-01"), Some(0));
+        assert_eq!(day_number("2024-03-01"), Some(19783));
+        assert_eq!(
+            streak(&dates(&[
+                "2024-03-01",
+                "2024-03-01",
+                "2024-02-29",
+                "2024-02-28",
+                "2024-02-26"
+            ])),
+            3
+        );
+        assert_eq!(streak(&dates(&["2024-03-01", "2024-02-27"])), 1);
+        assert_eq!(streak(&[]), 0);
+    }
+}
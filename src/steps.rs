@@ -0,0 +1,116 @@
+use crate::config::Config;
+use crate::glyph;
+use crate::keymap::Screen;
+use std::sync::{Mutex, MutexGuard};
+
+/// The screens of the running wizard, in order; empty outside it, where the same screens
+/// serve other commands.
+static STEPS: Mutex<Vec<Screen>> = Mutex::new(Vec::new());
+
+/// Held while the wizard runs; its steps are forgotten when this is dropped.
+pub struct Progress;
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        steps().clear();
+    }
+}
+
+/// Starts numbering the steps `config` turns on.
+pub fn start(config: &Config) -> Progress {
+    *steps() = enabled(config);
+    Progress
+}
+
+fn enabled(config: &Config) -> Vec<Screen> {
+    let mut steps = match (config.ask_scope, config.scope_pane) {
+        (true, true) => vec![Screen::TypeAndScope],
+        (true, false) => vec![Screen::TypeSelect, Screen::Scope],
+        (false, _) => vec![Screen::TypeSelect],
+    };
+    if config.emoji == Some(true) && config.emoji_picker {
+        steps.push(Screen::Emoji);
+    }
+    steps.push(Screen::Message);
+    if !config.trailer_presets.is_empty() {
+        steps.push(Screen::Trailers);
+    }
+    if config.confirm {
+        steps.push(Screen::Confirm);
+    }
+    steps
+}
+
+/// Whether the running wizard has `screen`.
+pub fn includes(screen: Screen) -> bool {
+    steps().contains(&screen)
+}
+
+/// Where `screen` is in the wizard and what follows it, or `None` outside the wizard.
+pub fn breadcrumb(screen: Screen) -> Option<String> {
+    format(&steps(), screen)
+}
+
+/// E.g. `Step 2/4 — Scope → Message → Confirmation`.
+fn format(steps: &[Screen], screen: Screen) -> Option<String> {
+    let position = steps.iter().position(|step| *step == screen)?;
+    let glyphs = glyph::get();
+    let mut line = format!(
+        "Step {}/{} {} {}",
+        position + 1,
+        steps.len(),
+        glyphs.dash,
+        screen.title()
+    );
+    for next in &steps[position + 1..] {
+        line.push_str(&format!(" {} {}", glyphs.arrow, next.title()));
+    }
+    Some(line)
+}
+
+fn steps() -> MutexGuard<'static, Vec<Screen>> {
+    STEPS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_number_the_enabled_steps() {
+        let config = Config {
+            ask_scope: true,
+            emoji: Some(false),
+            ..Config::default()
+        };
+        let steps = enabled(&config);
+
+        assert_eq!(
+            format(&steps, Screen::Scope).as_deref(),
+            Some("Step 2/4 — Scope → Message → Confirmation")
+        );
+        assert_eq!(
+            format(&steps, Screen::Confirm).as_deref(),
+            Some("Step 4/4 — Confirmation")
+        );
+        assert_eq!(format(&steps, Screen::Emoji), None);
+
+        let config = Config {
+            scope_pane: true,
+            ..config
+        };
+        assert_eq!(
+            format(&enabled(&config), Screen::TypeAndScope).as_deref(),
+            Some("Step 1/3 — Type and scope → Message → Confirmation")
+        );
+
+        let config = Config {
+            trailer_presets: vec!["Refs: #{}".to_string()],
+            ..config
+        };
+        assert_eq!(
+            format(&enabled(&config), Screen::Trailers).as_deref(),
+            Some("Step 3/4 — Trailers → Confirmation")
+        );
+    }
+}
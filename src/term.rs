@@ -0,0 +1,78 @@
+use crossterm::event::{
+    self, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::{execute, terminal};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// One frame of a 60 Hz display: the shortest time between two redraws.
+const FRAME: Duration = Duration::from_millis(16);
+/// The longest time between two redraws while keys keep arriving, so a long paste shows progress.
+const MAX_REDRAW_DELAY: Duration = Duration::from_millis(100);
+
+/// Whether to ask the terminal for the kitty keyboard protocol. Set once from the config.
+static KEYBOARD_PROTOCOL: AtomicBool = AtomicBool::new(true);
+/// Whether the screens show their keys at the bottom. Set once from the config.
+static STATUS_LINE: AtomicBool = AtomicBool::new(true);
+/// Whether the flags are currently pushed, so they are popped exactly once.
+static PUSHED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_keyboard_protocol(enabled: bool) {
+    KEYBOARD_PROTOCOL.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_status_line(enabled: bool) {
+    STATUS_LINE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn status_line() -> bool {
+    STATUS_LINE.load(Ordering::Relaxed)
+}
+
+/// Enters raw mode. On terminals that speak the kitty keyboard protocol, keys such as
+/// Ctrl+Enter, Shift+Enter or Ctrl+Backspace are then told apart from their plain versions;
+/// elsewhere they arrive as they always have.
+pub fn enable_raw_mode() -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    if KEYBOARD_PROTOCOL.load(Ordering::Relaxed)
+        && supports_keyboard_protocol()
+        && !PUSHED.swap(true, Ordering::Relaxed)
+    {
+        execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    Ok(())
+}
+
+/// Leaves raw mode, restoring the legacy key encoding for the editor and git hooks.
+pub fn disable_raw_mode() -> io::Result<()> {
+    if PUSHED.swap(false, Ordering::Relaxed) {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
+    }
+    terminal::disable_raw_mode()
+}
+
+/// Whether a screen should be drawn before reading the next key. Keys that are already
+/// queued, as from a paste or a held arrow key, are handled first and drawn together, so
+/// a slow connection isn't sent every intermediate state of the screen.
+pub fn should_redraw() -> io::Result<bool> {
+    static LAST_REDRAW: Mutex<Option<Instant>> = Mutex::new(None);
+    let mut last_redraw = LAST_REDRAW.lock().unwrap_or_else(|e| e.into_inner());
+
+    let since = last_redraw.map_or(MAX_REDRAW_DELAY, |last| last.elapsed());
+    if since < MAX_REDRAW_DELAY && event::poll(FRAME.saturating_sub(since))? {
+        return Ok(false);
+    }
+    *last_redraw = Some(Instant::now());
+    Ok(true)
+}
+
+/// Asks the terminal once; one that doesn't answer is treated as a legacy terminal.
+fn supports_keyboard_protocol() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| terminal::supports_keyboard_enhancement().unwrap_or(false))
+}
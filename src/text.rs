@@ -0,0 +1,471 @@
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Columns between tab stops when tabs are expanded, as in `git log --expand-tabs`.
+const TAB_WIDTH: usize = 8;
+
+/// Whether the terminal measures each code point on its own. Set once from `emoji_width`.
+static LEGACY_WIDTH: AtomicBool = AtomicBool::new(false);
+
+/// Accented Latin letters, lowercased, and the letter they fold to.
+const FOLDS: &[(&str, char)] = &[
+    ("àáâãäåāăą", 'a'),
+    ("çćĉċč", 'c'),
+    ("ďđ", 'd'),
+    ("èéêëēĕėęě", 'e'),
+    ("ĝğġģ", 'g'),
+    ("ĥħ", 'h'),
+    ("ìíîïĩīĭįı", 'i'),
+    ("ĵ", 'j'),
+    ("ķ", 'k'),
+    ("ĺļľŀł", 'l'),
+    ("ñńņňŉ", 'n'),
+    ("òóôõöøōŏő", 'o'),
+    ("ŕŗř", 'r'),
+    ("śŝşšș", 's'),
+    ("ţťŧț", 't'),
+    ("ùúûüũūŭůűų", 'u'),
+    ("ŵ", 'w'),
+    ("ýÿŷ", 'y'),
+    ("źżž", 'z'),
+];
+
+/// `s` lowercased and without diacritics, for matching what was typed against names:
+/// `Fëat` and `FEAT` both fold to `feat`.
+pub fn fold(s: &str) -> String {
+    s.chars().flat_map(fold_char).collect()
+}
+
+fn fold_char(c: char) -> impl Iterator<Item = char> {
+    c.to_lowercase()
+        // combining diacritical marks, as in a decomposed `é`
+        .filter(|c| !('\u{300}'..='\u{36f}').contains(c))
+        .map(|c| {
+            FOLDS
+                .iter()
+                .find(|(accented, _)| accented.contains(c))
+                .map_or(c, |&(_, base)| base)
+        })
+}
+
+pub fn set_legacy_width(legacy: bool) {
+    LEGACY_WIDTH.store(legacy, Ordering::Relaxed);
+}
+
+/// Columns `s` takes on the terminal.
+pub fn width(s: &str) -> usize {
+    s.graphemes(true).map(grapheme_width).sum()
+}
+
+/// Columns one grapheme takes on the terminal, as `emoji_width` says it draws them.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    measure(grapheme, LEGACY_WIDTH.load(Ordering::Relaxed))
+}
+
+/// Modern terminals give an emoji sequence (`♻️`, `👩‍💻`, `🇯🇵`, `1️⃣`, `👍🏽`) two columns
+/// as Unicode says. `legacy` ones add up the `wcwidth` of its code points, ignoring the
+/// variation selector and joiner, so `♻️` takes one column and `👩‍💻` four.
+fn measure(grapheme: &str, legacy: bool) -> usize {
+    if legacy {
+        grapheme.chars().filter_map(|c| c.width()).sum()
+    } else {
+        grapheme.width()
+    }
+}
+
+/// `s` cut to at most `width` columns.
+pub fn clip(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (at, grapheme) in s.grapheme_indices(true) {
+        used += grapheme_width(grapheme);
+        if used > width {
+            return &s[..at];
+        }
+    }
+    s
+}
+
+/// `message` without trailing whitespace on its lines, as git's whitespace cleanup leaves it,
+/// and with tabs turned into spaces up to the next stop of `TAB_WIDTH` when `expand_tabs`.
+pub fn tidy_whitespace(message: &str, expand_tabs: bool) -> String {
+    message
+        .lines()
+        .map(|line| {
+            if !expand_tabs || !line.contains('\t') {
+                return line.trim_end().to_string();
+            }
+            let mut expanded = String::new();
+            let mut column = 0;
+            for grapheme in line.graphemes(true) {
+                if grapheme == "\t" {
+                    let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                    expanded.push_str(&" ".repeat(spaces));
+                    column += spaces;
+                } else {
+                    expanded.push_str(grapheme);
+                    column += grapheme_width(grapheme);
+                }
+            }
+            expanded.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Byte range of the first part of `s` that folds to `needle`, which is folded already.
+pub fn find_folded(s: &str, needle: &str) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    let mut folded = String::new();
+    // the bytes of `s` each folded char comes from
+    let mut origins: Vec<Range<usize>> = Vec::new();
+    for (at, c) in s.char_indices() {
+        let end = at + c.len_utf8();
+        let before = folded.len();
+        folded.extend(fold_char(c));
+        if folded.len() == before {
+            // a dropped mark belongs to the char before it
+            if let Some(last) = origins.last_mut() {
+                last.end = end;
+            }
+        }
+        for _ in folded[before..].chars() {
+            origins.push(at..end);
+        }
+    }
+
+    let start = folded[..folded.find(needle)?].chars().count();
+    let last = start + needle.chars().count() - 1;
+    Some(origins[start].start..origins[last].end)
+}
+
+/// The text being edited inline, with the grapheme boundaries kept alongside so an
+/// edit only re-segments the few graphemes around it instead of the whole text.
+#[derive(Debug)]
+pub struct TextBuffer {
+    text: String,
+    /// Byte offset where each grapheme starts, followed by `text.len()`.
+    bounds: Vec<usize>,
+    /// Grapheme index of the cursor.
+    cursor: usize,
+}
+
+impl TextBuffer {
+    /// `cursor` is a grapheme offset into `text`; the cursor starts at the end when it is `None`.
+    pub fn new(text: &str, cursor: Option<usize>) -> Self {
+        let mut bounds: Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+        bounds.push(text.len());
+        let len = bounds.len() - 1;
+        TextBuffer {
+            text: text.to_string(),
+            bounds,
+            cursor: cursor.map_or(len, |cursor| cursor.min(len)),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte offset of the cursor.
+    pub fn cursor_byte(&self) -> usize {
+        self.bounds[self.cursor]
+    }
+
+    /// Byte offset in the text of `row`, one of the rows from [`TextBuffer::wrap`].
+    pub fn offset_of(&self, row: &str) -> usize {
+        row.as_ptr() as usize - self.text.as_ptr() as usize
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// Number of graphemes.
+    pub fn len(&self) -> usize {
+        self.bounds.len() - 1
+    }
+
+    /// Lays the text out in rows at most `width` columns wide: every line starts a row and
+    /// wraps before the grapheme that would overflow it. Returns the rows with the row and
+    /// column of the cursor.
+    pub fn wrap(&self, width: usize) -> (Vec<&str>, (usize, usize)) {
+        let cursor_byte = self.bounds[self.cursor];
+        let mut rows = Vec::new();
+        let mut cursor = (0, 0);
+        let mut line_start = 0;
+
+        for line in self.text.split('\n') {
+            let (mut row_start, mut column) = (line_start, 0);
+            for (offset, grapheme) in line.grapheme_indices(true) {
+                let at = line_start + offset;
+                let columns = grapheme_width(grapheme);
+                if column > 0 && column + columns > width {
+                    rows.push(&self.text[row_start..at]);
+                    (row_start, column) = (at, 0);
+                }
+                if at == cursor_byte {
+                    cursor = (rows.len(), column);
+                }
+                column += columns;
+            }
+
+            let line_end = line_start + line.len();
+            if line_end == cursor_byte {
+                // a cursor after a full row goes to the start of the next
+                if column >= width {
+                    rows.push(&self.text[row_start..line_end]);
+                    (row_start, column) = (line_end, 0);
+                }
+                cursor = (rows.len(), column);
+            }
+            rows.push(&self.text[row_start..line_end]);
+            line_start = line_end + 1;
+        }
+        (rows, cursor)
+    }
+
+    pub fn insert(&mut self, s: &str) {
+        let at = self.bounds[self.cursor] + s.len();
+        self.replace(self.cursor, self.cursor, s);
+        self.cursor = self.bounds.partition_point(|&b| b < at);
+    }
+
+    pub fn delete_back(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.replace(self.cursor, self.cursor + 1, "");
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        if self.cursor < self.len() {
+            self.replace(self.cursor, self.cursor + 1, "");
+        }
+    }
+
+    /// Deletes the word before the cursor and the whitespace between them.
+    pub fn delete_word(&mut self) {
+        let start = self.word_start();
+        self.replace(start, self.cursor, "");
+        self.cursor = start;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    /// Moves the cursor `rows` rows down, or up when negative, in the text wrapped to
+    /// `width`, staying as close to its column as the row allows.
+    pub fn move_rows(&mut self, rows: isize, width: usize) {
+        let (wrapped, (row, column)) = self.wrap(width);
+        let target = match row.checked_add_signed(rows) {
+            Some(target) if target < wrapped.len() => wrapped[target],
+            _ => return,
+        };
+
+        let mut at = target.as_ptr() as usize - self.text.as_ptr() as usize;
+        let mut target_column = 0;
+        for grapheme in target.graphemes(true) {
+            if target_column + grapheme_width(grapheme) > column {
+                break;
+            }
+            target_column += grapheme_width(grapheme);
+            at += grapheme.len();
+        }
+        self.cursor = self.bounds.partition_point(|&b| b < at);
+    }
+
+    fn grapheme(&self, index: usize) -> &str {
+        &self.text[self.bounds[index]..self.bounds[index + 1]]
+    }
+
+    /// Where the word before the cursor starts, skipping the whitespace right before it.
+    fn word_start(&self) -> usize {
+        let is_space = |index: usize| self.grapheme(index).chars().all(char::is_whitespace);
+        let mut start = self.cursor;
+        while start > 0 && is_space(start - 1) {
+            start -= 1;
+        }
+        while start > 0 && !is_space(start - 1) {
+            start -= 1;
+        }
+        start
+    }
+
+    /// Replaces graphemes `from..to` with `with`. Only the text around the edit is
+    /// segmented again, up to the first boundary that lines up with the old ones.
+    fn replace(&mut self, from: usize, to: usize, with: &str) {
+        let (start, end) = (self.bounds[from], self.bounds[to]);
+        self.text.replace_range(start..end, with);
+        let shift = |bound: usize| bound + with.len() - (end - start);
+
+        // the grapheme before the edit may merge with what was inserted
+        let first = from.saturating_sub(1);
+        let segment_start = self.bounds[first];
+        let mut old = to;
+        let mut segmented = Vec::new();
+        let mut synced_at = None;
+        for (offset, _) in self.text[segment_start..].grapheme_indices(true) {
+            let bound = segment_start + offset;
+            while old < self.bounds.len() && shift(self.bounds[old]) < bound {
+                old += 1;
+            }
+            if bound > start + with.len()
+                && old < self.bounds.len()
+                && shift(self.bounds[old]) == bound
+            {
+                synced_at = Some(old);
+                break;
+            }
+            segmented.push(bound);
+        }
+
+        let tail: Vec<usize> = match synced_at {
+            Some(old) => self.bounds[old..].iter().map(|&b| shift(b)).collect(),
+            None => vec![self.text.len()],
+        };
+        self.bounds.truncate(first);
+        self.bounds.extend(segmented);
+        self.bounds.extend(tail);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_segmented(buffer: &TextBuffer) {
+        assert_eq!(
+            buffer.bounds,
+            TextBuffer::new(buffer.as_str(), None).bounds,
+            "{:?}",
+            buffer.as_str()
+        );
+    }
+
+    #[test]
+    fn should_keep_graphemes_in_sync_while_editing() {
+        let mut buffer = TextBuffer::new("feat: add ログイン", Some(6));
+        buffer.insert("x");
+        buffer.insert("y");
+        assert_eq!(buffer.as_str(), "feat: xyadd ログイン");
+        assert_eq!(buffer.wrap(80).1, (0, 8));
+        assert_segmented(&buffer);
+
+        // a combining accent joins the grapheme before it
+        buffer.move_end();
+        buffer.insert("e");
+        buffer.insert("\u{301}");
+        assert_eq!(buffer.len(), 17);
+        assert_eq!(buffer.wrap(80).1, (0, 21));
+        assert_segmented(&buffer);
+
+        buffer.delete_back();
+        assert_eq!(buffer.as_str(), "feat: xyadd ログイン");
+        buffer.move_home();
+        buffer.delete_forward();
+        assert_eq!(buffer.as_str(), "eat: xyadd ログイン");
+        assert_segmented(&buffer);
+    }
+
+    #[test]
+    fn should_wrap_lines_to_the_width() {
+        let buffer = TextBuffer::new("feat: ログイン\n\nbody", Some(8));
+        assert_eq!(
+            buffer.wrap(7),
+            (vec!["feat: ", "ログイ", "ン", "", "body"], (1, 4))
+        );
+
+        let mut buffer = buffer;
+        buffer.move_rows(1, 7);
+        assert_eq!(buffer.wrap(7).1, (2, 2));
+        buffer.move_rows(2, 7);
+        assert_eq!(buffer.wrap(7).1, (4, 2));
+        buffer.move_rows(-4, 7);
+        assert_eq!(buffer.wrap(7).1, (0, 2));
+
+        let buffer = TextBuffer::new("abcd", None);
+        assert_eq!(buffer.wrap(4), (vec!["abcd", ""], (1, 0)));
+        assert_eq!(buffer.wrap(5), (vec!["abcd"], (0, 4)));
+    }
+
+    #[test]
+    fn should_fold_case_and_diacritics() {
+        assert_eq!(fold("FEAT"), "feat");
+        assert_eq!(fold("Fëat"), "feat");
+        assert_eq!(fold("Fe\u{301}at"), "feat");
+        assert_eq!(fold("ログイン"), "ログイン");
+
+        assert_eq!(find_folded("refactor", "fact"), Some(2..6));
+        assert_eq!(find_folded("Fe\u{301}at", "fe"), Some(0..4));
+        assert_eq!(find_folded("feat", ""), None);
+    }
+
+    #[test]
+    fn should_measure_emoji_as_the_terminal_draws_them() {
+        // (grapheme, unicode width, legacy width)
+        let cases = [
+            ("a", 1, 1),
+            ("ロ", 2, 2),
+            ("e\u{301}", 1, 1),
+            ("✨", 2, 2),
+            // text-default symbol made an emoji by a variation selector
+            ("♻️", 2, 1),
+            ("❤️", 2, 1),
+            // and kept as text
+            ("♻\u{fe0e}", 1, 1),
+            ("👩\u{200d}💻", 2, 4),
+            ("🇯🇵", 2, 2),
+            ("1️⃣", 2, 1),
+            ("👍🏽", 2, 4),
+        ];
+        for (grapheme, unicode, legacy) in cases {
+            assert_eq!(grapheme.graphemes(true).count(), 1, "{}", grapheme);
+            assert_eq!(measure(grapheme, false), unicode, "{}", grapheme);
+            assert_eq!(measure(grapheme, true), legacy, "{}", grapheme);
+        }
+        assert_eq!(width("refactor ♻️"), 11);
+    }
+
+    #[test]
+    fn should_tidy_trailing_whitespace_and_tabs() {
+        let message = "fix: crash  \n\t\nif x:\n\treturn\nログ\tid \t";
+        assert_eq!(
+            tidy_whitespace(message, false),
+            "fix: crash\n\nif x:\n\treturn\nログ\tid"
+        );
+        assert_eq!(
+            tidy_whitespace(message, true),
+            "fix: crash\n\nif x:\n        return\nログ    id"
+        );
+    }
+
+    #[test]
+    fn should_delete_the_previous_word() {
+        let mut buffer = TextBuffer::new("feat: add  ログイン", None);
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "feat: add  ");
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "feat: ");
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "");
+        buffer.delete_word();
+        assert_eq!(buffer.as_str(), "");
+    }
+}
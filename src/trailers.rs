@@ -0,0 +1,99 @@
+use crate::config::Config;
+use crate::keymap::{Action, Screen};
+use crate::term::enable_raw_mode;
+use crate::{ask_line, draw_progress, draw_status_line, finalize, glyph, show_help, GczError};
+use crossterm::{
+    cursor,
+    event::{self, Event},
+    execute,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+
+/// Lets the user pick any of `trailer_presets`, asking for the `{}` of each picked one.
+/// A preset whose value is left empty is dropped.
+pub fn choose(stdout: &mut io::Stdout, config: &Config) -> Result<Vec<String>, GczError> {
+    let presets = &config.trailer_presets;
+    let mut checked = vec![false; presets.len()];
+    let mut selected = 0;
+
+    enable_raw_mode()?;
+    loop {
+        execute!(
+            stdout,
+            cursor::Hide,
+            Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        draw_progress(stdout, Screen::Trailers)?;
+        execute!(stdout, Print("Add trailers:"), cursor::MoveToNextLine(1))?;
+        for (i, preset) in presets.iter().enumerate() {
+            let mark = if checked[i] { "x" } else { " " };
+            if i == selected {
+                execute!(
+                    stdout,
+                    SetForegroundColor(Color::Green),
+                    Print(format!("{} [{}] {}", glyph::get().pointer, mark, preset)),
+                    SetForegroundColor(Color::Reset),
+                    cursor::MoveToNextLine(1)
+                )?;
+            } else {
+                execute!(
+                    stdout,
+                    Print(format!("  [{}] {}", mark, preset)),
+                    cursor::MoveToNextLine(1)
+                )?;
+            }
+        }
+        draw_status_line(stdout, &config.keymap, Screen::Trailers)?;
+        stdout.flush()?;
+
+        if let Event::Key(key_event) = event::read()? {
+            match config.keymap.action(Screen::Trailers, &key_event) {
+                Some(Action::Abort) => return Err(GczError::UserInterrupt),
+                Some(Action::Up) => selected = (selected + presets.len() - 1) % presets.len(),
+                Some(Action::Down) => selected = (selected + 1) % presets.len(),
+                Some(Action::ToggleFile) => checked[selected] = !checked[selected],
+                Some(Action::Confirm) => break finalize((), stdout)?,
+                Some(Action::Help) => show_help(stdout, &config.keymap, &[Screen::Trailers])?,
+                _ => {}
+            }
+        }
+    }
+
+    let mut trailers = Vec::new();
+    for (preset, _) in presets.iter().zip(checked).filter(|(_, checked)| *checked) {
+        if !preset.contains("{}") {
+            trailers.push(preset.clone());
+            continue;
+        }
+        execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        let value = ask_line(
+            stdout,
+            &format!("{} (leave empty to skip):", preset),
+            &config.keymap,
+        )?;
+        if let Some(trailer) = fill(preset, &value) {
+            trailers.push(trailer);
+        }
+    }
+    Ok(trailers)
+}
+
+/// `preset` with its `{}` replaced by `value`, or `None` when `value` is empty.
+fn fill(preset: &str, value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| preset.replacen("{}", value, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_fill_the_preset_value() {
+        assert_eq!(fill("Refs: #{}", " 123 ").as_deref(), Some("Refs: #123"));
+        assert_eq!(fill("Refs: #{}", ""), None);
+    }
+}
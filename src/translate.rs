@@ -0,0 +1,200 @@
+use crate::config::Config;
+use crate::keymap::{Action, Keymap, Screen};
+use crate::term::enable_raw_mode;
+use crate::text::{self, TextBuffer};
+use crate::{
+    conventional, draw_status_line, finalize, git, glyph, join_message, show_help, terminal_size,
+    GczError,
+};
+use crossterm::{
+    cursor,
+    event::{self, Event},
+    execute,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+use std::process::Command;
+
+/// What became of the message offered for translation.
+pub enum Offer {
+    /// The translation replaced the message.
+    Translated,
+    /// The translation replaced the message and should be edited before committing.
+    Edit,
+    Kept,
+    /// `translate_command` failed for this reason; the message is unchanged.
+    Failed(String),
+}
+
+/// Runs the description and body through `translate_command` and shows the result next to
+/// the original, replacing `header` and `body` when the translation is taken. The type and
+/// scope stay as they are.
+pub fn offer(
+    stdout: &mut io::Stdout,
+    config: &Config,
+    header: &mut String,
+    body: &mut String,
+) -> Result<Offer, GczError> {
+    let Some(command) = config.translate_command.as_deref() else {
+        return Ok(Offer::Kept);
+    };
+    let (prefix, description) = conventional::split_prefix(header);
+    let original = join_message(description, body);
+    let translated = match translate(command, &original) {
+        Ok(translated) => translated,
+        Err(reason) => return Ok(Offer::Failed(reason)),
+    };
+    if translated == original.trim() {
+        return Ok(Offer::Kept);
+    }
+    let (new_description, new_body) = conventional::split_message(&translated);
+    let new_header = format!("{}{}", prefix, new_description.trim());
+
+    let options = [
+        "Use the translation".to_string(),
+        "Edit the translation".to_string(),
+        "Keep the original".to_string(),
+    ];
+    enable_raw_mode()?;
+    execute!(stdout, cursor::Hide)?;
+    let choice = choose(
+        stdout,
+        &join_message(header, body),
+        &join_message(&new_header, new_body),
+        &options,
+        &config.keymap,
+    )
+    .and_then(|choice| finalize(choice, stdout))?;
+    if choice == 2 {
+        return Ok(Offer::Kept);
+    }
+    *header = new_header;
+    *body = new_body.to_string();
+    Ok(if choice == 0 {
+        Offer::Translated
+    } else {
+        Offer::Edit
+    })
+}
+
+/// Pipes `text` through `command`, run by the shell so it can hold arguments and pipes.
+fn translate(command: &str, text: &str) -> Result<String, String> {
+    let output = git::run_with_input(
+        Command::new("sh").arg("-c").arg(command),
+        text.as_bytes().to_vec(),
+        "Translating",
+    )
+    .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        let mut reason = match output.status.code() {
+            Some(code) => format!("`{}` exited with code {}", command, code),
+            None => format!("`{}` was killed by a signal", command),
+        };
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stderr.trim().is_empty() {
+            reason = format!("{}: {}", reason, stderr.trim());
+        }
+        return Err(reason);
+    }
+    let translated = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if translated.is_empty() {
+        return Err(format!("`{}` printed nothing", command));
+    }
+    Ok(translated)
+}
+
+fn choose(
+    stdout: &mut io::Stdout,
+    original: &str,
+    translation: &str,
+    options: &[String],
+    keymap: &Keymap,
+) -> Result<usize, GczError> {
+    let mut selected = 0;
+    loop {
+        render(stdout, original, translation, options, selected, keymap)?;
+        let key_event = match event::read()? {
+            Event::Key(key_event) => key_event,
+            _ => continue,
+        };
+        match keymap.action(Screen::Menu, &key_event) {
+            Some(Action::Abort) => return Err(GczError::UserInterrupt),
+            Some(Action::Help) => show_help(stdout, keymap, &[Screen::Menu])?,
+            Some(Action::Up) | Some(Action::Left) => {
+                selected = (selected + options.len() - 1) % options.len()
+            }
+            Some(Action::Down) => selected = (selected + 1) % options.len(),
+            Some(Action::Confirm) => return Ok(selected),
+            _ => {}
+        }
+    }
+}
+
+fn render(
+    stdout: &mut io::Stdout,
+    original: &str,
+    translation: &str,
+    options: &[String],
+    selected: usize,
+    keymap: &Keymap,
+) -> Result<(), GczError> {
+    let (width, _) = terminal_size()?;
+    let column = (width.saturating_sub(3) / 2).max(10);
+    let original = TextBuffer::new(original, None);
+    let translation = TextBuffer::new(translation, None);
+    let (left, _) = original.wrap(column);
+    let (right, _) = translation.wrap(column);
+
+    execute!(
+        stdout,
+        Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        Print(format!(
+            "Commit the translation? ({}: help)",
+            keymap.describe(Screen::Menu, Action::Help)
+        )),
+        cursor::MoveToNextLine(2),
+        SetForegroundColor(Color::DarkGrey),
+        Print(format!("{:<column$} │ Translation", "Original")),
+        SetForegroundColor(Color::Reset),
+        cursor::MoveToNextLine(1)
+    )?;
+    for row in 0..left.len().max(right.len()) {
+        let left = left.get(row).copied().unwrap_or_default();
+        let right = right.get(row).copied().unwrap_or_default();
+        execute!(
+            stdout,
+            Print(left),
+            Print(" ".repeat(column.saturating_sub(text::width(left)))),
+            SetForegroundColor(Color::DarkGrey),
+            Print(" │ "),
+            SetForegroundColor(Color::Cyan),
+            Print(right),
+            SetForegroundColor(Color::Reset),
+            cursor::MoveToNextLine(1)
+        )?;
+    }
+
+    execute!(stdout, cursor::MoveToNextLine(1))?;
+    for (i, option) in options.iter().enumerate() {
+        if i == selected {
+            execute!(
+                stdout,
+                SetForegroundColor(Color::Green),
+                Print(format!("{} {}", glyph::get().pointer, option)),
+                SetForegroundColor(Color::Reset),
+                cursor::MoveToNextLine(1)
+            )?;
+        } else {
+            execute!(
+                stdout,
+                Print(format!("  {}", option)),
+                cursor::MoveToNextLine(1)
+            )?;
+        }
+    }
+    draw_status_line(stdout, keymap, Screen::Menu)?;
+    stdout.flush()?;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+use crate::config::Config;
+use crate::{conventional, draft, git, GczError};
+use serde_json::{json, Map, Value};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::PathBuf;
+
+/// What gcz has been used for in this repository, kept in `.git/GCZ_USAGE` for `gcz stats`
+/// and `sort_types_by_use`. It never leaves the repository.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Commits per type, the most used first.
+    types: Vec<(String, usize)>,
+    commits: usize,
+    /// Characters in all the subjects committed, for the average.
+    subject_length: usize,
+    /// Runs of the wizard that ended with Ctrl+C.
+    aborted: usize,
+}
+
+impl Usage {
+    /// The counts so far; missing or unreadable ones start again from zero.
+    pub fn load() -> Usage {
+        path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .map(|json| Usage::from_json(&json))
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), GczError> {
+        draft::write_atomically(&path()?, &self.to_json().to_string())?;
+        Ok(())
+    }
+
+    fn from_json(json: &Value) -> Usage {
+        let count = |key: &str| json[key].as_u64().unwrap_or(0) as usize;
+        let mut types: Vec<(String, usize)> = json["types"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, count)| Some((name.clone(), count.as_u64()? as usize)))
+            .collect();
+        types.sort_by_key(|(_, count)| Reverse(*count));
+        Usage {
+            types,
+            commits: count("commits"),
+            subject_length: count("subject_length"),
+            aborted: count("aborted"),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let types: Map<String, Value> = self
+            .types
+            .iter()
+            .map(|(name, count)| (name.clone(), json!(count)))
+            .collect();
+        json!({
+            "types": types,
+            "commits": self.commits,
+            "subject_length": self.subject_length,
+            "aborted": self.aborted,
+        })
+    }
+
+    fn add_commit(&mut self, message: &str) {
+        let subject = message.lines().next().unwrap_or_default();
+        self.commits += 1;
+        self.subject_length += subject.chars().count();
+        let Ok(header) = conventional::parse_header(subject) else {
+            return;
+        };
+        match self.types.iter_mut().find(|(t, _)| t == header.commit_type) {
+            Some((_, count)) => *count += 1,
+            None => self.types.push((header.commit_type.to_string(), 1)),
+        }
+        self.types.sort_by_key(|(_, count)| Reverse(*count));
+    }
+
+    /// `types` with the most used first; the rest keep their order.
+    pub fn sort(&self, types: &[String]) -> Vec<String> {
+        let mut sorted = types.to_vec();
+        sorted.sort_by_key(|t| {
+            let used = self.types.iter().find(|(name, _)| name == t);
+            Reverse(used.map_or(0, |(_, count)| *count))
+        });
+        sorted
+    }
+}
+
+fn path() -> Result<PathBuf, GczError> {
+    git::git_path("GCZ_USAGE")
+}
+
+/// Counts a commit made with `message`, unless `record_usage` is off. A failure to write
+/// is ignored: the commit is made.
+pub fn record_commit(config: &Config, message: &str) {
+    if config.record_usage {
+        let mut usage = Usage::load();
+        usage.add_commit(message);
+        let _ = usage.save();
+    }
+}
+
+/// Counts a wizard aborted with Ctrl+C, unless `record_usage` is off.
+pub fn record_abort(config: &Config) {
+    if config.record_usage {
+        let mut usage = Usage::load();
+        usage.aborted += 1;
+        let _ = usage.save();
+    }
+}
+
+/// Entry point of `gcz stats`.
+pub fn print(config: &Config) -> Result<(), GczError> {
+    let usage = Usage::load();
+    if !config.record_usage {
+        println!("Recording is off (record_usage = false)");
+    }
+    if usage.commits == 0 && usage.aborted == 0 {
+        println!("No commits made with gcz in this repository yet");
+        return Ok(());
+    }
+    println!("Commits: {}", usage.commits);
+    println!("Aborted runs: {}", usage.aborted);
+    if let Some(average) = usage.subject_length.checked_div(usage.commits) {
+        println!("Average subject length: {} characters", average);
+    }
+    if !usage.types.is_empty() {
+        println!("Types:");
+        let width = usage.types.iter().map(|(t, _)| t.len()).max().unwrap_or(0);
+        for (name, count) in &usage.types {
+            println!(
+                "  {:<width$}  {:>4}  {:>3}%",
+                name,
+                count,
+                count * 100 / usage.commits.max(1)
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_commits_and_sort_types_by_use() {
+        let mut usage = Usage::default();
+        usage.add_commit("fix: typo");
+        usage.add_commit("feat(api): add endpoint\n\nWith tests.");
+        usage.add_commit("fix: crash on empty input");
+        usage.add_commit("Merge branch 'main'");
+
+        assert_eq!(usage.commits, 4);
+        assert_eq!(
+            usage.types,
+            [("fix".to_string(), 2), ("feat".to_string(), 1)]
+        );
+        let types: Vec<String> = ["feat", "docs", "fix"].map(String::from).into();
+        assert_eq!(usage.sort(&types), ["fix", "feat", "docs"]);
+        assert_eq!(Usage::from_json(&usage.to_json()), usage);
+    }
+}
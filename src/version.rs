@@ -0,0 +1,18 @@
+use crate::{doctor, git};
+
+/// What `gcz --version` prints: the version, the commit and target it was built for, and
+/// the git it runs, for bug reports.
+pub fn report() -> String {
+    let git = match (doctor::find_on_path("git"), git::version()) {
+        (Some(path), Ok(version)) => format!("{} ({})", path.display(), version),
+        (None, Ok(version)) => version,
+        (_, Err(err)) => format!("not usable: {}", err),
+    };
+    format!(
+        "gcz {}\ncommit: {}\ntarget: {}\ngit: {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("GCZ_BUILD_COMMIT"),
+        env!("GCZ_TARGET"),
+        git
+    )
+}